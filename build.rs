@@ -26,4 +26,24 @@ fn main() {
         &["src/widgets/split_panel"], 
         "src/widgets/split_panel/sptable.gresource.xml", 
         "sptable.gresource");
+
+    glib_build_tools::compile_resources(
+        &["src/widgets/audio_mixer"], 
+        "src/widgets/audio_mixer/mixerpanel.gresource.xml", 
+        "mixerpanel.gresource");
+
+    glib_build_tools::compile_resources(
+        &["src/widgets/nudge_panel"], 
+        "src/widgets/nudge_panel/nudgepanel.gresource.xml", 
+        "nudgepanel.gresource");
+
+    glib_build_tools::compile_resources(
+        &["src/widgets/log_console"], 
+        "src/widgets/log_console/logconsole.gresource.xml", 
+        "logconsole.gresource");
+
+    glib_build_tools::compile_resources(
+        &["src/widgets/diagnostics"], 
+        "src/widgets/diagnostics/diagnosticswindow.gresource.xml", 
+        "diagnosticswindow.gresource");
 }
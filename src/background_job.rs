@@ -0,0 +1,81 @@
+use gstreamer::{prelude::*, ClockTime, MessageView};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+//Result of the pipelines run by background jobs
+pub type JobResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+//Data a background job fills in while widgets read it from the main thread
+pub trait BackgroundJob: Send + 'static {
+    //Checks if whoever started the job no longer wants it
+    fn is_cancelled(&self) -> bool;
+    //Records the error the job stopped on
+    fn fail(&mut self, _error: &str) {}
+    //Marks the job as stopped, whether it finished, failed or was cancelled
+    fn complete(&mut self);
+}
+
+//Runs a job on a background thread
+//Inputs: what the job does for error messages, data the job fills in, job run on that data
+//Output: the data, shared with the job
+pub fn spawn_job<T, F>(description: String, data: T, run: F) -> Arc<Mutex<T>>
+where
+    T: BackgroundJob,
+    F: FnOnce(&Arc<Mutex<T>>) -> JobResult + Send + 'static,
+{
+    let data = Arc::new(Mutex::new(data));
+    let data_clone = data.clone();
+    thread::spawn(move || {
+        let result = run(&data_clone);
+        let mut data = data_clone.lock().unwrap();
+        if let Err(e) = result {
+            log_error!("Failed to {description}: {e}");
+            data.fail(&e.to_string());
+        }
+        data.complete();
+    });
+    data
+}
+
+//Links the first decoded stream of a media type to an element
+//Input: bin adding a pad for each stream, element taking the stream, start of the media type such as "video/"
+pub fn link_first_stream(bin: &gstreamer::Element, target: &gstreamer::Element, media_type: &'static str) {
+    let target_weak = target.downgrade();
+    bin.connect_pad_added(move |_, src_pad| {
+        let target = match target_weak.upgrade() {
+            Some(t) => t,
+            None => return,
+        };
+        let matches = src_pad.current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().starts_with(media_type)))
+            .unwrap_or(false);
+        if !matches {
+            return;
+        }
+        if let Some(sink_pad) = target.static_pad("sink") {
+            if !sink_pad.is_linked() {
+                let _ = src_pad.link(&sink_pad);
+            }
+        }
+    });
+}
+
+//Waits for a playing pipeline to reach the end of the stream
+//Inputs: pipeline, data of the job running it, called every time the bus is checked
+//Output: whether the end of the stream was reached, false when the job was cancelled
+pub fn wait_for_end<T: BackgroundJob>(pipeline: &gstreamer::Pipeline, data: &Arc<Mutex<T>>, mut on_wait: impl FnMut()) -> JobResult<bool> {
+    let bus = pipeline.bus().ok_or("Pipeline has no bus")?;
+    loop {
+        if data.lock().unwrap().is_cancelled() {
+            return Ok(false);
+        }
+        if let Some(message) = bus.timed_pop(ClockTime::from_mseconds(200)) {
+            match message.view() {
+                MessageView::Eos(..) => return Ok(true),
+                MessageView::Error(err) => return Err(err.error().to_string().into()),
+                _ => {}
+            }
+        }
+        on_wait();
+    }
+}
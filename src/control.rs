@@ -0,0 +1,472 @@
+use gtk::prelude::*;
+use gtk::glib;
+use std::path::Path;
+use crate::error::Error;
+use crate::helpers::data::store_data;
+use crate::helpers::json::JsonValue;
+use crate::helpers::parse::string_to_nseconds;
+use crate::helpers::ui::flowbox_children;
+use crate::widgets::split_panel::splits::{Annotation, VideoSegment};
+use crate::widgets::video_player_widget::video_player::VideoPlayer;
+use crate::{add_video_player, insert_row, AppContext};
+
+//Port to listen on, the control API is off unless it's set
+const PORT_VARIABLE: &str = "MVTAT_CONTROL_PORT";
+//Largest request accepted, headers included
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+const READ_CHUNK_SIZE: usize = 4096;
+//Connections that haven't sent a whole request by then are closed
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+//JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+//Command understood but the application could not carry it out
+const COMMAND_FAILED: i64 = -32000;
+
+//A command that failed, answered as a JSON-RPC error
+struct CallError {
+    code: i64,
+    message: String,
+}
+
+impl From<Error> for CallError {
+    fn from(error: Error) -> Self {
+        CallError { code: COMMAND_FAILED, message: error.message().to_string() }
+    }
+}
+
+fn invalid_params(message: impl Into<String>) -> CallError {
+    CallError { code: INVALID_PARAMS, message: message.into() }
+}
+
+struct HttpRequest {
+    method: String,
+    //Header names are lower case
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+// Control API:
+// Lets scripts and stream decks drive the application with JSON-RPC 2.0 requests POSTed to http://127.0.0.1:<port>
+// Every command goes through the same functions as the buttons in the main window
+pub fn start(context: &AppContext) {
+    let port = match std::env::var(PORT_VARIABLE) {
+        Ok(port) => port,
+        Err(_) => return,
+    };
+    let port: u16 = match port.parse() {
+        Ok(port) => port,
+        Err(_) => {
+            log_warn!("{PORT_VARIABLE} must be a port number, not \"{port}\", the control API is off");
+            return;
+        }
+    };
+
+    //Only reachable from this machine
+    let service = gio::SocketService::new();
+    let address = gio::InetSocketAddress::from_string("127.0.0.1", port as u32)
+        .expect("Loopback address is valid");
+    if let Err(e) = service.add_address(&address, gio::SocketType::Stream, gio::SocketProtocol::Tcp, None::<&glib::Object>) {
+        log_error!("Failed to start the control API on port {port}: {e}");
+        return;
+    }
+
+    let context_clone = context.clone();
+    service.connect_incoming(move |_, connection, _| {
+        let context = context_clone.clone();
+        let connection = connection.clone();
+        glib::MainContext::default().spawn_local(async move {
+            if let Err(e) = handle_connection(&context, &connection).await {
+                log_warn!("Control API: {e}");
+            }
+        });
+        false
+    });
+    service.start();
+    //Keeps the service listening for as long as the window exists
+    store_data(&context.window, "control_service", service);
+    log_info!("Control API listening on http://127.0.0.1:{port}");
+}
+
+//Answers a single request on a connection and closes it
+async fn handle_connection(context: &AppContext, connection: &gio::SocketConnection) -> Result<(), Error> {
+    let (status, body) = match glib::future_with_timeout(REQUEST_TIMEOUT, read_request(connection.clone())).await {
+        Ok(Ok(request)) => handle_request(context, &request),
+        //Nothing can be answered on a connection that failed
+        Ok(Err(e @ Error::Io(_))) => return Err(e),
+        Ok(Err(e)) => ("400 Bad Request", JsonValue::object(vec![("error", JsonValue::from(e.message()))])),
+        Err(_) => ("408 Request Timeout", JsonValue::object(vec![("error", JsonValue::from("Request not received in time"))])),
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    connection.output_stream()
+        .write_all_future(response.into_bytes(), glib::Priority::default())
+        .await
+        .map_err(|(_, e)| Error::Io(format!("Failed to send response: {e}")))?;
+    connection.close_future(glib::Priority::default())
+        .await
+        .map_err(|e| Error::Io(format!("Failed to close connection: {e}")))
+}
+
+//Reads an HTTP request from a connection
+async fn read_request(connection: gio::SocketConnection) -> Result<HttpRequest, Error> {
+    let input = connection.input_stream();
+    let mut data = Vec::new();
+    loop {
+        if let Some(request) = parse_request(&data)? {
+            return Ok(request);
+        }
+        let (buffer, read) = input.read_future(vec![0u8; READ_CHUNK_SIZE], glib::Priority::default())
+            .await
+            .map_err(|(_, e)| Error::Io(format!("Failed to read request: {e}")))?;
+        if read == 0 {
+            return Err(Error::Io("Connection closed before the request was complete".to_string()));
+        }
+        data.extend_from_slice(&buffer[..read]);
+    }
+}
+
+//Parses the request line, headers and body of an HTTP request from the bytes read so far
+//Output: the request, None while more bytes are needed
+fn parse_request(data: &[u8]) -> Result<Option<HttpRequest>, Error> {
+    let too_large = || Error::Parse("Request too large".to_string());
+    if data.len() > MAX_REQUEST_SIZE {
+        return Err(too_large());
+    }
+    let header_end = match data.windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(position) => position + 4,
+        None => return Ok(None),
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.lines();
+    let method = lines.next()
+        .and_then(|request_line| request_line.split_whitespace().next())
+        .ok_or_else(|| Error::Parse("Missing request line".to_string()))?
+        .to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let content_length = headers.iter()
+        .find(|(name, _)| name == "content-length")
+        .map(|(_, value)| value.parse::<usize>())
+        .transpose()
+        .map_err(|_| Error::Parse("Invalid Content-Length".to_string()))?
+        .unwrap_or(0);
+    //Checked before adding so a huge Content-Length can't overflow
+    if content_length > MAX_REQUEST_SIZE {
+        return Err(too_large());
+    }
+    let request_end = header_end.checked_add(content_length)
+        .filter(|end| *end <= MAX_REQUEST_SIZE)
+        .ok_or_else(too_large)?;
+    if data.len() < request_end {
+        return Ok(None);
+    }
+    Ok(Some(HttpRequest { method, headers, body: data[header_end..request_end].to_vec() }))
+}
+
+//Checks an HTTP request and runs the JSON-RPC call it carries
+//Output: HTTP status and the JSON body to answer with
+fn handle_request(context: &AppContext, request: &HttpRequest) -> (&'static str, JsonValue) {
+    let refuse = |message: &str| JsonValue::object(vec![("error", JsonValue::from(message))]);
+    if request.method != "POST" {
+        return ("405 Method Not Allowed", refuse("Send JSON-RPC requests with POST"));
+    }
+    //Web pages can reach localhost too, anything sent from a browser is refused
+    if request.header("origin").is_some() {
+        return ("403 Forbidden", refuse("Requests from web pages are not accepted"));
+    }
+    if !request.header("content-type").is_some_and(|content_type| content_type.starts_with("application/json")) {
+        return ("415 Unsupported Media Type", refuse("Content-Type must be application/json"));
+    }
+
+    let json = std::str::from_utf8(&request.body)
+        .map_err(|_| Error::Parse("Request body is not UTF-8".to_string()))
+        .and_then(JsonValue::parse);
+    let call = match json {
+        Ok(call) => call,
+        Err(e) => return ("200 OK", rpc_error(JsonValue::Null, PARSE_ERROR, e.message())),
+    };
+    let id = call.get("id").cloned().unwrap_or(JsonValue::Null);
+    let method = match call.get("method").and_then(JsonValue::as_str) {
+        Some(method) if call.get("jsonrpc").and_then(JsonValue::as_str) == Some("2.0") => method,
+        _ => return ("200 OK", rpc_error(id, INVALID_REQUEST, "Expected a JSON-RPC 2.0 request object")),
+    };
+    let params = call.get("params").cloned().unwrap_or(JsonValue::object(Vec::<(String, JsonValue)>::new()));
+
+    log_debug!("Control API call {method}");
+    let response = match run_command(context, method, &params) {
+        Ok(result) => JsonValue::object(vec![
+            ("jsonrpc", JsonValue::from("2.0")),
+            ("result", result),
+            ("id", id),
+        ]),
+        Err(e) => {
+            log_warn!("Control API {method} failed: {}", e.message);
+            rpc_error(id, e.code, &e.message)
+        }
+    };
+    ("200 OK", response)
+}
+
+fn rpc_error(id: JsonValue, code: i64, message: &str) -> JsonValue {
+    JsonValue::object(vec![
+        ("jsonrpc", JsonValue::from("2.0")),
+        ("error", JsonValue::object(vec![
+            ("code", JsonValue::Number(code as f64)),
+            ("message", JsonValue::from(message)),
+        ])),
+        ("id", id),
+    ])
+}
+
+//Runs a command
+//Input: method name and its named parameters
+//Output: result of the command
+fn run_command(context: &AppContext, method: &str, params: &JsonValue) -> Result<JsonValue, CallError> {
+    match method {
+        "add_player" => {
+            let path = get_string(params, "path")?;
+            if !Path::new(path).is_file() {
+                return Err(invalid_params(format!("No file at {path}")));
+            }
+            let video_player_id = add_video_player(context, Some(path))?;
+            Ok(JsonValue::object(vec![("player", JsonValue::from(video_player_id))]))
+        }
+        "add_row" => {
+            let row_count = get_row_count(context);
+            let index = match params.get("row") {
+                Some(row) => row.as_u64().filter(|row| *row <= row_count as u64)
+                    .ok_or_else(|| invalid_params(format!("row must be a number from 0 to {row_count}")))? as u32,
+                None => row_count,
+            };
+            insert_row(context, index);
+            Ok(JsonValue::object(vec![("row", JsonValue::from(index))]))
+        }
+        "set_split" => {
+            let video_player = get_video_player(context, params)?;
+            let row = params.get("row").and_then(JsonValue::as_u64)
+                .ok_or_else(|| invalid_params("row must be a row number starting at 0"))?;
+            let time = get_time(params, "time")?;
+            let segment = context.split_table.get_split_table_liststore()
+                .and_then(|liststore| liststore.item(row as u32))
+                .and_downcast::<VideoSegment>()
+                .ok_or_else(|| invalid_params(format!("Row {row} does not exist")))?;
+            context.split_table.set_split_time(&video_player.get_id(), &segment.get_segment_id(), time)?;
+            Ok(JsonValue::object(vec![("time", JsonValue::from(segment.get_time(&video_player.get_id())))]))
+        }
+        "set_start_offset" => {
+            let video_player = get_video_player(context, params)?;
+            let time = get_time(params, "time")?;
+            context.split_table.set_start_time_offset(&video_player.get_id(), time)?;
+            let offset = context.split_table.get_offset_time_entry(&video_player.get_id()).get_time();
+            Ok(JsonValue::object(vec![("time", JsonValue::from(offset))]))
+        }
+        "toggle_sync" => {
+            let enabled = match params.get("enabled") {
+                Some(enabled) => enabled.as_bool().ok_or_else(|| invalid_params("enabled must be true or false"))?,
+                None => !context.shared_seek_bar.get_control_state(),
+            };
+            if enabled != context.shared_seek_bar.get_control_state() {
+                context.shared_seek_bar.toggle_has_control();
+            }
+            Ok(JsonValue::object(vec![("sync", JsonValue::from(context.shared_seek_bar.get_control_state()))]))
+        }
+        "play" | "pause" => {
+            let play = method == "play";
+            if params.get("player").is_some() {
+                let video_player = get_video_player(context, params)?;
+                if video_player.is_playing() != play {
+                    video_player.toggle_play();
+                }
+            } else if context.shared_seek_bar.get_control_state() {
+                let shared_seek_bar = &context.shared_seek_bar;
+                if play && !shared_seek_bar.is_playing() {
+                    shared_seek_bar.play_all()?;
+                } else if !play && shared_seek_bar.is_playing() {
+                    shared_seek_bar.pause_all()?;
+                }
+            } else {
+                return Err(invalid_params("Give a player when not in the sync view"));
+            }
+            Ok(JsonValue::Null)
+        }
+        "seek" => {
+            let time = get_time(params, "time")?;
+            if params.get("player").is_some() {
+                get_video_player(context, params)?.seek(time)?;
+            } else if context.shared_seek_bar.get_control_state() {
+                let from_segment = params.get("from_segment").and_then(JsonValue::as_bool).unwrap_or(false);
+                context.shared_seek_bar.go_to_time(time, from_segment)?;
+            } else {
+                return Err(invalid_params("Give a player when not in the sync view"));
+            }
+            Ok(JsonValue::Null)
+        }
+        "get_split_table" => Ok(describe_split_table(context)),
+        _ => Err(CallError { code: METHOD_NOT_FOUND, message: format!("Unknown method {method}") }),
+    }
+}
+
+fn get_string<'a>(params: &'a JsonValue, name: &str) -> Result<&'a str, CallError> {
+    params.get(name).and_then(JsonValue::as_str)
+        .ok_or_else(|| invalid_params(format!("{name} must be a string")))
+}
+
+//Reads a time given in nanoseconds or as MM:SS.sss
+fn get_time(params: &JsonValue, name: &str) -> Result<u64, CallError> {
+    match params.get(name) {
+        Some(JsonValue::String(time)) => string_to_nseconds(time).map_err(|e| invalid_params(e.message())),
+        Some(time) => time.as_u64().ok_or_else(|| invalid_params(format!("{name} must be nanoseconds or MM:SS.sss"))),
+        None => Err(invalid_params(format!("{name} is missing"))),
+    }
+}
+
+//Finds the video player named by the "player" parameter
+fn get_video_player(context: &AppContext, params: &JsonValue) -> Result<VideoPlayer, CallError> {
+    let video_player_id = match params.get("player") {
+        Some(JsonValue::String(id)) => id.clone(),
+        Some(id) => id.as_u64().map(|id| id.to_string())
+            .ok_or_else(|| invalid_params("player must be a video player id"))?,
+        None => return Err(invalid_params("player is missing")),
+    };
+    get_video_players(context).into_iter()
+        .find(|video_player| video_player.get_id() == video_player_id)
+        .ok_or_else(|| invalid_params(format!("No video player {video_player_id}")))
+}
+
+fn get_video_players(context: &AppContext) -> Vec<VideoPlayer> {
+    flowbox_children(&context.video_container)
+        .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+        .collect()
+}
+
+fn get_row_count(context: &AppContext) -> u32 {
+    context.split_table.get_split_table_liststore().map_or(0, |liststore| liststore.n_items())
+}
+
+//Describes every video player and split row, times in nanoseconds and null when not recorded
+fn describe_split_table(context: &AppContext) -> JsonValue {
+    let video_players = get_video_players(context);
+    let recorded = |time: u64| if time == u64::MAX { None } else { Some(time) };
+
+    let players: Vec<JsonValue> = video_players.iter().map(|video_player| {
+        let video_player_id = video_player.get_id();
+        let offsets = context.split_table.get_start_time_offset_row_map();
+        let start_offset = offsets.borrow().get(&video_player_id).and_then(|entry| recorded(entry.get_time()));
+        JsonValue::object(vec![
+            ("id", JsonValue::from(video_player_id)),
+            ("color", JsonValue::from(video_player.get_color())),
+            ("file", JsonValue::from(video_player.get_source_path())),
+            ("start_offset", JsonValue::from(start_offset)),
+            ("position", JsonValue::from(video_player.get_position())),
+            ("playing", JsonValue::from(video_player.is_playing())),
+        ])
+    }).collect();
+
+    let liststore = context.split_table.get_split_table_liststore();
+    let rows: Vec<JsonValue> = (0..get_row_count(context))
+        .filter_map(|index| liststore.as_ref()?.item(index).and_downcast::<VideoSegment>())
+        .enumerate()
+        .map(|(index, segment)| {
+            let splits = video_players.iter().map(|video_player| {
+                let video_player_id = video_player.get_id();
+                let split = JsonValue::object(vec![
+                    ("time", JsonValue::from(recorded(segment.get_time(&video_player_id)))),
+                    ("duration", JsonValue::from(segment.get_duration(&video_player_id).and_then(recorded))),
+                    ("comment", describe_annotation(&segment.get_annotation(Some(&video_player_id)))),
+                ]);
+                (video_player_id, split)
+            }).collect();
+            JsonValue::object(vec![
+                ("row", JsonValue::from(index as u32)),
+                ("id", JsonValue::from(segment.get_segment_id())),
+                ("name", JsonValue::from(segment.get_name())),
+                ("note", describe_annotation(&segment.get_annotation(None))),
+                ("splits", JsonValue::Object(splits)),
+            ])
+        })
+        .collect();
+
+    JsonValue::object(vec![
+        ("sync", JsonValue::from(context.shared_seek_bar.get_control_state())),
+        ("players", JsonValue::from(players)),
+        ("rows", JsonValue::from(rows)),
+    ])
+}
+
+fn describe_annotation(annotation: &Annotation) -> JsonValue {
+    JsonValue::object(vec![
+        ("note", JsonValue::from(annotation.note.as_str())),
+        ("tags", JsonValue::from(annotation.tags.clone())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &[u8] = b"POST / HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: 14\r\n\r\n{\"id\":1,\"x\":2}";
+
+    #[test]
+    fn parses_complete_request() {
+        let request = parse_request(REQUEST).unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(request.body, b"{\"id\":1,\"x\":2}");
+    }
+
+    #[test]
+    fn waits_for_split_reads() {
+        for split in 0..REQUEST.len() {
+            assert!(parse_request(&REQUEST[..split]).unwrap().is_none(), "complete after {split} bytes");
+        }
+        assert!(parse_request(REQUEST).unwrap().is_some());
+    }
+
+    #[test]
+    fn ignores_bytes_after_body() {
+        let mut data = REQUEST.to_vec();
+        data.extend_from_slice(b"extra");
+        assert_eq!(parse_request(&data).unwrap().unwrap().body.len(), 14);
+    }
+
+    #[test]
+    fn missing_body_waits_and_missing_length_is_empty() {
+        let headers_only = b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n";
+        assert!(parse_request(headers_only).unwrap().is_none());
+        let no_length = b"POST / HTTP/1.1\r\n\r\n";
+        assert!(parse_request(no_length).unwrap().unwrap().body.is_empty());
+    }
+
+    #[test]
+    fn rejects_oversized_requests() {
+        let huge_length = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", usize::MAX);
+        assert!(parse_request(huge_length.as_bytes()).is_err());
+        let too_long = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_REQUEST_SIZE);
+        assert!(parse_request(too_long.as_bytes()).is_err());
+        let mut too_much = b"POST / HTTP/1.1\r\nContent-Length: 1\r\n\r\n".to_vec();
+        too_much.resize(MAX_REQUEST_SIZE + 1, b'x');
+        assert!(parse_request(&too_much).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_content_length() {
+        assert!(parse_request(b"POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n").is_err());
+        assert!(parse_request(b"\r\n\r\n").is_err());
+    }
+}
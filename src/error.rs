@@ -0,0 +1,54 @@
+use std::fmt;
+
+//Errors reported by the pipeline, synchronized playback, parsing, the split table, connections and remote control
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    //Building, seeking or changing the state of a video pipeline failed
+    Pipeline(String),
+    //Synchronized playback could not be applied to every video
+    Sync(String),
+    //Text such as a time or a log filter could not be read
+    Parse(String),
+    //A split table edit was rejected
+    Table(String),
+    //Reading from or writing to a connection failed
+    Io(String),
+    //A media key or remote control request could not be carried out
+    Remote(String),
+}
+
+impl Error {
+    //Gets the message without the kind of error
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Pipeline(message) | Error::Sync(message) | Error::Parse(message) | Error::Table(message) | Error::Io(message) | Error::Remote(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Pipeline(message) => write!(f, "Pipeline error: {message}"),
+            Error::Sync(message) => write!(f, "Sync error: {message}"),
+            Error::Parse(message) => write!(f, "Invalid input: {message}"),
+            Error::Table(message) => write!(f, "Split table error: {message}"),
+            Error::Io(message) => write!(f, "Connection error: {message}"),
+            Error::Remote(message) => write!(f, "Remote control error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<glib::BoolError> for Error {
+    fn from(error: glib::BoolError) -> Self {
+        Error::Pipeline(error.to_string())
+    }
+}
+
+impl From<gstreamer::StateChangeError> for Error {
+    fn from(error: gstreamer::StateChangeError) -> Self {
+        Error::Pipeline(error.to_string())
+    }
+}
@@ -0,0 +1,256 @@
+use gstreamer::{prelude::*, ClockTime};
+use std::sync::{Arc, Mutex};
+use crate::background_job::{link_first_stream, spawn_job, wait_for_end, BackgroundJob, JobResult};
+
+//How far a frame duration can stray from the typical frame duration before the video counts as variable frame rate
+const VARIABLE_RATE_TOLERANCE: f64 = 0.1;
+
+//Presentation time of every frame of a video, filled in by a background thread
+pub struct FrameIndex {
+    //Sorted presentation times in nanoseconds
+    pub timestamps: Vec<u64>,
+    pub variable_rate: bool,
+    pub complete: bool,
+    pub cancelled: bool,
+}
+
+impl FrameIndex {
+    pub fn new() -> Self {
+        FrameIndex {
+            timestamps: Vec::new(),
+            variable_rate: false,
+            complete: false,
+            cancelled: false,
+        }
+    }
+
+    //Checks if the index can be used to look up frames
+    pub fn is_ready(&self) -> bool {
+        self.complete && !self.cancelled && !self.timestamps.is_empty()
+    }
+
+    //Gets the number of the frame shown at a time
+    //Output: zero based frame number, None if the index isn't ready
+    pub fn frame_at(&self, time: u64) -> Option<u64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let after = self.timestamps.partition_point(|timestamp| *timestamp <= time);
+        Some(after.saturating_sub(1) as u64)
+    }
+
+    //Gets the presentation time of a frame number
+    pub fn frame_time(&self, frame: u64) -> Option<u64> {
+        if !self.is_ready() {
+            return None;
+        }
+        self.timestamps.get(frame as usize).copied()
+    }
+
+    //Gets the start of the frame shown at a time
+    pub fn snap(&self, time: u64) -> Option<u64> {
+        self.frame_at(time).and_then(|frame| self.frame_time(frame))
+    }
+
+    //Gets the presentation time of the frame after the one shown at a time
+    pub fn next_frame_time(&self, time: u64) -> Option<u64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let after = self.timestamps.partition_point(|timestamp| *timestamp <= time);
+        self.timestamps.get(after).copied()
+    }
+
+    //Gets the presentation time of the frame before the one shown at a time
+    pub fn previous_frame_time(&self, time: u64) -> Option<u64> {
+        let frame = self.frame_at(time)?;
+        if frame == 0 {
+            return None;
+        }
+        self.frame_time(frame - 1)
+    }
+
+    //Gets the typical time between two frames
+    pub fn typical_frame_duration(&self) -> Option<u64> {
+        let mut durations: Vec<u64> = self.timestamps.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_unstable();
+        Some(durations[durations.len() / 2])
+    }
+
+    //Sorts the collected timestamps and checks if the frames are evenly spaced
+    fn finish(&mut self) {
+        self.timestamps.sort_unstable();
+        self.timestamps.dedup();
+        let typical = match self.typical_frame_duration() {
+            Some(d) if d > 0 => d as f64,
+            _ => return,
+        };
+        self.variable_rate = self.timestamps.windows(2).any(|pair| {
+            let duration = (pair[1] - pair[0]) as f64;
+            (duration - typical).abs() / typical > VARIABLE_RATE_TOLERANCE
+        });
+    }
+}
+
+impl Default for FrameIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackgroundJob for FrameIndex {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    //A partial index would give wrong frame numbers past the point it stopped
+    fn fail(&mut self, _error: &str) {
+        self.timestamps.clear();
+    }
+
+    fn complete(&mut self) {
+        self.complete = true;
+    }
+}
+
+//Starts indexing the frames of a file in the background
+//Input: path of the video file
+//Output: frame index that is usable once complete
+pub fn generate_frame_index(path: &str) -> Arc<Mutex<FrameIndex>> {
+    let path = path.to_string();
+    spawn_job(format!("index frames of {path}"), FrameIndex::new(), move |index| run_index_pipeline(&path, index))
+}
+
+//Demuxes the video stream without decoding it and records the timestamp of every frame
+fn run_index_pipeline(path: &str, index: &Arc<Mutex<FrameIndex>>) -> JobResult {
+    let pipeline = gstreamer::Pipeline::new();
+    let source = gstreamer::ElementFactory::make("filesrc")
+        .property("location", path)
+        .build()?;
+    let parsebin = gstreamer::ElementFactory::make("parsebin").build()?;
+    let fake_sink = gstreamer::ElementFactory::make("fakesink")
+        .property("sync", false)
+        .build()?;
+
+    pipeline.add_many([&source, &parsebin, &fake_sink])?;
+    source.link(&parsebin)?;
+    //Only the video stream is indexed
+    link_first_stream(&parsebin, &fake_sink, "video/");
+
+    //Buffers arrive in decoding order so the timestamps are sorted once everything is collected
+    //Timestamps are converted to stream time, the time positions and seeks use, through the segment of the stream
+    let sink_pad = fake_sink.static_pad("sink").ok_or("Sink has no pad")?;
+    let index_clone = index.clone();
+    sink_pad.add_probe(gstreamer::PadProbeType::BUFFER, move |pad, info| {
+        if let Some(gstreamer::PadProbeData::Buffer(ref buffer)) = info.data {
+            //Codec headers and buffers without a presentation time aren't frames that are shown
+            if buffer.flags().contains(gstreamer::BufferFlags::HEADER) {
+                return gstreamer::PadProbeReturn::Ok;
+            }
+            let stream_time = buffer.pts().and_then(|pts| {
+                let segment_event = pad.sticky_event::<gstreamer::event::Segment>(0)?;
+                segment_event.segment().downcast_ref::<ClockTime>()?.to_stream_time(pts)
+            });
+            if let Some(stream_time) = stream_time {
+                index_clone.lock().unwrap().timestamps.push(stream_time.nseconds());
+            }
+        }
+        gstreamer::PadProbeReturn::Ok
+    });
+
+    pipeline.set_state(gstreamer::State::Playing)?;
+    let result = wait_for_end(&pipeline, index, || {});
+    pipeline.set_state(gstreamer::State::Null)?;
+    result?;
+
+    let mut index = index.lock().unwrap();
+    index.finish();
+    if index.variable_rate {
+        log_info!("{path} has a variable frame rate");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: u64 = 40_000_000;
+
+    fn index(timestamps: Vec<u64>) -> FrameIndex {
+        let mut index = FrameIndex { timestamps, ..FrameIndex::new() };
+        index.finish();
+        index.complete = true;
+        index
+    }
+
+    #[test]
+    fn sorts_and_removes_duplicates() {
+        let index = index(vec![2 * FRAME, 0, FRAME, FRAME, 3 * FRAME]);
+        assert_eq!(index.timestamps, vec![0, FRAME, 2 * FRAME, 3 * FRAME]);
+        assert!(!index.variable_rate);
+    }
+
+    #[test]
+    fn looks_up_frames_at_boundaries() {
+        let index = index(vec![FRAME, 2 * FRAME, 3 * FRAME]);
+        //Exactly on a frame and just before the next one
+        assert_eq!(index.frame_at(2 * FRAME), Some(1));
+        assert_eq!(index.frame_at(3 * FRAME - 1), Some(1));
+        assert_eq!(index.snap(2 * FRAME + 1), Some(2 * FRAME));
+        assert_eq!(index.next_frame_time(2 * FRAME), Some(3 * FRAME));
+        assert_eq!(index.previous_frame_time(2 * FRAME), Some(FRAME));
+        //Before the first frame the first frame is shown
+        assert_eq!(index.frame_at(0), Some(0));
+        assert_eq!(index.snap(0), Some(FRAME));
+        assert_eq!(index.next_frame_time(0), Some(FRAME));
+        assert_eq!(index.previous_frame_time(0), None);
+        //After the last frame the last frame stays shown
+        assert_eq!(index.frame_at(10 * FRAME), Some(2));
+        assert_eq!(index.snap(10 * FRAME), Some(3 * FRAME));
+        assert_eq!(index.next_frame_time(3 * FRAME), None);
+        assert_eq!(index.previous_frame_time(10 * FRAME), Some(2 * FRAME));
+        assert_eq!(index.frame_time(3), None);
+    }
+
+    #[test]
+    fn is_unusable_until_complete() {
+        let mut index = index(vec![0, FRAME]);
+        index.complete = false;
+        assert_eq!(index.frame_at(0), None);
+        assert_eq!(index.next_frame_time(0), None);
+        let empty = self::index(Vec::new());
+        assert_eq!(empty.snap(0), None);
+        assert_eq!(empty.typical_frame_duration(), None);
+    }
+
+    #[test]
+    fn uses_median_frame_duration() {
+        let index = index(vec![0, FRAME, 2 * FRAME, 3 * FRAME, 10 * FRAME]);
+        assert_eq!(index.typical_frame_duration(), Some(FRAME));
+    }
+
+    #[test]
+    fn keeps_ntsc_rates_constant() {
+        //29.97 fps timestamps rounded to whole nanoseconds alternate between two durations
+        let timestamps = (0..300u64).map(|frame| frame * 1_001_000_000_000 / 30_000).collect();
+        let index = index(timestamps);
+        assert!(!index.variable_rate);
+        assert_eq!(index.frame_at(1_001_000_000), Some(30));
+    }
+
+    #[test]
+    fn detects_variable_rate_past_tolerance() {
+        //A frame 10% longer than typical is still within tolerance
+        let within = index(vec![0, FRAME, 2 * FRAME, 3 * FRAME + FRAME / 10, 4 * FRAME + FRAME / 10]);
+        assert!(!within.variable_rate);
+        let beyond = index(vec![0, FRAME, 2 * FRAME, 3 * FRAME + FRAME / 5, 4 * FRAME + FRAME / 5]);
+        assert!(beyond.variable_rate);
+        //Frame numbers follow the real timestamps of a variable rate video
+        assert_eq!(beyond.frame_at(3 * FRAME + FRAME / 10), Some(2));
+        assert_eq!(beyond.next_frame_time(2 * FRAME), Some(3 * FRAME + FRAME / 5));
+    }
+}
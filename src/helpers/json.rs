@@ -0,0 +1,440 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+use crate::error::Error;
+
+//Deepest nesting accepted, keeps malformed input from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+//A parsed JSON document, object keys keep their order
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    //Reads a JSON document, an error describing where it is malformed
+    pub fn parse(text: &str) -> Result<JsonValue, Error> {
+        let mut parser = Parser { chars: text.chars().peekable(), position: 0 };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.chars.peek().is_some() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    //Creates an object from key value pairs
+    pub fn object<K: Into<String>>(entries: Vec<(K, JsonValue)>) -> JsonValue {
+        JsonValue::Object(entries.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    //Gets a member of an object
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    //Gets a whole, non negative number
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::Number(number) if *number >= 0.0 && number.fract() == 0.0 && *number < u64::MAX as f64 => Some(*number as u64),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<u32> for JsonValue {
+    fn from(value: u32) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(values: Vec<T>) -> Self {
+        JsonValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+//Writes compact JSON
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            //Whole numbers such as times in nanoseconds are written without a fraction
+            JsonValue::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => write!(f, "{}", *number as i64),
+            JsonValue::Number(number) if number.is_finite() => write!(f, "{number}"),
+            JsonValue::Number(_) => write!(f, "null"),
+            JsonValue::String(text) => write_string(f, text),
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    //Characters read so far, used in error messages
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, problem: &str) -> Error {
+        Error::Parse(format!("Invalid JSON at character {}: {problem}", self.position))
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c.is_some() {
+            self.position += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\n' | '\r' | '\t')) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{expected}'"))),
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, Error> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("expected {word}")));
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => self.parse_object(depth),
+            Some('[') => self.parse_array(depth),
+            Some('"') => self.parse_string().map(JsonValue::String),
+            Some('t') => self.expect_word("true", JsonValue::Bool(true)),
+            Some('f') => self.expect_word("false", JsonValue::Bool(false)),
+            Some('n') => self.expect_word("null", JsonValue::Null),
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<JsonValue, Error> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value(depth + 1)?;
+            entries.push((key, value));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(entries)),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<JsonValue, Error> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(values)),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    text.push(escaped);
+                }
+                Some(c) if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                Some(c) => text.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    //Reads the digits after \u, joining surrogate pairs
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let high = self.parse_hex()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let low = self.parse_hex()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, Error> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("invalid unicode escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, Error> {
+        let mut text = String::new();
+        while let Some(c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(*c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        if !is_json_number(&text) {
+            return Err(self.error(&format!("invalid number \"{text}\"")));
+        }
+        text.parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .map(JsonValue::Number)
+            .ok_or_else(|| self.error(&format!("invalid number \"{text}\"")))
+    }
+}
+
+//Checks a number follows the JSON grammar, which is stricter than what f64 parsing accepts
+fn is_json_number(text: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|c| c.is_ascii_digit());
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let integer_valid = digits(integer) && (integer == "0" || !integer.starts_with('0'));
+    let fraction_valid = fraction.is_none_or(digits);
+    let exponent_valid = exponent.is_none_or(|exponent| {
+        digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))
+    });
+    integer_valid && fraction_valid && exponent_valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> JsonValue {
+        JsonValue::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_documents() {
+        let value = parse(r#" {"jsonrpc": "2.0", "id": 1, "params": [true, false, null, {"a": []}]} "#);
+        assert_eq!(value.get("jsonrpc").and_then(JsonValue::as_str), Some("2.0"));
+        assert_eq!(value.get("id").and_then(JsonValue::as_u64), Some(1));
+        assert_eq!(value.to_string(), r#"{"jsonrpc":"2.0","id":1,"params":[true,false,null,{"a":[]}]}"#);
+    }
+
+    #[test]
+    fn parses_escapes() {
+        assert_eq!(parse(r#""\"\\\/\b\f\n\r\t""#), JsonValue::from("\"\\/\u{8}\u{c}\n\r\t"));
+        assert_eq!(parse(r#""\u00e9\u0041""#), JsonValue::from("éA"));
+        assert!(JsonValue::parse(r#""\x""#).is_err());
+        assert!(JsonValue::parse(r#""\u00g0""#).is_err());
+        assert!(JsonValue::parse("\"a\nb\"").is_err());
+        assert!(JsonValue::parse(r#""open"#).is_err());
+    }
+
+    #[test]
+    fn parses_surrogate_pairs() {
+        assert_eq!(parse(r#""\ud83d\ude00""#), JsonValue::from("😀"));
+        assert!(JsonValue::parse(r#""\ud83d""#).is_err());
+        assert!(JsonValue::parse(r#""\ud83d\u0041""#).is_err());
+        assert!(JsonValue::parse(r#""\ude00""#).is_err());
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse("0"), JsonValue::Number(0.0));
+        assert_eq!(parse("-12.5e2"), JsonValue::Number(-1250.0));
+        assert_eq!(parse("1E+2"), JsonValue::Number(100.0));
+        assert_eq!(parse("25000000000").as_u64(), Some(25_000_000_000));
+        assert_eq!(parse("-1").as_u64(), None);
+        assert_eq!(parse("1.5").as_u64(), None);
+        assert_eq!(parse("18446744073709551616").as_u64(), None);
+        for invalid in ["01", "1.", "-", "-.5", "1e", "1e+", "--1", "1-2", "1e400", "+1", ".5"] {
+            assert!(JsonValue::parse(invalid).is_err(), "{invalid} was accepted");
+        }
+    }
+
+    #[test]
+    fn writes_numbers() {
+        assert_eq!(JsonValue::from(3_600_000_000_000u64).to_string(), "3600000000000");
+        assert_eq!(JsonValue::Number(1.25).to_string(), "1.25");
+        assert_eq!(JsonValue::Number(f64::NAN).to_string(), "null");
+    }
+
+    #[test]
+    fn limits_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(JsonValue::parse(&nested(MAX_DEPTH + 1)).is_ok());
+        assert!(JsonValue::parse(&nested(MAX_DEPTH + 2)).is_err());
+        assert!(JsonValue::parse(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn rejects_trailing_garbage() {
+        assert!(JsonValue::parse("{} x").is_err());
+        assert!(JsonValue::parse("[1,]").is_err());
+        assert!(JsonValue::parse(r#"{"a":1,}"#).is_err());
+        assert!(JsonValue::parse("truex").is_err());
+        assert!(JsonValue::parse("").is_err());
+    }
+
+    #[test]
+    fn writes_escaped_strings() {
+        let text = "quote \" slash \\ line\n bell \u{7}";
+        assert_eq!(JsonValue::from(text).to_string(), r#""quote \" slash \\ line\n bell \u0007""#);
+        assert_eq!(parse(&JsonValue::from(text).to_string()), JsonValue::from(text));
+    }
+}
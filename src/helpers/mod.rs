@@ -1,4 +1,5 @@
 pub mod data;
 pub mod ui;
 pub mod parse;
-pub mod format;
\ No newline at end of file
+pub mod format;
+pub mod json;
\ No newline at end of file
@@ -1,28 +1,35 @@
 use gtk::{Entry};
 use glib::{Regex, RegexCompileFlags, RegexMatchFlags};
 use gtk::{ glib, prelude::*};
+use crate::error::Error;
 
-pub fn string_to_nseconds(time: &String) -> Option<u64> {
-    let (min, rest) = time.split_once(":").unwrap();
-    let (sec, subseconds) = rest.split_once(".").unwrap();
+//Reads a MM:SS.sss time as nanoseconds
+pub fn string_to_nseconds(time: &String) -> Result<u64, Error> {
+    let invalid = || Error::Parse(format!("\"{time}\" is not in the format MM:SS.sss"));
+    let (min, rest) = time.split_once(":").ok_or_else(invalid)?;
+    let (sec, subseconds) = rest.split_once(".").unwrap_or((rest, ""));
 
-    let minutes = min.parse::<u64>().unwrap();
-    let seconds = sec.parse::<u64>().unwrap();
+    let minutes = min.parse::<u64>().map_err(|_| invalid())?;
+    let seconds = sec.parse::<u64>().map_err(|_| invalid())?;
+    let fraction = if subseconds.is_empty() { 0 } else { subseconds.parse::<u64>().map_err(|_| invalid())? };
 
     let nanos = match subseconds.len() {
         0 => 0,
-        1 => subseconds.parse::<u64>().unwrap() * 100_000_000, // 0.1s = 100_000_000ns
-        2 => subseconds.parse::<u64>().unwrap() * 10_000_000,  // 0.01s = 10_000_000ns
-        3 => subseconds.parse::<u64>().unwrap() * 1_000_000,   // 0.001s = 1_000_000ns
-        4 => subseconds.parse::<u64>().unwrap() * 100_000,     // 0.0001s
-        5 => subseconds.parse::<u64>().unwrap() * 10_000,      // ...
-        6 => subseconds.parse::<u64>().unwrap() * 1_000,
-        7 => subseconds.parse::<u64>().unwrap() * 100,
-        8 => subseconds.parse::<u64>().unwrap() * 10,
-        _ => subseconds.parse::<u64>().unwrap() // assume already in nanoseconds
+        1 => fraction * 100_000_000, // 0.1s = 100_000_000ns
+        2 => fraction * 10_000_000,  // 0.01s = 10_000_000ns
+        3 => fraction * 1_000_000,   // 0.001s = 1_000_000ns
+        4 => fraction * 100_000,     // 0.0001s
+        5 => fraction * 10_000,      // ...
+        6 => fraction * 1_000,
+        7 => fraction * 100,
+        8 => fraction * 10,
+        9 => fraction, // already in nanoseconds
+        _ => return Err(invalid()),
     };
-    let total_nanos = minutes * 60 * 1_000_000_000 + seconds * 1_000_000_000 + nanos;
-    return Some(total_nanos);
+    minutes.checked_mul(60 * 1_000_000_000)
+        .and_then(|total| total.checked_add(seconds.checked_mul(1_000_000_000)?))
+        .and_then(|total| total.checked_add(nanos))
+        .ok_or_else(invalid)
 }
 
 pub fn validate_split_table_entry(entry: &Entry) -> bool {
@@ -31,7 +38,40 @@ pub fn validate_split_table_entry(entry: &Entry) -> bool {
     // Checks if the input matches the format: MM:SS.sss
     let re = Regex::match_simple(pattern, input.clone(), RegexCompileFlags::empty(), RegexMatchFlags::empty());
     if !re {
-        println!("Entry is not in valid format");
+        log_debug!("Entry is not in valid format");
     }
     re
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(time: &str) -> Result<u64, Error> {
+        string_to_nseconds(&time.to_string())
+    }
+
+    #[test]
+    fn reads_times() {
+        assert_eq!(parse("01:23.456").unwrap(), 83_456_000_000);
+        assert_eq!(parse("00:00").unwrap(), 0);
+        assert_eq!(parse("1:2.5").unwrap(), 62_500_000_000);
+        assert_eq!(parse("00:00.000000001").unwrap(), 1);
+        assert_eq!(parse("90:00.000").unwrap(), 5_400_000_000_000);
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for invalid in ["", "12", "aa:00", "00:bb", "00:00.x", "-1:00", "00:00.1234567890"] {
+            assert!(parse(invalid).is_err(), "{invalid} was accepted");
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_times() {
+        assert_eq!(parse("307445734:33.709551615").unwrap(), u64::MAX);
+        assert!(parse("307445734:33.709551616").is_err());
+        assert!(parse("307445735:00").is_err());
+        assert!(parse("00:18446744074").is_err());
+        assert!(parse("18446744073709551616:00").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use gtk::{ gdk::Display, CssProvider, FlowBox, prelude::*};
+use crate::error::Error;
 
 pub fn load_css(path: &str) {
     let provider = CssProvider::new();
@@ -8,7 +9,7 @@ pub fn load_css(path: &str) {
             provider.load_from_file(&file);
         }
         Err(e) => {
-            eprintln!("Failed to get current working directory to load css ({e})");
+            log_warn!("Failed to get current working directory to load css ({e})");
         }
     }
     if let Some(display) = Display::default() {
@@ -18,4 +19,20 @@ pub fn load_css(path: &str) {
 
 pub fn flowbox_children(flowbox: &FlowBox) -> impl Iterator<Item = gtk::Widget> {
     std::iter::successors(flowbox.first_child(), |w| w.next_sibling())
-}
\ No newline at end of file
+}
+
+//Tells the user about an error in a dialog over the window containing a widget
+pub fn show_error(widget: &impl IsA<gtk::Widget>, error: &Error) {
+    log_error!("{error}");
+    let dialog = gtk::MessageDialog::builder()
+        .modal(true)
+        .message_type(gtk::MessageType::Error)
+        .buttons(gtk::ButtonsType::Close)
+        .text(error.to_string())
+        .build();
+    if let Some(window) = widget.root().and_downcast::<gtk::Window>() {
+        dialog.set_transient_for(Some(&window));
+    }
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.present();
+}
@@ -0,0 +1,291 @@
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::Error;
+
+//Environment variable holding the log filter, e.g. "info" or "warn,video_pipeline=debug,gstreamer=info"
+pub const LOG_ENV_VAR: &str = "MVTAT_LOG";
+//Command line flag holding the log filter, overrides the environment variable
+pub const LOG_FLAG: &str = "--log-level";
+//Name used in filters for messages coming from GStreamer's debug categories
+pub const GSTREAMER_MODULE: &str = "gstreamer";
+//Number of messages kept for the log console
+const MAX_RECORDS: usize = 5000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Level> {
+        match text.trim().to_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    //Gets the GStreamer threshold that produces roughly the same amount of output
+    fn to_gstreamer(self) -> gstreamer::DebugLevel {
+        match self {
+            Level::Error => gstreamer::DebugLevel::Error,
+            Level::Warn => gstreamer::DebugLevel::Warning,
+            Level::Info => gstreamer::DebugLevel::Info,
+            Level::Debug => gstreamer::DebugLevel::Debug,
+            Level::Trace => gstreamer::DebugLevel::Log,
+        }
+    }
+
+    fn from_gstreamer(level: gstreamer::DebugLevel) -> Option<Level> {
+        match level {
+            gstreamer::DebugLevel::Error => Some(Level::Error),
+            gstreamer::DebugLevel::Warning => Some(Level::Warn),
+            gstreamer::DebugLevel::Fixme | gstreamer::DebugLevel::Info => Some(Level::Info),
+            gstreamer::DebugLevel::Debug => Some(Level::Debug),
+            gstreamer::DebugLevel::Log | gstreamer::DebugLevel::Trace | gstreamer::DebugLevel::Memdump => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+//A single logged message
+#[derive(Clone, Debug)]
+pub struct Record {
+    //Increasing number used by the log console to fetch new messages
+    pub id: u64,
+    pub time: SystemTime,
+    pub level: Level,
+    pub module: String,
+    pub message: String,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs() % 86_400;
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:03} {:<5} {}: {}",
+            seconds / 3600,
+            (seconds / 60) % 60,
+            seconds % 60,
+            since_epoch.subsec_millis(),
+            self.level,
+            self.module,
+            self.message
+        )
+    }
+}
+
+//Which levels are logged for each module
+#[derive(Clone, Debug)]
+struct Filter {
+    default_level: Level,
+    //Module prefix and level, longest prefix wins
+    module_levels: Vec<(String, Level)>,
+}
+
+impl Filter {
+    //Reads a filter like "warn,video_pipeline=debug,widgets::sync=trace"
+    fn parse(spec: &str) -> Result<Self, Error> {
+        let mut filter = Filter { default_level: Level::Info, module_levels: Vec::new() };
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => {
+                    let level = Level::parse(level)
+                        .ok_or_else(|| Error::Parse(format!("Unknown log level \"{level}\" for {module}")))?;
+                    filter.module_levels.push((module.trim().to_string(), level));
+                }
+                None => {
+                    filter.default_level = Level::parse(part)
+                        .ok_or_else(|| Error::Parse(format!("Unknown log level \"{part}\"")))?;
+                }
+            }
+        }
+        filter.module_levels.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        Ok(filter)
+    }
+
+    fn level_for(&self, module: &str) -> Level {
+        self.module_levels.iter()
+            .find(|(prefix, _)| module == prefix || module.starts_with(&format!("{prefix}::")))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default_level)
+    }
+}
+
+struct Logger {
+    //Filter text as entered, shown in the log console
+    spec: String,
+    filter: Filter,
+    records: VecDeque<Record>,
+    next_id: u64,
+}
+
+static LOGGER: Lazy<Mutex<Logger>> = Lazy::new(|| Mutex::new(Logger {
+    spec: String::from("info"),
+    filter: Filter { default_level: Level::Info, module_levels: Vec::new() },
+    records: VecDeque::new(),
+    next_id: 0,
+}));
+
+//Module paths are logged without the crate name so filters stay short
+fn short_module(module: &str) -> &str {
+    module.split_once("::").map(|(_, rest)| rest).unwrap_or(module)
+}
+
+//Sets up logging from the environment and command line, and routes GStreamer's debug output through it
+//Must be called after GStreamer is initialized
+//Input: command line arguments
+//Output: the arguments without the log flag, to hand to GTK
+pub fn init(args: Vec<String>) -> Vec<String> {
+    let mut spec = std::env::var(LOG_ENV_VAR).ok();
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if arg == LOG_FLAG {
+            spec = iter.next();
+        } else if let Some(value) = arg.strip_prefix(&format!("{LOG_FLAG}=")) {
+            spec = Some(value.to_string());
+        } else {
+            remaining.push(arg);
+        }
+    }
+
+    //Without a filter informational messages are logged along with GStreamer warnings
+    if let Err(e) = set_filter(spec.as_deref().unwrap_or("info")) {
+        log(Level::Error, module_path!(), format_args!("{e}"));
+    }
+
+    //GStreamer messages are recorded in the log console instead of only on the terminal
+    //GST_DEBUG has already picked which GStreamer messages are wanted, so they aren't filtered again
+    let gstreamer_filtered = std::env::var_os("GST_DEBUG").is_none();
+    gstreamer::log::remove_default_log_function();
+    gstreamer::log::add_log_function(move |category, level, file, function, line, _object, message| {
+        let level = match Level::from_gstreamer(level) {
+            Some(level) => level,
+            None => return,
+        };
+        if let Some(message) = message.get() {
+            let module = format!("{GSTREAMER_MODULE}::{}", category.name());
+            log_module(level, &module, format_args!("{message} ({file}:{line}:{function})"), gstreamer_filtered);
+        }
+    });
+    remaining
+}
+
+//Changes which messages are logged
+//Input: filter like "info" or "warn,video_pipeline=debug,gstreamer=info"
+pub fn set_filter(spec: &str) -> Result<(), Error> {
+    let filter = Filter::parse(spec)?;
+    //GST_DEBUG keeps control of GStreamer's own thresholds when it is set
+    if std::env::var_os("GST_DEBUG").is_none() {
+        let gstreamer_level = filter.module_levels.iter()
+            .find(|(module, _)| module == GSTREAMER_MODULE)
+            .map(|(_, level)| *level)
+            .unwrap_or(filter.default_level.min(Level::Warn));
+        gstreamer::log::set_default_threshold(gstreamer_level.to_gstreamer());
+    }
+    let mut logger = LOGGER.lock().unwrap();
+    logger.spec = spec.trim().to_string();
+    logger.filter = filter;
+    Ok(())
+}
+
+//Gets the filter currently in use
+pub fn get_filter() -> String {
+    LOGGER.lock().unwrap().spec.clone()
+}
+
+//Logs a message from a module of this application, used through the log_* macros
+pub fn log(level: Level, module: &str, args: fmt::Arguments) {
+    log_module(level, short_module(module), args, true);
+}
+
+fn log_module(level: Level, module: &str, args: fmt::Arguments, filtered: bool) {
+    let mut logger = LOGGER.lock().unwrap();
+    if filtered && level > logger.filter.level_for(module) {
+        return;
+    }
+    let record = Record {
+        id: logger.next_id,
+        time: SystemTime::now(),
+        level,
+        module: module.to_string(),
+        message: args.to_string(),
+    };
+    logger.next_id += 1;
+    eprintln!("{record}");
+    logger.records.push_back(record);
+    while logger.records.len() > MAX_RECORDS {
+        logger.records.pop_front();
+    }
+}
+
+//Gets the kept messages logged after a message
+//Input: id of the last message already seen, None for every kept message
+pub fn records_after(id: Option<u64>) -> Vec<Record> {
+    let logger = LOGGER.lock().unwrap();
+    logger.records.iter()
+        .filter(|record| id.map_or(true, |id| record.id > id))
+        .cloned()
+        .collect()
+}
+
+//Forgets every kept message
+pub fn clear_records() {
+    LOGGER.lock().unwrap().records.clear();
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Error, module_path!(), format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Warn, module_path!(), format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Info, module_path!(), format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Debug, module_path!(), format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::Level::Trace, module_path!(), format_args!($($arg)*)) };
+}
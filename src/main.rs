@@ -1,6 +1,16 @@
+#[macro_use]
+mod logging;
+mod background_job;
 mod video_pipeline;
+mod waveform;
+mod thumbnails;
+mod proxy;
+mod frame_index;
+mod error;
+mod mpris;
+mod control;
 use glib::{ExitCode, prelude::ObjectExt};
-use gtk::{FlowBox, FlowBoxChild, SelectionMode, SingleSelection,};
+use gtk::{FlowBox, SelectionMode, SingleSelection,};
 use gtk::{glib, prelude::*, Application, ApplicationWindow, Box, Builder, Button};
 use gstgtk4;
 mod widgets;
@@ -13,11 +23,14 @@ use widgets::split_panel::splits::VideoSegment;
 use gtk::prelude::GtkWindowExt;
 mod helpers;
 use crate::helpers::data::{get_data, store_data};
+use crate::error::Error;
 use crate::helpers::ui::load_css;
-use crate::helpers::ui::flowbox_children;
+use crate::helpers::ui::{flowbox_children, show_error};
 use crate::widgets::seek_bar::color_picker::ColorPool;
 use crate::widgets::split_panel::timeentry::TimeEntry;
 use crate::widgets::sync::sync_manager::SyncManager;
+use crate::widgets::log_console::log_console::LogConsole;
+use crate::widgets::diagnostics::diagnostics_window::DiagnosticsWindow;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -54,9 +67,69 @@ fn build_ui(app: &Application) -> Builder {
 
     split_table.setup_start_time_offset_column("Start Time Offsets");
 
+    //Finds segments by name, note, comment or tag, enter moves on to the next match
+    let split_search_entry: gtk::SearchEntry = builder.object("split_search_entry").expect("Failed to get split_search_entry from UI File");
+    let split_table_clone = split_table.clone();
+    split_search_entry.connect_search_changed(move |search_entry| {
+        let found = split_table_clone.select_next_match(&search_entry.text(), false);
+        if found {
+            search_entry.remove_css_class("error");
+        } else {
+            search_entry.add_css_class("error");
+        }
+    });
+    let split_table_clone = split_table.clone();
+    split_search_entry.connect_activate(move |search_entry| {
+        let found = split_table_clone.select_next_match(&search_entry.text(), true);
+        if found {
+            search_entry.remove_css_class("error");
+        } else {
+            search_entry.add_css_class("error");
+        }
+    });
+
+    //Snaps recorded and entered times to the frame shown by the matching video player
+    let snap_to_frames_button: gtk::ToggleButton = builder.object("snap_to_frames_button").expect("Failed to get snap_to_frames_button from UI File");
+    split_table.set_snap_to_frames(snap_to_frames_button.is_active());
+    snap_to_frames_button.connect_toggled(glib::clone!(
+        #[weak] split_table,
+        move |button| {
+            split_table.set_snap_to_frames(button.is_active());
+        }
+    ));
+    //Single log console reused every time it's opened
+    let log_console_button: Button = builder.object("log_console_button").expect("Failed to get log_console_button from UI File");
+    let log_console: RefCell<Option<LogConsole>> = RefCell::new(None);
+    log_console_button.connect_clicked(move |_| {
+        log_console.borrow_mut()
+            .get_or_insert_with(LogConsole::new)
+            .present();
+    });
+
+    let video_container_clone = video_container.clone();
+    split_table.set_frame_snapper(move |video_player_id, time| {
+        flowbox_children(&video_container_clone)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .find(|video_player| video_player.get_id() == video_player_id)
+            .and_then(|video_player| video_player.snap_to_frame(time))
+    });
+
     let sync_manager = SyncManager::new();
     store_data(&window, "sync_manager", sync_manager.clone());
 
+    //Single diagnostics window reused every time it's opened
+    let diagnostics_button: Button = builder.object("diagnostics_button").expect("Failed to get diagnostics_button from UI File");
+    let diagnostics_window: RefCell<Option<DiagnosticsWindow>> = RefCell::new(None);
+    diagnostics_button.connect_clicked(glib::clone!(
+        #[weak] video_container,
+        #[weak] sync_manager,
+        move |_| {
+            diagnostics_window.borrow_mut()
+                .get_or_insert_with(|| DiagnosticsWindow::new(&video_container, &sync_manager))
+                .present();
+        }
+    ));
+
     let ssb = SharedSeekBar::new(&video_container, &split_table_cv, &start_time_offset_ls, &split_table_ls, &sync_manager, &split_table);
     bottom_vbox.append(&ssb);
 
@@ -64,6 +137,9 @@ fn build_ui(app: &Application) -> Builder {
     sync_manager.add_sync_callback(move |event| {
         shared_seek_bar_clone.handle_sync_event(event);
     });
+
+    //Media keys and scripts control the shared controls or the focused video player
+    mpris::start(app, &video_container, &ssb, &split_table);
     
     // Adds an initial row
     //split_table.append_empty_row();
@@ -76,11 +152,19 @@ fn build_ui(app: &Application) -> Builder {
     let initial_child_count = 0_usize;
     store_data(&video_container, "count", initial_child_count);
 
-    let video_container_clone = video_container.clone();
-    let shared_seek_bar_clone = ssb.clone();
-    let split_table_clone = split_table.clone();
+    let color_picker = Rc::new(RefCell::new(ColorPool::new(["red", "blue", "green", "black", "coral", "lavender"].into_iter().map(String::from).collect())));
+    let context = AppContext {
+        window: window.clone(),
+        video_container: video_container.clone(),
+        split_table: split_table.clone(),
+        shared_seek_bar: ssb.clone(),
+        sync_manager: sync_manager.clone(),
+        color_picker,
+    };
+
+    let context_clone = context.clone();
     add_row_above_button.connect_clicked(move |_| {
-        let selection_model = split_table_clone.get_split_table_column_view()
+        let selection_model = context_clone.split_table.get_split_table_column_view()
             .unwrap()
             .model()
             .and_downcast::<SingleSelection>()
@@ -89,16 +173,12 @@ fn build_ui(app: &Application) -> Builder {
         if let Some(_selection) = selection_model.selected_item().and_downcast::<VideoSegment>() {
             selected_index = selection_model.selected();
         }
-        split_table_clone.insert_empty_row(selected_index);
-        split_table_clone.connect_row_to_seekbar(&video_container_clone, selected_index);
-        shared_seek_bar_clone.connect_row(selected_index);
+        insert_row(&context_clone, selected_index);
     });
 
-    let video_container_clone = video_container.clone();
-    let shared_seek_bar_clone = ssb.clone();
-    let split_table_clone = split_table.clone();
+    let context_clone = context.clone();
     add_row_below_button.connect_clicked(move |_| {
-        let selection_model = split_table_clone.get_split_table_column_view()
+        let selection_model = context_clone.split_table.get_split_table_column_view()
             .unwrap()
             .model()
             .and_downcast::<SingleSelection>()
@@ -107,162 +187,235 @@ fn build_ui(app: &Application) -> Builder {
         if let Some(_selection) = selection_model.selected_item().and_downcast::<VideoSegment>() {
             selected_index = selection_model.selected() + 1;
         }
-        split_table_clone.insert_empty_row(selected_index);
-        split_table_clone.connect_row_to_seekbar(&video_container_clone, selected_index);
-        shared_seek_bar_clone.connect_row(selected_index);
+        insert_row(&context_clone, selected_index);
     });
     
+    // Adds new video player and new columns to split table
     let new_video_player_button: Button = builder.object("new_video_player_button").expect("Failed to get button");
-    let builder_clone = builder.clone();
-    let video_container_clone = video_container.clone();
+    let context_clone = context.clone();
+    new_video_player_button.connect_clicked(move |_| {
+        if let Err(e) = add_video_player(&context_clone, None) {
+            show_error(&context_clone.window, &e);
+        }
+    });
+
     let shared_seek_bar_clone = ssb.clone();
+    toggle_shared_video_play.connect_clicked(move |_| {
+        shared_seek_bar_clone.toggle_has_control();
+    });
 
-    let color_picker = Rc::new(RefCell::new(ColorPool::new(["red", "blue", "green", "black", "coral", "lavender"].into_iter().map(String::from).collect())));
+    //Scripts on this machine drive the same handlers over HTTP when MVTAT_CONTROL_PORT is set
+    control::start(&context);
+
+    app.add_window(&window);
+    window.show();
+    builder
+}
+
+//Widgets the UI handlers and the control server work through
+#[derive(Clone)]
+pub struct AppContext {
+    pub window: ApplicationWindow,
+    pub video_container: FlowBox,
+    pub split_table: SplitTable,
+    pub shared_seek_bar: SharedSeekBar,
+    pub sync_manager: SyncManager,
+    pub color_picker: Rc<RefCell<ColorPool>>,
+}
+
+//Inserts an empty split row and connects it to the seek bars
+//Input: index the new row is inserted at
+pub fn insert_row(context: &AppContext, index: u32) {
+    context.split_table.insert_empty_row(index);
+    context.split_table.connect_row_to_seekbar(&context.video_container, index);
+    context.shared_seek_bar.connect_row(index);
+}
+
+//Adds a video player and its split table columns, asking for a file when no path is given
+//Output: id of the new video player
+pub fn add_video_player(context: &AppContext, path: Option<&str>) -> Result<String, Error> {
+    let video_container = context.video_container.clone();
+    let shared_seek_bar = context.shared_seek_bar.clone();
+    let split_table = context.split_table.clone();
+    let sync_manager = context.sync_manager.clone();
+    let color_picker = context.color_picker.clone();
+
+    let count = *unsafe{ get_data::<usize>(&video_container, "count").unwrap().as_ref() };
+    if count as u32 == MAX_VIDEO_PLAYERS {
+        log_warn!("Max video players reached");
+        return Err(Error::Table(format!("Max video players reached, at most {MAX_VIDEO_PLAYERS} videos can be open")));
+    }
+    
+    // Sets up new video player
+    let video_player_id = get_next_id().to_string();
+
+    // Adds start time offset entry text to start_time_offset liststore/columnview
+    // Done first so nothing is left half set up if the split table can't take the video
+    let new_start_time_offset_time_entry = split_table.add_start_time_offset_row(video_player_id.as_str())?;
+
+    let new_player = VideoPlayer::new(video_player_id.as_str());
+    let color = color_picker.borrow_mut().assign_color(video_player_id.as_str()).unwrap();
+    new_player.set_color(color.as_str());
+    new_player.setup_event_handlers();
+
+    split_table.add_empty_column(video_player_id.as_str());
     
-    let color_picker_clone = color_picker.clone();
-    // Adds new video player and new columns to split table
     let split_table_clone = split_table.clone();
-    let sync_manager_clone = sync_manager.clone();
-    new_video_player_button.connect_clicked(move |_| {
-        let count = *unsafe{ get_data::<usize>(&video_container_clone, "count").unwrap().as_ref() };
-        if count as u32 == MAX_VIDEO_PLAYERS {
-            println!("Max video players reached");
-            return;
+    let video_player_weak = new_player.downgrade();
+    // Listens to the split button from a video player
+    // args[1] ID u32: index from the video player thats button was pressed
+    // args[2] Position u64: time in nano seconds that the video player playback head was at when the button was pressed
+    new_player.connect_local("split-button-clicked", false, move |args| {
+        let video_player_id: String = args[1].get().unwrap();
+        let video_player_position: u64 = args[2].get().unwrap();
+        // Sets the time for the selected row
+        if let Err(e) = split_table_clone.set_split(video_player_id.as_str(), video_player_position) {
+            if let Some(video_player) = video_player_weak.upgrade() {
+                video_player.report_error(&e);
+            }
         }
-        
-        let window: ApplicationWindow = builder_clone.object("main_window").expect("Failed to get main_window from UI file");
-        
-        // Sets up new video player
-        let video_player_id = get_next_id().to_string();
-        let new_player = VideoPlayer::new(video_player_id.as_str());
-        let mut picker = color_picker_clone.borrow_mut();
-        let color = picker.assign_color(video_player_id.as_str()).unwrap();
-        new_player.set_color(color.as_str());
-        new_player.setup_event_handlers();
-
-        split_table_clone.add_empty_column(video_player_id.as_str());
-        
-        let split_table_clone_clone = split_table_clone.clone();
-        // Listens to the split button from a video player
-        // args[1] ID u32: index from the video player thats button was pressed
-        // args[2] Position u64: time in nano seconds that the video player playback head was at when the button was pressed
-        new_player.connect_local("split-button-clicked", false, move |args| {
-            let video_player_id: String = args[1].get().unwrap();
-            let video_player_position: u64 = args[2].get().unwrap();
-            // Sets the time for the selected row
-            if let Err(e) = split_table_clone_clone.set_split(video_player_id.as_str(), video_player_position) {
-                eprintln!("{e}");
+        None
+    });
+
+    let split_table_clone = split_table.clone();
+    let video_player_weak = new_player.downgrade();
+    new_player.connect_local("set-start-button-clicked", false, move |args| {
+        let video_player_id: String = args[1].get().unwrap();
+        let video_player_position: u64 = args[2].get().unwrap();
+        if let Err(e) = split_table_clone.set_start_time_offset(video_player_id.as_str(), video_player_position) {
+            if let Some(video_player) = video_player_weak.upgrade() {
+                video_player.report_error(&e);
             }
-            None
-        });
+        }
+        None
+    });
 
-        let split_table_clone_clone = split_table_clone.clone();
-        new_player.connect_local("set-start-button-clicked", false, move |args| {
-            let video_player_id: String = args[1].get().unwrap();
-            let video_player_position: u64 = args[2].get().unwrap();
-            if let Err(e) = split_table_clone_clone.set_start_time_offset(video_player_id.as_str(), video_player_position) {
-                eprintln!("{e}");
+    // Dragging a split mark on the video players seek bar moves that split
+    let split_table_clone = split_table.clone();
+    let video_player_weak = new_player.downgrade();
+    new_player.get_seek_bar().unwrap().connect_local("mark-dragged", false, move |args| {
+        let video_player_id: String = args[1].get().unwrap();
+        let segment_id: String = args[2].get().unwrap();
+        let video_player_position: u64 = args[3].get().unwrap();
+        if let Err(e) = split_table_clone.set_split_time(video_player_id.as_str(), segment_id.as_str(), video_player_position) {
+            if let Some(video_player) = video_player_weak.upgrade() {
+                video_player.report_error(&e);
             }
+        }
+        None
+    });
+
+    new_player.connect_local("solo-button-clicked", false, glib::clone!(
+        #[strong] shared_seek_bar,
+        move |args| {
+            let video_player_id: String = args[1].get().unwrap();
+            shared_seek_bar.solo(video_player_id.as_str());
             None
-        });
+        }
+    ));
 
-        new_player.connect_local("seek-bar-pressed", false, glib::clone!(
-            #[strong(rename_to = shared_seek_bar)] shared_seek_bar_clone,
-            move |_| {
-                if shared_seek_bar.get_control_state() {
-                    println!("User interacted with video player -> toggling control off");
-                    shared_seek_bar.toggle_has_control();
-                }
-                None
-            }
-        ));
-
-        new_player.connect_local("pipeline-built", false, glib::clone!(
-            #[strong(rename_to = sync_man)] sync_manager_clone,
-            #[strong(rename_to = pipeline_id)] video_player_id,
-            #[strong(rename_to = video_player)] new_player,
-            move |_| {
-                let pipeline = video_player.pipeline();
-                //split_table.reset_individual_video_segments(video_player_index);
-                sync_man.add_pipeline(pipeline_id.as_str(), pipeline);
-                None
-            }
-        ));
-
-        new_player.connect_local("remove-video-player", false, glib::clone!(
-            #[strong(rename_to = sync_man)] sync_manager_clone,
-            #[strong(rename_to = pipeline_id)] video_player_id,
-            #[strong(rename_to = video_player)] new_player,
-            #[strong(rename_to = video_player_container)] video_container_clone,
-            #[strong(rename_to = shared_seek_bar)] shared_seek_bar_clone,
-            #[strong(rename_to = split_table)] split_table_clone,
-            #[strong(rename_to = color_picker)] color_picker_clone,
-            move |_| {
-                println!("removing video player");
-                split_table.remove_column(pipeline_id.as_str());
-                shared_seek_bar.remove_marks(pipeline_id.as_str());
-                sync_man.remove_pipeline(pipeline_id.as_str());
-                let count = *unsafe{ get_data::<usize>(&video_player_container, "count").unwrap().as_ref() };
-                store_data(&video_player_container, "count", count - 1);
-                let number_of_columns = ((count as u32).saturating_sub(1)).clamp(1,3);
-                video_player_container.set_max_children_per_line(number_of_columns);
-                video_player_container.set_min_children_per_line(number_of_columns);
-                let mut picker = color_picker.borrow_mut();
-                picker.release_color(pipeline_id.as_str());
-                if let Some(flowbox_child) = video_player.parent().and_then(|x| x.dynamic_cast::<FlowBoxChild>().ok()) {
-                    video_player_container.remove(&flowbox_child);
-                }
-                video_player.cleanup();
-                None
+    new_player.connect_local("clamp-end-reached", false, glib::clone!(
+        #[strong] shared_seek_bar,
+        move |args| {
+            let video_player_id: String = args[1].get().unwrap();
+            shared_seek_bar.handle_clamp_end_reached(video_player_id.as_str());
+            None
+        }
+    ));
+
+    new_player.connect_local("seek-bar-pressed", false, glib::clone!(
+        #[strong] shared_seek_bar,
+        move |_| {
+            if shared_seek_bar.get_control_state() {
+                log_debug!("Video player was used directly, releasing shared control");
+                shared_seek_bar.toggle_has_control();
             }
-        ));
-        
-        // Adds start time offset entry text to start_time_offset liststore/columnview
-        let new_start_time_offset_time_entry = match split_table_clone.add_start_time_offset_row(video_player_id.as_str()) {
-            Ok(te) => te,
-            Err(e) => {
-                panic!("{e}")
+            None
+        }
+    ));
+
+    new_player.connect_local("pipeline-built", false, glib::clone!(
+        #[strong(rename_to = sync_man)] sync_manager,
+        #[strong(rename_to = pipeline_id)] video_player_id,
+        #[strong(rename_to = video_player)] new_player,
+        #[strong] shared_seek_bar,
+        move |_| {
+            let pipeline = video_player.pipeline();
+            //split_table.reset_individual_video_segments(video_player_index);
+            if let Err(e) = sync_man.add_pipeline(pipeline_id.as_str(), pipeline) {
+                video_player.report_error(&e);
             }
-        };
-        
-        new_start_time_offset_time_entry.connect_notify_local(Some("time"), glib::clone!(
-            #[weak(rename_to = shared_seek_bar)] shared_seek_bar_clone,
-            move |_, _| {
-                shared_seek_bar.update_timeline_length();
+            shared_seek_bar.connect_video_previews(&video_player);
+            None
+        }
+    ));
+
+    new_player.connect_local("remove-video-player", false, glib::clone!(
+        #[strong(rename_to = sync_man)] sync_manager,
+        #[strong(rename_to = pipeline_id)] video_player_id,
+        #[strong(rename_to = video_player)] new_player,
+        #[strong(rename_to = video_player_container)] video_container,
+        #[strong] shared_seek_bar,
+        #[strong] split_table,
+        #[strong] color_picker,
+        move |_| {
+            log_info!("Removing video player {pipeline_id}");
+            split_table.remove_column(pipeline_id.as_str());
+            shared_seek_bar.remove_marks(pipeline_id.as_str());
+            shared_seek_bar.remove_mixer_channel(pipeline_id.as_str());
+            shared_seek_bar.remove_nudge_channel(pipeline_id.as_str());
+            shared_seek_bar.remove_video_previews(pipeline_id.as_str());
+            sync_man.remove_pipeline(pipeline_id.as_str());
+            let count = *unsafe{ get_data::<usize>(&video_player_container, "count").unwrap().as_ref() };
+            store_data(&video_player_container, "count", count - 1);
+            let number_of_columns = ((count as u32).saturating_sub(1)).clamp(1,3);
+            video_player_container.set_max_children_per_line(number_of_columns);
+            video_player_container.set_min_children_per_line(number_of_columns);
+            let mut picker = color_picker.borrow_mut();
+            picker.release_color(pipeline_id.as_str());
+            let flowbox_child = video_player.flowbox_child();
+            video_player.cleanup();
+            if let Some(flowbox_child) = flowbox_child {
+                video_player_container.remove(&flowbox_child);
             }
-        ));
-            
-        // Adds two columns to split table for each new video player
-        // Column 1: (Time) Split time -> time since the start of the clip
-        // Column 2: (Duration) Segment time -> time since the last split
-        split_table_clone.add_column(video_player_id.to_string().as_str(), video_player_id.as_str(), "relative-time");
-        split_table_clone.add_column(video_player_id.to_string().as_str(), video_player_id.as_str(), "duration");
-
-        // Updates formatting of the video players and adds the new video player to the container
-        let number_of_columns = (count as u32 + 1).clamp(1,3);
-        video_container_clone.set_max_children_per_line(number_of_columns);
-        video_container_clone.set_min_children_per_line(number_of_columns);
-        video_container_clone.append(&new_player);
-        
-        let video_player_index = count as u32;
-        // Updates video_container data keeping track of the active video players
-        store_data(&video_container_clone, "count", count + 1);
-
+            None
+        }
+    ));
+    
+    new_start_time_offset_time_entry.connect_notify_local(Some("time"), glib::clone!(
+        #[weak] shared_seek_bar,
+        move |_, _| {
+            shared_seek_bar.update_timeline_length();
+        }
+    ));
         
-        split_table_clone.connect_column_to_seekbar(&video_container_clone, video_player_index);
-        shared_seek_bar_clone.connect_column(video_player_id.as_str(), color.as_str());
-
-        new_player.load_file(window);
-    });
-
-    let shared_seek_bar_clone = ssb.clone();
-    toggle_shared_video_play.connect_clicked(move |_| {
-        shared_seek_bar_clone.toggle_has_control();
-    });
+    // Adds two columns to split table for each new video player
+    // Column 1: (Time) Split time -> time since the start of the clip
+    // Column 2: (Duration) Segment time -> time since the last split
+    split_table.add_column(video_player_id.to_string().as_str(), video_player_id.as_str(), "relative-time");
+    split_table.add_column(video_player_id.to_string().as_str(), video_player_id.as_str(), "duration");
+
+    // Updates formatting of the video players and adds the new video player to the container
+    let number_of_columns = (count as u32 + 1).clamp(1,3);
+    video_container.set_max_children_per_line(number_of_columns);
+    video_container.set_min_children_per_line(number_of_columns);
+    video_container.append(&new_player);
+    
+    let video_player_index = count as u32;
+    // Updates video_container data keeping track of the active video players
+    store_data(&video_container, "count", count + 1);
 
-    app.add_window(&window);
-    window.show();
-    builder
+    
+    split_table.connect_column_to_seekbar(&video_container, video_player_index);
+    shared_seek_bar.connect_column(video_player_id.as_str(), color.as_str());
+    shared_seek_bar.add_mixer_channel(&new_player, color.as_str());
+    shared_seek_bar.add_nudge_channel(video_player_id.as_str(), color.as_str());
+
+    match path {
+        Some(path) => new_player.load_path(path),
+        None => new_player.load_file(context.window.clone()),
+    }
+    Ok(video_player_id)
 }
 
 fn main() -> glib::ExitCode {
@@ -270,6 +423,9 @@ fn main() -> glib::ExitCode {
     gstreamer::init().unwrap();
     gtk::init().unwrap();
 
+    //Log filter comes from MVTAT_LOG or --log-level, which GTK shouldn't see
+    let args = logging::init(std::env::args().collect());
+
     std::env::set_var("GTK_THEME", "Adwaita:dark");
 
     gstgtk4::plugin_register_static().expect("Failed to register gstgtk4 plugin");
@@ -291,6 +447,18 @@ fn main() -> glib::ExitCode {
 
     gio::resources_register_include!("sptable.gresource")
         .expect("Failed to register sptable resource");
+
+    gio::resources_register_include!("mixerpanel.gresource")
+        .expect("Failed to register mixer panel resource");
+
+    gio::resources_register_include!("nudgepanel.gresource")
+        .expect("Failed to register nudge panel resource");
+
+    gio::resources_register_include!("logconsole.gresource")
+        .expect("Failed to register log console resource");
+
+    gio::resources_register_include!("diagnosticswindow.gresource")
+        .expect("Failed to register diagnostics window resource");
     
     let app = gtk::Application::new(None::<&str>, gtk::gio::ApplicationFlags::FLAGS_NONE);
     app.connect_activate(|app| {
@@ -300,25 +468,18 @@ fn main() -> glib::ExitCode {
         let builder_clone = builder.clone();
         // ensures all video player are properly disposed 
         app.connect_shutdown(move |_| {
-            println!("shutting down");
+            log_info!("Shutting down");
             let video_container: FlowBox = builder_clone.object("video_container").expect("failed to get video_container from UI file");
             for child in flowbox_children(&video_container) {
-                let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                    Some(c) => c,
-                    None => continue,
-                };
-
-                let content = match fb_child.child() {
-                    Some(c) => c,
-                    None => continue,
-                };
-
-                let video_player = match content.downcast_ref::<VideoPlayer>() {
+                let video_player = match VideoPlayer::from_flowbox_child(&child) {
                     Some(vp) => vp,
                     None => continue,
                 };
-                video_player.unparent();
+                //Detached video players are removed from their window during cleanup
                 video_player.cleanup();
+                if video_player.parent().is_some() {
+                    video_player.unparent();
+                }
             }
             let window: ApplicationWindow = builder.object("main_window").expect("Failed to get main_window from UI file");
             let sync_manager = unsafe { get_data::<SyncManager>(&window, "sync_manager").unwrap().as_ref() };
@@ -328,7 +489,7 @@ fn main() -> glib::ExitCode {
         });
 
     });
-    let res = app.run();
+    let res = app.run_with_args(&args);
 
     unsafe {
         gstreamer::deinit();
@@ -0,0 +1,570 @@
+use gtk::prelude::*;
+use gtk::{glib, Application, FlowBox};
+use glib::{Variant, WeakRef};
+use glib::variant::ObjectPath;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::error::Error;
+use crate::helpers::ui::flowbox_children;
+use crate::widgets::seek_bar::shared_seek_bar::SharedSeekBar;
+use crate::widgets::split_panel::splits::VideoSegment;
+use crate::widgets::split_panel::splittable::SplitTable;
+use crate::widgets::video_player_widget::video_player::VideoPlayer;
+
+//Name media key daemons and scripts find the player by
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.mvtat";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+//How often the playback status and metadata are checked for changes to announce
+const REFRESH_INTERVAL_MS: u64 = 500;
+//Splits this close to the position are skipped by next and previous so repeated presses keep moving
+const SPLIT_TOLERANCE: u64 = 100_000_000;
+//Player properties announced when they change
+const WATCHED_PROPERTIES: [&str; 7] = ["PlaybackStatus", "Metadata", "CanGoNext", "CanGoPrevious", "CanPlay", "CanPause", "CanSeek"];
+
+const INTROSPECTION_XML: &str = r#"
+<node>
+    <interface name="org.mpris.MediaPlayer2">
+        <method name="Raise"/>
+        <method name="Quit"/>
+        <property name="CanQuit" type="b" access="read"/>
+        <property name="CanRaise" type="b" access="read"/>
+        <property name="HasTrackList" type="b" access="read"/>
+        <property name="Identity" type="s" access="read"/>
+        <property name="SupportedUriSchemes" type="as" access="read"/>
+        <property name="SupportedMimeTypes" type="as" access="read"/>
+    </interface>
+    <interface name="org.mpris.MediaPlayer2.Player">
+        <method name="Next"/>
+        <method name="Previous"/>
+        <method name="Pause"/>
+        <method name="PlayPause"/>
+        <method name="Stop"/>
+        <method name="Play"/>
+        <method name="Seek">
+            <arg name="Offset" type="x" direction="in"/>
+        </method>
+        <method name="SetPosition">
+            <arg name="TrackId" type="o" direction="in"/>
+            <arg name="Position" type="x" direction="in"/>
+        </method>
+        <method name="OpenUri">
+            <arg name="Uri" type="s" direction="in"/>
+        </method>
+        <signal name="Seeked">
+            <arg name="Position" type="x"/>
+        </signal>
+        <property name="PlaybackStatus" type="s" access="read"/>
+        <property name="Rate" type="d" access="read"/>
+        <property name="Metadata" type="a{sv}" access="read"/>
+        <property name="Position" type="x" access="read"/>
+        <property name="MinimumRate" type="d" access="read"/>
+        <property name="MaximumRate" type="d" access="read"/>
+        <property name="CanGoNext" type="b" access="read"/>
+        <property name="CanGoPrevious" type="b" access="read"/>
+        <property name="CanPlay" type="b" access="read"/>
+        <property name="CanPause" type="b" access="read"/>
+        <property name="CanSeek" type="b" access="read"/>
+        <property name="CanControl" type="b" access="read"/>
+    </interface>
+</node>
+"#;
+
+//What the MPRIS player controls: every video while in sync view, otherwise a single video
+enum Target {
+    Shared(SharedSeekBar),
+    Player(VideoPlayer),
+}
+
+//Widgets the MPRIS player works through, looked up on every call since video players come and go
+#[derive(Clone)]
+struct Mpris {
+    app: WeakRef<Application>,
+    video_container: WeakRef<FlowBox>,
+    shared_seek_bar: WeakRef<SharedSeekBar>,
+    split_table: WeakRef<SplitTable>,
+}
+
+// MPRIS:
+// Publishes an MPRIS2 media player on the session bus so media keys and scripts can control playback
+// The name is kept until the application exits
+pub fn start(app: &Application, video_container: &FlowBox, shared_seek_bar: &SharedSeekBar, split_table: &SplitTable) {
+    let mpris = Mpris {
+        app: app.downgrade(),
+        video_container: video_container.downgrade(),
+        shared_seek_bar: shared_seek_bar.downgrade(),
+        split_table: split_table.downgrade(),
+    };
+    gio::bus_own_name(
+        gio::BusType::Session,
+        BUS_NAME,
+        gio::BusNameOwnerFlags::NONE,
+        move |connection, _| {
+            if let Err(e) = mpris.register(&connection) {
+                log_warn!("Failed to publish the MPRIS player: {e}");
+            }
+        },
+        |_, name| log_info!("MPRIS player available as {name}"),
+        |_, name| log_warn!("Could not own {name} on the session bus, media keys won't control playback"),
+    );
+}
+
+impl Mpris {
+    //Exports both MPRIS interfaces and starts announcing changes
+    fn register(&self, connection: &gio::DBusConnection) -> Result<(), glib::Error> {
+        let node = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)?;
+        for interface in [ROOT_INTERFACE, PLAYER_INTERFACE] {
+            let interface_info = node.lookup_interface(interface)
+                .expect("MPRIS interface missing from introspection data");
+            let method_mpris = self.clone();
+            let property_mpris = self.clone();
+            connection.register_object(OBJECT_PATH, &interface_info)
+                .method_call(move |connection, _, _, _, method, parameters, invocation| {
+                    log_debug!("MPRIS call {method}");
+                    match method_mpris.call(&connection, method, &parameters) {
+                        Ok(()) => invocation.return_value(None),
+                        Err(e) => {
+                            log_warn!("MPRIS {method} failed: {e}");
+                            invocation.return_dbus_error("org.freedesktop.DBus.Error.Failed", e.message());
+                        }
+                    }
+                })
+                .property(move |_, _, _, interface, property| property_mpris.get_property(interface, property))
+                .build()?;
+        }
+        self.start_change_notifications(connection);
+        Ok(())
+    }
+
+    //Runs an MPRIS method
+    fn call(&self, connection: &gio::DBusConnection, method: &str, parameters: &Variant) -> Result<(), Error> {
+        let app = self.app.upgrade()
+            .ok_or_else(|| Error::Remote("Application closed".to_string()))?;
+        match method {
+            "Raise" => {
+                if let Some(window) = app.active_window() {
+                    window.present();
+                }
+                return Ok(());
+            }
+            "Quit" => {
+                app.quit();
+                return Ok(());
+            }
+            "OpenUri" => return Err(Error::Remote("Opening videos from other programs is not supported".to_string())),
+            _ => {}
+        }
+
+        let target = self.get_target()
+            .ok_or_else(|| Error::Remote("No video to control".to_string()))?;
+        match method {
+            "Play" => self.set_playing(&target, Some(true)),
+            "Pause" => self.set_playing(&target, Some(false)),
+            "PlayPause" => self.set_playing(&target, None),
+            "Stop" => {
+                self.set_playing(&target, Some(false))?;
+                self.go_to_start(&target)?;
+                self.emit_seeked(connection, &target);
+                Ok(())
+            }
+            "Next" => self.step_split(&target, true),
+            "Previous" => self.step_split(&target, false),
+            "Seek" => {
+                let offset = parameters.child_value(0).get::<i64>().unwrap_or(0);
+                let position = seek_target(self.get_position(&target).unwrap_or(0), offset, self.get_length(&target));
+                self.set_position(&target, position)?;
+                self.emit_seeked(connection, &target);
+                Ok(())
+            }
+            "SetPosition" => {
+                //Requests for a track that is no longer current are ignored as the specification asks
+                let track_id = parameters.child_value(0).str().map(str::to_string);
+                if track_id.as_deref() != Some(self.get_track_id(&target).as_str()) {
+                    return Ok(());
+                }
+                let position = parameters.child_value(1).get::<i64>().unwrap_or(0);
+                let position = match set_position_target(position, self.get_length(&target)) {
+                    Some(position) => position,
+                    None => return Ok(()),
+                };
+                self.set_position(&target, position)?;
+                self.emit_seeked(connection, &target);
+                Ok(())
+            }
+            _ => Err(Error::Remote(format!("Unknown method {method}"))),
+        }
+    }
+
+    //Gets the value of an MPRIS property
+    fn get_property(&self, interface: &str, property: &str) -> Variant {
+        let target = self.get_target();
+        match (interface, property) {
+            (ROOT_INTERFACE, "CanQuit") | (ROOT_INTERFACE, "CanRaise") => true.to_variant(),
+            (ROOT_INTERFACE, "HasTrackList") => false.to_variant(),
+            (ROOT_INTERFACE, "Identity") => "Multi Video Timing Analysis Tool".to_variant(),
+            (ROOT_INTERFACE, "SupportedUriSchemes") | (ROOT_INTERFACE, "SupportedMimeTypes") => Vec::<String>::new().to_variant(),
+            (PLAYER_INTERFACE, "PlaybackStatus") => {
+                let playing = match &target {
+                    Some(Target::Shared(shared_seek_bar)) => shared_seek_bar.is_playing(),
+                    Some(Target::Player(video_player)) => video_player.is_playing(),
+                    None => false,
+                };
+                let status = match (&target, playing) {
+                    (None, _) => "Stopped",
+                    (Some(_), true) => "Playing",
+                    (Some(_), false) => "Paused",
+                };
+                status.to_variant()
+            }
+            (PLAYER_INTERFACE, "Rate") | (PLAYER_INTERFACE, "MinimumRate") | (PLAYER_INTERFACE, "MaximumRate") => 1.0f64.to_variant(),
+            (PLAYER_INTERFACE, "Metadata") => self.get_metadata(target.as_ref()).to_variant(),
+            (PLAYER_INTERFACE, "Position") => {
+                let position = target.as_ref().and_then(|target| self.get_position(target)).unwrap_or(0);
+                ((position / 1000) as i64).to_variant()
+            }
+            (PLAYER_INTERFACE, "CanControl") => true.to_variant(),
+            (PLAYER_INTERFACE, _) => target.is_some().to_variant(),
+            _ => ().to_variant(),
+        }
+    }
+
+    //Tells listeners when the playback status or metadata changes
+    fn start_change_notifications(&self, connection: &gio::DBusConnection) {
+        let connection = connection.clone();
+        let last_values: RefCell<HashMap<&'static str, Variant>> = RefCell::new(HashMap::new());
+        glib::timeout_add_local(Duration::from_millis(REFRESH_INTERVAL_MS), glib::clone!(
+            #[strong(rename_to = mpris)] self,
+            #[weak] connection,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                if mpris.app.upgrade().is_none() {
+                    return glib::ControlFlow::Break;
+                }
+                let mut last_values = last_values.borrow_mut();
+                let mut changed: HashMap<String, Variant> = HashMap::new();
+                for property in WATCHED_PROPERTIES {
+                    let value = mpris.get_property(PLAYER_INTERFACE, property);
+                    if last_values.get(property) != Some(&value) {
+                        last_values.insert(property, value.clone());
+                        changed.insert(property.to_string(), value);
+                    }
+                }
+                if !changed.is_empty() {
+                    let parameters = (PLAYER_INTERFACE, changed, Vec::<String>::new()).to_variant();
+                    if let Err(e) = connection.emit_signal(None, OBJECT_PATH, "org.freedesktop.DBus.Properties", "PropertiesChanged", Some(&parameters)) {
+                        log_warn!("Failed to announce MPRIS changes: {e}");
+                    }
+                }
+                glib::ControlFlow::Continue
+            }
+        ));
+    }
+
+    //Tells listeners the position jumped
+    fn emit_seeked(&self, connection: &gio::DBusConnection, target: &Target) {
+        let position = (self.get_position(target).unwrap_or(0) / 1000) as i64;
+        if let Err(e) = connection.emit_signal(None, OBJECT_PATH, PLAYER_INTERFACE, "Seeked", Some(&(position,).to_variant())) {
+            log_warn!("Failed to announce MPRIS seek: {e}");
+        }
+    }
+
+    //Gets what is controlled: the shared controls in sync view, otherwise the focused or first video player
+    fn get_target(&self) -> Option<Target> {
+        let shared_seek_bar = self.shared_seek_bar.upgrade()?;
+        if shared_seek_bar.get_control_state() {
+            return Some(Target::Shared(shared_seek_bar));
+        }
+        let focused = self.app.upgrade()
+            .and_then(|app| app.active_window())
+            .and_then(|window| GtkWindowExt::focus(&window))
+            .and_then(|widget| widget.ancestor(VideoPlayer::static_type()))
+            .and_downcast::<VideoPlayer>();
+        focused
+            .or_else(|| self.get_video_players().into_iter().next())
+            .map(Target::Player)
+    }
+
+    fn get_video_players(&self) -> Vec<VideoPlayer> {
+        match self.video_container.upgrade() {
+            Some(video_container) => flowbox_children(&video_container)
+                .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    //Gets the start time offset of a video
+    fn get_start_time_offset(&self, video_player_id: &str) -> Option<u64> {
+        let split_table = self.split_table.upgrade()?;
+        let offsets = split_table.get_start_time_offset_row_map();
+        let offset = offsets.borrow().get(video_player_id).map(|entry| entry.get_time());
+        offset.filter(|time| *time != u64::MAX)
+    }
+
+    //Gets the reported position in nanoseconds, counted from the start time offsets in sync view
+    fn get_position(&self, target: &Target) -> Option<u64> {
+        match target {
+            Target::Shared(shared_seek_bar) => {
+                let (video_player_id, position) = shared_seek_bar.get_reference_position()?;
+                let offset = self.get_start_time_offset(&video_player_id).unwrap_or(0);
+                Some(position.saturating_sub(offset))
+            }
+            Target::Player(video_player) => video_player.get_position(),
+        }
+    }
+
+    //Gets the length positions are kept within: the video, or the shared seek bar in sync view
+    fn get_length(&self, target: &Target) -> Option<u64> {
+        let length = match target {
+            Target::Shared(shared_seek_bar) => shared_seek_bar.get_timeline_length(),
+            Target::Player(video_player) => video_player.get_seek_bar()?.get_timeline_length(),
+        };
+        (length > 0).then_some(length)
+    }
+
+    //Moves to a reported position, playing videos under shared control carry on playing
+    fn set_position(&self, target: &Target, position: u64) -> Result<(), Error> {
+        match target {
+            Target::Shared(shared_seek_bar) => {
+                let was_playing = shared_seek_bar.is_playing();
+                shared_seek_bar.go_to_time(position, false)?;
+                if was_playing {
+                    shared_seek_bar.play_all()?;
+                }
+                Ok(())
+            }
+            Target::Player(video_player) => video_player.seek(position),
+        }
+    }
+
+    //Moves back to the start time offsets
+    fn go_to_start(&self, target: &Target) -> Result<(), Error> {
+        match target {
+            Target::Shared(_) => self.set_position(target, 0),
+            Target::Player(video_player) => {
+                let offset = self.get_start_time_offset(&video_player.get_id()).unwrap_or(0);
+                video_player.seek(offset)
+            }
+        }
+    }
+
+    //Plays or pauses, toggling when no state is given
+    fn set_playing(&self, target: &Target, playing: Option<bool>) -> Result<(), Error> {
+        match target {
+            Target::Shared(shared_seek_bar) => {
+                let is_playing = shared_seek_bar.is_playing();
+                match playing.unwrap_or(!is_playing) {
+                    true if !is_playing => shared_seek_bar.play_all(),
+                    false if is_playing => shared_seek_bar.pause_all(),
+                    _ => Ok(()),
+                }
+            }
+            Target::Player(video_player) => {
+                if playing.unwrap_or(!video_player.is_playing()) != video_player.is_playing() {
+                    video_player.toggle_play();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    //Moves to the next or previous split, before the first split previous goes back to the start
+    fn step_split(&self, target: &Target, forward: bool) -> Result<(), Error> {
+        let (video_player_id, position) = match target {
+            Target::Shared(shared_seek_bar) => shared_seek_bar.get_reference_position(),
+            Target::Player(video_player) => video_player.get_position().map(|position| (video_player.get_id(), position)),
+        }.ok_or_else(|| Error::Pipeline("Video has no position yet".to_string()))?;
+
+        match (self.find_adjacent_split(&video_player_id, position, forward), target) {
+            (Some((split_number, _)), Target::Shared(shared_seek_bar)) => {
+                let was_playing = shared_seek_bar.is_playing();
+                shared_seek_bar.go_to_split(split_number)?;
+                if was_playing {
+                    shared_seek_bar.play_all()?;
+                }
+                Ok(())
+            }
+            (Some((_, split_time)), Target::Player(video_player)) => video_player.seek(split_time),
+            (None, _) if !forward => self.go_to_start(target),
+            (None, _) => Ok(()),
+        }
+    }
+
+    //Finds the closest split after or before a position of a video
+    //Output: split number starting at 1 and its time in the video
+    fn find_adjacent_split(&self, video_player_id: &str, position: u64, forward: bool) -> Option<(u32, u64)> {
+        let split_times: Vec<u64> = self.get_rows()?.iter().map(|row| row.get_time(video_player_id)).collect();
+        find_split(&split_times, position, forward)
+    }
+
+    //Gets the name of the segment a video is in
+    fn get_segment_name(&self, video_player_id: &str, position: u64) -> Option<String> {
+        let segments: Vec<(String, u64)> = self.get_rows()?.iter()
+            .map(|row| (row.get_name(), row.get_time(video_player_id)))
+            .collect();
+        segment_name(&segments, position)
+    }
+
+    //Gets the rows of the split table in order
+    fn get_rows(&self) -> Option<Vec<VideoSegment>> {
+        let split_table_liststore = self.split_table.upgrade()?.get_split_table_liststore()?;
+        Some((0..split_table_liststore.n_items())
+            .filter_map(|index| split_table_liststore.item(index).and_downcast::<VideoSegment>())
+            .collect())
+    }
+
+    fn get_track_id(&self, target: &Target) -> String {
+        match target {
+            Target::Shared(_) => String::from("/org/mvtat/Track/sync"),
+            Target::Player(video_player) => format!("/org/mvtat/Track/{}", video_player.get_id()),
+        }
+    }
+
+    //Describes what is playing: the current segment as the title and every loaded file as comments
+    fn get_metadata(&self, target: Option<&Target>) -> HashMap<String, Variant> {
+        let mut metadata = HashMap::new();
+        let target = match target {
+            Some(target) => target,
+            None => {
+                metadata.insert(String::from("mpris:trackid"), object_path(NO_TRACK));
+                return metadata;
+            }
+        };
+        metadata.insert(String::from("mpris:trackid"), object_path(&self.get_track_id(target)));
+
+        let (reference, fallback_title) = match target {
+            Target::Shared(shared_seek_bar) => (shared_seek_bar.get_reference_position(), String::from("Sync view")),
+            Target::Player(video_player) => (
+                video_player.get_position().map(|position| (video_player.get_id(), position)),
+                format!("Video {}", video_player.get_id()),
+            ),
+        };
+        let title = reference
+            .and_then(|(video_player_id, position)| self.get_segment_name(&video_player_id, position))
+            .unwrap_or(fallback_title);
+        metadata.insert(String::from("xesam:title"), title.to_variant());
+
+        if let Target::Player(video_player) = target {
+            if let Some(path) = video_player.get_source_path() {
+                metadata.insert(String::from("xesam:url"), gio::File::for_path(path).uri().to_string().to_variant());
+            }
+        }
+        let files: Vec<String> = self.get_video_players().iter()
+            .filter_map(|video_player| video_player.get_source_path())
+            .collect();
+        metadata.insert(String::from("xesam:comment"), files.to_variant());
+        metadata
+    }
+}
+
+//Gets the position a Seek call moves to
+//Inputs: position in nanoseconds, offset in microseconds as MPRIS sends it, length to stay within
+fn seek_target(position: u64, offset: i64, length: Option<u64>) -> u64 {
+    let target = position.saturating_add_signed(offset.saturating_mul(1000));
+    length.map_or(target, |length| target.min(length))
+}
+
+//Gets the position a SetPosition call moves to, None for negative positions which the specification says to ignore
+//Inputs: position in microseconds as MPRIS sends it, length to stay within
+fn set_position_target(position: i64, length: Option<u64>) -> Option<u64> {
+    let target = u64::try_from(position).ok()?.saturating_mul(1000);
+    Some(length.map_or(target, |length| target.min(length)))
+}
+
+//Finds the closest split further than SPLIT_TOLERANCE after or before a position
+//Inputs: time of each split in order, u64::MAX when not recorded, position, direction
+//Output: split number starting at 1 and its time
+fn find_split(split_times: &[u64], position: u64, forward: bool) -> Option<(u32, u64)> {
+    let mut splits = split_times.iter()
+        .enumerate()
+        .filter(|(_, time)| **time != u64::MAX)
+        .map(|(index, time)| (index as u32 + 1, *time));
+    if forward {
+        splits.find(|(_, time)| *time > position.saturating_add(SPLIT_TOLERANCE))
+    } else {
+        splits.rev().find(|(_, time)| time.saturating_add(SPLIT_TOLERANCE) < position)
+    }
+}
+
+//Gets the name of the segment a position is in, the first segment whose split is after it
+//Input: name and split time of each segment in order, u64::MAX when not recorded
+fn segment_name(segments: &[(String, u64)], position: u64) -> Option<String> {
+    segments.iter()
+        .find(|(_, time)| *time != u64::MAX && *time > position)
+        .map(|(name, _)| name.clone())
+        .filter(|name| !name.is_empty())
+}
+
+fn object_path(path: &str) -> Variant {
+    ObjectPath::try_from(path.to_string())
+        .expect("MPRIS track ids are valid object paths")
+        .to_variant()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    #[test]
+    fn seeks_within_the_video() {
+        assert_eq!(seek_target(10 * SECOND, 5_000_000, Some(60 * SECOND)), 15 * SECOND);
+        assert_eq!(seek_target(10 * SECOND, -5_000_000, Some(60 * SECOND)), 5 * SECOND);
+        assert_eq!(seek_target(10 * SECOND, -20_000_000, Some(60 * SECOND)), 0);
+        assert_eq!(seek_target(10 * SECOND, 100_000_000, Some(60 * SECOND)), 60 * SECOND);
+        assert_eq!(seek_target(10 * SECOND, i64::MAX, Some(60 * SECOND)), 60 * SECOND);
+        assert_eq!(seek_target(10 * SECOND, i64::MIN, Some(60 * SECOND)), 0);
+        assert_eq!(seek_target(u64::MAX, i64::MAX, None), u64::MAX);
+    }
+
+    #[test]
+    fn sets_positions_within_the_video() {
+        assert_eq!(set_position_target(1_500_000, Some(60 * SECOND)), Some(1_500_000_000));
+        assert_eq!(set_position_target(0, Some(60 * SECOND)), Some(0));
+        assert_eq!(set_position_target(90_000_000, Some(60 * SECOND)), Some(60 * SECOND));
+        assert_eq!(set_position_target(i64::MAX, Some(60 * SECOND)), Some(60 * SECOND));
+        assert_eq!(set_position_target(i64::MAX, None), Some(u64::MAX));
+        assert_eq!(set_position_target(-1, Some(60 * SECOND)), None);
+    }
+
+    #[test]
+    fn finds_adjacent_splits() {
+        let splits = [10 * SECOND, u64::MAX, 30 * SECOND];
+        assert_eq!(find_split(&splits, 0, true), Some((1, 10 * SECOND)));
+        assert_eq!(find_split(&splits, 15 * SECOND, true), Some((3, 30 * SECOND)));
+        assert_eq!(find_split(&splits, 30 * SECOND, true), None);
+        assert_eq!(find_split(&splits, 35 * SECOND, false), Some((3, 30 * SECOND)));
+        assert_eq!(find_split(&splits, 15 * SECOND, false), Some((1, 10 * SECOND)));
+        assert_eq!(find_split(&splits, 5 * SECOND, false), None);
+        assert_eq!(find_split(&[], 0, true), None);
+        assert_eq!(find_split(&splits, u64::MAX, true), None);
+    }
+
+    #[test]
+    fn skips_splits_within_tolerance() {
+        let splits = [10 * SECOND, 20 * SECOND];
+        //Sitting on a split, or just short of it, moves past it rather than back onto it
+        assert_eq!(find_split(&splits, 10 * SECOND - SPLIT_TOLERANCE, true), Some((2, 20 * SECOND)));
+        assert_eq!(find_split(&splits, 10 * SECOND - SPLIT_TOLERANCE - 1, true), Some((1, 10 * SECOND)));
+        assert_eq!(find_split(&splits, 20 * SECOND + SPLIT_TOLERANCE, false), Some((1, 10 * SECOND)));
+        assert_eq!(find_split(&splits, 20 * SECOND + SPLIT_TOLERANCE + 1, false), Some((2, 20 * SECOND)));
+    }
+
+    #[test]
+    fn names_the_current_segment() {
+        let segments = [
+            (String::from("Intro"), 10 * SECOND),
+            (String::from("Unrecorded"), u64::MAX),
+            (String::new(), 20 * SECOND),
+            (String::from("Final"), 30 * SECOND),
+        ];
+        assert_eq!(segment_name(&segments, 0).as_deref(), Some("Intro"));
+        assert_eq!(segment_name(&segments, 10 * SECOND), None);
+        assert_eq!(segment_name(&segments, 25 * SECOND).as_deref(), Some("Final"));
+        assert_eq!(segment_name(&segments, 30 * SECOND), None);
+    }
+}
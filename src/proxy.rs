@@ -0,0 +1,207 @@
+use gstreamer::{prelude::*, ClockTime};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::background_job::{spawn_job, wait_for_end, BackgroundJob, JobResult};
+use std::time::UNIX_EPOCH;
+
+//Height in pixels of proxy videos, width follows the aspect ratio of the video
+const PROXY_HEIGHT: i32 = 360;
+//JPEG quality of each proxy frame
+const PROXY_QUALITY: i32 = 80;
+
+//Low resolution copy of a video, filled in by a background thread
+pub struct ProxyData {
+    pub path: PathBuf,
+    //Progress of the transcode between 0.0 and 1.0
+    pub progress: f64,
+    pub error: Option<String>,
+    pub complete: bool,
+    pub cancelled: bool,
+}
+
+impl ProxyData {
+    pub fn new(path: PathBuf) -> Self {
+        ProxyData {
+            path,
+            progress: 0.0,
+            error: None,
+            complete: false,
+            cancelled: false,
+        }
+    }
+
+    //Checks if the proxy finished and can be played
+    pub fn is_ready(&self) -> bool {
+        self.complete && self.error.is_none() && !self.cancelled
+    }
+}
+
+impl BackgroundJob for ProxyData {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn fail(&mut self, error: &str) {
+        self.error = Some(error.to_string());
+    }
+
+    fn complete(&mut self) {
+        self.complete = true;
+    }
+}
+
+//Gets the directory proxy files are stored in
+fn proxy_directory() -> PathBuf {
+    gtk::glib::user_cache_dir().join("multi-video-timing-analysis-tool").join("proxies")
+}
+
+//Gets the location of the proxy for a file
+//The name changes whenever the original file is modified so stale proxies aren't reused
+//The name is a FNV-1a hash, which unlike the standard library's hasher stays the same between builds
+fn proxy_path(path: &str) -> PathBuf {
+    let mut key = path.as_bytes().to_vec();
+    if let Ok(metadata) = std::fs::metadata(path) {
+        key.extend_from_slice(&metadata.len().to_le_bytes());
+        if let Ok(modified) = metadata.modified().and_then(|time| time.duration_since(UNIX_EPOCH).map_err(std::io::Error::other)) {
+            key.extend_from_slice(&modified.as_secs().to_le_bytes());
+            key.extend_from_slice(&modified.subsec_nanos().to_le_bytes());
+        }
+    }
+    proxy_directory().join(format!("{:016x}.mkv", fnv1a_hash(&key)))
+}
+
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+//Starts transcoding a file into an all-intra low resolution proxy in the background
+//Input: path of the video file
+//Output: proxy data that updates as the transcode progresses, complete right away if the proxy already exists
+pub fn generate_proxy(path: &str) -> Arc<Mutex<ProxyData>> {
+    let output = proxy_path(path);
+    if output.exists() {
+        let mut existing = ProxyData::new(output);
+        existing.progress = 1.0;
+        existing.complete = true;
+        return Arc::new(Mutex::new(existing));
+    }
+
+    let path = path.to_string();
+    let data = ProxyData::new(output.clone());
+    spawn_job(format!("generate proxy for {path}"), data, move |data| run_proxy_pipeline(&path, &output, data))
+}
+
+//Decodes the file and encodes every frame as a scaled down JPEG into a matroska file
+//Writes to a temporary file that is only renamed once the whole file is transcoded
+fn run_proxy_pipeline(path: &str, output: &Path, data: &Arc<Mutex<ProxyData>>) -> JobResult {
+    if let Some(directory) = output.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let partial = output.with_extension("mkv.part");
+
+    let pipeline = gstreamer::Pipeline::new();
+    let source = gstreamer::ElementFactory::make("filesrc")
+        .property("location", path)
+        .build()?;
+    let decodebin = gstreamer::ElementFactory::make("decodebin").build()?;
+    let muxer = gstreamer::ElementFactory::make("matroskamux").build()?;
+    let file_sink = gstreamer::ElementFactory::make("filesink")
+        .property("location", partial.to_string_lossy().to_string())
+        .build()?;
+
+    pipeline.add_many([&source, &decodebin, &muxer, &file_sink])?;
+    source.link(&decodebin)?;
+    muxer.link(&file_sink)?;
+
+    //Builds an encoding branch into the muxer for each decoded stream
+    let pipeline_weak = pipeline.downgrade();
+    let muxer_weak = muxer.downgrade();
+    decodebin.connect_pad_added(move |_, src_pad| {
+        let (pipeline, muxer) = match (pipeline_weak.upgrade(), muxer_weak.upgrade()) {
+            (Some(p), Some(m)) => (p, m),
+            _ => return,
+        };
+        let media_type = match src_pad.current_caps().and_then(|caps| caps.structure(0).map(|s| s.name().to_string())) {
+            Some(t) => t,
+            None => return,
+        };
+        let result = if media_type.starts_with("video/") {
+            add_video_branch(&pipeline, &muxer, src_pad)
+        } else if media_type.starts_with("audio/") {
+            add_audio_branch(&pipeline, &muxer, src_pad)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result {
+            log_warn!("Failed to add {media_type} stream to proxy: {e}");
+        }
+    });
+
+    pipeline.set_state(gstreamer::State::Playing)?;
+    let result = wait_for_end(&pipeline, data, || {
+        if let (Some(position), Some(duration)) = (pipeline.query_position::<ClockTime>(), pipeline.query_duration::<ClockTime>()) {
+            if duration.nseconds() > 0 {
+                data.lock().unwrap().progress = (position.nseconds() as f64 / duration.nseconds() as f64).min(1.0);
+            }
+        }
+    });
+    pipeline.set_state(gstreamer::State::Null)?;
+
+    if let Ok(true) = result {
+        std::fs::rename(&partial, output)?;
+        data.lock().unwrap().progress = 1.0;
+        Ok(())
+    } else {
+        let _ = std::fs::remove_file(&partial);
+        result.map(|_| ())
+    }
+}
+
+//Links a decoded video pad through a scaler and JPEG encoder into the muxer
+fn add_video_branch(pipeline: &gstreamer::Pipeline, muxer: &gstreamer::Element, src_pad: &gstreamer::Pad) -> JobResult {
+    let video_convert = gstreamer::ElementFactory::make("videoconvert").build()?;
+    let video_scale = gstreamer::ElementFactory::make("videoscale").build()?;
+    let caps = gstreamer::Caps::builder("video/x-raw")
+        .field("height", PROXY_HEIGHT)
+        .field("pixel-aspect-ratio", gstreamer::Fraction::new(1, 1))
+        .build();
+    let capsfilter = gstreamer::ElementFactory::make("capsfilter")
+        .property("caps", &caps)
+        .build()?;
+    let encoder = gstreamer::ElementFactory::make("jpegenc")
+        .property("quality", PROXY_QUALITY)
+        .build()?;
+    let queue = gstreamer::ElementFactory::make("queue").build()?;
+    let elements = [&video_convert, &video_scale, &capsfilter, &encoder, &queue];
+    link_branch(pipeline, muxer, src_pad, &elements, "video_%u")
+}
+
+//Links a decoded audio pad as raw audio into the muxer
+fn add_audio_branch(pipeline: &gstreamer::Pipeline, muxer: &gstreamer::Element, src_pad: &gstreamer::Pad) -> JobResult {
+    let audio_convert = gstreamer::ElementFactory::make("audioconvert").build()?;
+    let audio_resample = gstreamer::ElementFactory::make("audioresample").build()?;
+    let caps = gstreamer::Caps::builder("audio/x-raw")
+        .field("format", "S16LE")
+        .build();
+    let capsfilter = gstreamer::ElementFactory::make("capsfilter")
+        .property("caps", &caps)
+        .build()?;
+    let queue = gstreamer::ElementFactory::make("queue").build()?;
+    let elements = [&audio_convert, &audio_resample, &capsfilter, &queue];
+    link_branch(pipeline, muxer, src_pad, &elements, "audio_%u")
+}
+
+//Adds a chain of elements between a decoded pad and a new muxer pad
+fn link_branch(pipeline: &gstreamer::Pipeline, muxer: &gstreamer::Element, src_pad: &gstreamer::Pad, elements: &[&gstreamer::Element], muxer_pad: &str) -> JobResult {
+    pipeline.add_many(elements.iter().copied())?;
+    gstreamer::Element::link_many(elements.iter().copied())?;
+    let first = elements.first().ok_or("Empty branch")?;
+    let last = elements.last().ok_or("Empty branch")?;
+    let sink_pad = muxer.request_pad_simple(muxer_pad).ok_or("Muxer refused the stream")?;
+    last.static_pad("src").ok_or("Branch has no output")?.link(&sink_pad)?;
+    src_pad.link(&first.static_pad("sink").ok_or("Branch has no input")?)?;
+    for element in elements {
+        element.sync_state_with_parent()?;
+    }
+    Ok(())
+}
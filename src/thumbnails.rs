@@ -0,0 +1,151 @@
+use gstreamer::{prelude::*, ClockTime, SeekFlags};
+use gtk::{gdk, glib};
+use std::sync::{Arc, Mutex};
+use crate::background_job::{link_first_stream, spawn_job, BackgroundJob, JobResult};
+
+//Width in pixels of each thumbnail, height follows the aspect ratio of the video
+const THUMBNAIL_WIDTH: i32 = 160;
+//Number of thumbnails spread across a video
+const THUMBNAIL_COUNT: u64 = 120;
+//Shortest time between two thumbnails
+const MIN_THUMBNAIL_INTERVAL_NS: u64 = 1_000_000_000;
+
+//Low resolution RGBA frame of a video
+pub struct Thumbnail {
+    pub time: u64,
+    pub width: i32,
+    pub height: i32,
+    pub stride: usize,
+    pub bytes: glib::Bytes,
+}
+
+impl Thumbnail {
+    //Creates a texture of the thumbnail, must be called from the main thread
+    pub fn to_texture(&self) -> gdk::Texture {
+        gdk::MemoryTexture::new(self.width, self.height, gdk::MemoryFormat::R8g8b8a8, &self.bytes, self.stride).upcast()
+    }
+}
+
+//Thumbnails of a video, filled in by a background thread
+pub struct ThumbnailData {
+    pub thumbnails: Vec<Thumbnail>,
+    pub complete: bool,
+    pub cancelled: bool,
+}
+
+impl ThumbnailData {
+    pub fn new() -> Self {
+        ThumbnailData {
+            thumbnails: Vec::new(),
+            complete: false,
+            cancelled: false,
+        }
+    }
+
+    //Gets the thumbnail closest to a time
+    pub fn closest(&self, time: u64) -> Option<&Thumbnail> {
+        self.thumbnails.iter().min_by_key(|thumbnail| thumbnail.time.abs_diff(time))
+    }
+}
+
+impl Default for ThumbnailData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackgroundJob for ThumbnailData {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn complete(&mut self) {
+        self.complete = true;
+    }
+}
+
+//Starts generating thumbnails of a file in the background
+//Input: path of the video file
+//Output: thumbnail data that fills in as frames are decoded
+pub fn generate_thumbnails(path: &str) -> Arc<Mutex<ThumbnailData>> {
+    let path = path.to_string();
+    spawn_job(format!("generate thumbnails for {path}"), ThumbnailData::new(), move |data| run_thumbnail_pipeline(&path, data))
+}
+
+//Seeks through the file to keyframes at even intervals grabbing a scaled down frame at each one
+fn run_thumbnail_pipeline(path: &str, data: &Arc<Mutex<ThumbnailData>>) -> JobResult {
+    let pipeline = gstreamer::Pipeline::new();
+    let source = gstreamer::ElementFactory::make("filesrc")
+        .property("location", path)
+        .build()?;
+    let decodebin = gstreamer::ElementFactory::make("decodebin").build()?;
+    let video_convert = gstreamer::ElementFactory::make("videoconvert").build()?;
+    let video_scale = gstreamer::ElementFactory::make("videoscale").build()?;
+    let caps = gstreamer::Caps::builder("video/x-raw")
+        .field("format", "RGBA")
+        .field("width", THUMBNAIL_WIDTH)
+        .field("pixel-aspect-ratio", gstreamer::Fraction::new(1, 1))
+        .build();
+    let capsfilter = gstreamer::ElementFactory::make("capsfilter")
+        .property("caps", &caps)
+        .build()?;
+    let appsink = gstreamer::ElementFactory::make("appsink")
+        .property("sync", false)
+        .property("max-buffers", 1u32)
+        .build()?;
+
+    pipeline.add_many([&source, &decodebin, &video_convert, &video_scale, &capsfilter, &appsink])?;
+    source.link(&decodebin)?;
+    gstreamer::Element::link_many([&video_convert, &video_scale, &capsfilter, &appsink])?;
+
+    //Only the video stream is decoded
+    link_first_stream(&decodebin, &video_convert, "video/");
+
+    pipeline.set_state(gstreamer::State::Paused)?;
+    let (result, _, _) = pipeline.state(ClockTime::from_seconds(10));
+    result?;
+
+    let duration = pipeline.query_duration::<ClockTime>()
+        .ok_or("Failed to query duration")?
+        .nseconds();
+    let interval = (duration / THUMBNAIL_COUNT).max(MIN_THUMBNAIL_INTERVAL_NS);
+
+    let mut time = 0;
+    while time < duration {
+        if data.lock().unwrap().is_cancelled() {
+            break;
+        }
+        pipeline.seek_simple(SeekFlags::FLUSH | SeekFlags::KEY_UNIT, ClockTime::from_nseconds(time))?;
+        let sample = match appsink.emit_by_name::<Option<gstreamer::Sample>>("pull-preroll", &[]) {
+            Some(s) => s,
+            None => break,
+        };
+        //Key unit seeks land on the keyframe before the requested time, which several requests can share
+        if let Some(thumbnail) = sample_to_thumbnail(&sample) {
+            let mut data = data.lock().unwrap();
+            if data.thumbnails.last().is_none_or(|last| last.time != thumbnail.time) {
+                data.thumbnails.push(thumbnail);
+            }
+        }
+        time += interval;
+    }
+
+    pipeline.set_state(gstreamer::State::Null)?;
+    Ok(())
+}
+
+//Copies a decoded RGBA sample into a thumbnail, timed at the frame that was decoded
+fn sample_to_thumbnail(sample: &gstreamer::Sample) -> Option<Thumbnail> {
+    let info = gstreamer_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+    let buffer = sample.buffer()?;
+    //Converted to stream time through the segment of the sample, the time positions and seeks use
+    let time = sample.segment()?.downcast_ref::<ClockTime>()?.to_stream_time(buffer.pts()?)?;
+    let map = buffer.map_readable().ok()?;
+    Some(Thumbnail {
+        time: time.nseconds(),
+        width: info.width() as i32,
+        height: info.height() as i32,
+        stride: info.stride()[0] as usize,
+        bytes: glib::Bytes::from(map.as_slice()),
+    })
+}
@@ -1,11 +1,12 @@
-use std::{cell::{RefCell, Cell}, iter::Once, thread::{self, current}, time::Duration};
+use std::{cell::{RefCell, Cell}, collections::BTreeMap, iter::Once, rc::Rc, thread::{self, current}, time::Duration};
 use glib::BoolError;
-use gstreamer::{event::{Seek, Step}, prelude::*, Clock, ClockTime, Element, Pipeline, SeekFlags, SeekType };
+use gstreamer::{event::{Seek, Step}, prelude::*, Clock, ClockTime, Element, MessageView, Pipeline, SeekFlags, SeekType };
 use gtk::{self, Ordering};
 use gtk::gdk;
 use once_cell::sync::OnceCell;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::AtomicBool;
+use crate::error::Error;
+use crate::frame_index::FrameIndex;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PlaybackDirection {
@@ -16,6 +17,74 @@ pub enum PlaybackDirection {
 pub struct PipelineState {
     pub direction: PlaybackDirection,
 }
+
+//Messages from the pipeline bus that the owner of the pipeline should know about
+#[derive(Clone, Debug)]
+pub enum PipelineEvent {
+    Error { message: String, debug: Option<String> },
+    Warning { message: String, debug: Option<String> },
+    MissingPlugin { name: String },
+    EndOfStream,
+    ClampEndReached,
+}
+
+//Latest quality of service report posted by the sinks when frames are late or dropped
+#[derive(Clone, Debug, Default)]
+pub struct QosStats {
+    pub messages: u64,
+    //How late the last frame arrived in nanoseconds, negative when early
+    pub jitter: i64,
+    //Long term rate compared to real time the sink asked upstream to process at
+    pub proportion: f64,
+    pub quality: i32,
+    pub processed: u64,
+    pub dropped: u64,
+}
+
+//Snapshot of a pipeline shown in the diagnostics window
+#[derive(Clone, Debug)]
+pub struct PipelineDiagnostics {
+    pub state: gstreamer::State,
+    pub pending_state: gstreamer::State,
+    pub clock: Option<Clock>,
+    pub base_time: Option<ClockTime>,
+    pub position: Option<ClockTime>,
+    pub duration: Option<ClockTime>,
+    pub rate: Option<f64>,
+    pub direction: PlaybackDirection,
+    pub clamp: Option<(ClockTime, ClockTime)>,
+    pub frame_duration: Option<u64>,
+    pub rendered_frames: Option<u64>,
+    pub dropped_frames: Option<u64>,
+    pub qos: QosStats,
+}
+
+//Suggests what to install when GStreamer is missing an element or decoder
+pub fn missing_plugin_hint(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let package = if lower.contains("h.264") || lower.contains("h264") || lower.contains("h.265") || lower.contains("h265") || lower.contains("hevc") {
+        "gst-libav (or a hardware decoder plugin)"
+    } else if lower.contains("aac") || lower.contains("mp3") || lower.contains("mpeg") {
+        "gst-libav or gst-plugins-ugly"
+    } else if lower.contains("av1") {
+        "gst-plugins-bad (dav1d) or gst-libav"
+    } else if lower.contains("vp8") || lower.contains("vp9") || lower.contains("jpeg") || lower.contains("matroska") {
+        "gst-plugins-good"
+    } else if lower.contains("gtk4paintablesink") {
+        "gst-plugin-gtk4"
+    } else {
+        "gst-plugins-base, gst-plugins-good, gst-plugins-bad, gst-plugins-ugly and gst-libav"
+    };
+    format!("GStreamer is missing \"{name}\". Installing {package} usually provides it.")
+}
+
+//Creates a named pipeline element, reporting which plugin is missing if it can't be made
+fn make_element(factory: &str, name: &str) -> Result<Element, Error> {
+    gstreamer::ElementFactory::make(factory)
+        .name(name)
+        .build()
+        .map_err(|_| Error::Pipeline(format!("Failed to build {factory} element. {}", missing_plugin_hint(factory))))
+}
 pub struct VolumeControl {
     volume_element: Element,
     volume: Cell<f64>,
@@ -28,7 +97,7 @@ impl VolumeControl {
         if self.is_muted.get() {
             return;
         }
-        println!("Muting audio");
+        log_debug!("Muting audio");
         self.volume_element.set_property("volume", 0.0);
         self.is_muted.set(true);
     }
@@ -39,10 +108,18 @@ impl VolumeControl {
             return;
         }
         let previous_volume = self.volume.get();
-        println!("Unmuting audio: volume: {previous_volume}");
+        log_debug!("Unmuting audio: volume: {previous_volume}");
         self.volume_element.set_property("volume", previous_volume);
         self.is_muted.set(false);
     }
+
+    //Sets the audio volume of the pipeline. Applied once unmuted if currently muted
+    pub fn set_volume(&self, volume: f64) {
+        self.volume.set(volume);
+        if !self.is_muted.get() {
+            self.volume_element.set_property("volume", volume);
+        }
+    }
 }
 
 pub struct VideoClamp {
@@ -62,34 +139,78 @@ impl VideoClamp {
     pub fn clamp_position(&self, position: ClockTime) -> ClockTime {
         position.min(self.end_time).max(self.start_time)
     }
+}
 
-    //Checks pipeline position and clamps position if necessary
-    pub fn check_and_clamp_position(&self, pipeline: &Pipeline) -> Result<bool, Box<dyn std::error::Error>> {
-        //check only if pipeline is currently playing
-        if pipeline.current_state() == gstreamer::State::Playing {
-            //Query current position
-            if let Some(position) = pipeline.query_position::<ClockTime>() {
-                //Pause and seek to clamp time if position is outside bounds
-                if position >= self.end_time {
-                    println!("clamping end");
-                    pipeline.set_state(gstreamer::State::Paused)?;
-                    pipeline.state(ClockTime::from_seconds(2)).0?;
-                    pipeline.seek_simple(
-                        SeekFlags::FLUSH | SeekFlags::ACCURATE, 
-                        self.end_time
-                    )?;
-                    return Ok(true);
-                } else if position < self.start_time {
-                    println!("clamping start");
-                    pipeline.seek_simple(
-                        SeekFlags::FLUSH | SeekFlags::ACCURATE, 
-                        self.start_time
-                    )?;
-                    return Ok(true);
-                }
+//Most memory the decoded frames kept for stepping backward may use, about 20 frames of 1080p video
+const FRAME_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+//Recently displayed frames of a pipeline keyed by presentation time
+pub struct FrameCache {
+    frames: BTreeMap<u64, gstreamer::Sample>,
+    //Size of the cached frames in bytes
+    size: usize,
+    //Presentation time of the frame currently on screen
+    current_pts: Option<u64>,
+    //Presentation time of a cached frame shown while the pipeline seeks to it
+    pending_pts: Option<u64>,
+}
+
+impl FrameCache {
+    pub fn new() -> Self {
+        FrameCache {
+            frames: BTreeMap::new(),
+            size: 0,
+            current_pts: None,
+            pending_pts: None,
+        }
+    }
+
+    //Records the frame that reached the sink
+    fn set_current(&mut self, pts: u64) {
+        self.current_pts = Some(pts);
+        if self.pending_pts == Some(pts) {
+            self.pending_pts = None;
+        }
+    }
+
+    //Stores a frame, dropping the frames furthest away once over the size limit
+    fn insert(&mut self, pts: u64, sample: gstreamer::Sample) {
+        self.size += sample_size(&sample);
+        if let Some(replaced) = self.frames.insert(pts, sample) {
+            self.size -= sample_size(&replaced);
+        }
+        while self.size > FRAME_CACHE_MAX_BYTES && self.frames.len() > 1 {
+            let first = *self.frames.keys().next().unwrap();
+            let last = *self.frames.keys().next_back().unwrap();
+            let furthest = if pts.abs_diff(first) >= pts.abs_diff(last) { first } else { last };
+            if let Some(removed) = self.frames.remove(&furthest) {
+                self.size -= sample_size(&removed);
             }
         }
-        Ok(false)
+    }
+
+    //Gets the cached frame at an exact time
+    fn get(&self, pts: u64) -> Option<gstreamer::Sample> {
+        self.frames.get(&pts).cloned()
+    }
+
+    //Gets the frame directly before a time
+    //Input: time of the current frame, longest gap allowed between the two frames
+    //Output: time and sample of the previous frame, None if it was never decoded
+    fn previous(&self, pts: u64, max_gap: u64) -> Option<(u64, gstreamer::Sample)> {
+        self.frames.range(..pts).next_back()
+            .filter(|(previous_pts, _)| pts - **previous_pts <= max_gap)
+            .map(|(previous_pts, sample)| (*previous_pts, sample.clone()))
+    }
+}
+
+fn sample_size(sample: &gstreamer::Sample) -> usize {
+    sample.buffer().map_or(0, |buffer| buffer.size())
+}
+
+impl Default for FrameCache {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -106,9 +227,13 @@ pub struct VideoPipeline {
     state: RefCell<PipelineState>,
     frame_duration: OnceCell<u64>,
     clamp: Arc<Mutex<Option<VideoClamp>>>,
-    monitor_thread: Option<thread::JoinHandle<()>>,
-    monitor_active: Arc<AtomicBool>,
+    bus_watch: RefCell<Option<gstreamer::bus::BusWatchGuard>>,
+    event_callback: Rc<RefCell<Option<Box<dyn Fn(PipelineEvent)>>>>,
     volume_control: Arc<Mutex<Option<VolumeControl>>>,
+    frame_cache: Arc<Mutex<FrameCache>>,
+    cached_frame_callback: RefCell<Option<Box<dyn Fn(gstreamer::Sample)>>>,
+    frame_index: RefCell<Option<Arc<Mutex<FrameIndex>>>>,
+    qos_stats: Rc<RefCell<QosStats>>,
 }
 
 
@@ -121,15 +246,21 @@ impl VideoPipeline {
             state: RefCell::new(PipelineState::new()),
             frame_duration: OnceCell::new(),
             clamp: Arc::new(Mutex::new(None)),
-            monitor_thread: None,
-            monitor_active: Arc::new(AtomicBool::new(false)),
+            bus_watch: RefCell::new(None),
+            event_callback: Rc::new(RefCell::new(None)),
             volume_control: Arc::new(Mutex::new(None)),
+            frame_cache: Arc::new(Mutex::new(FrameCache::new())),
+            cached_frame_callback: RefCell::new(None),
+            frame_index: RefCell::new(None),
+            qos_stats: Rc::new(RefCell::new(QosStats::default())),
         }
     }
 
     // Sets state to NULL to be cleaned up
     pub fn cleanup(&mut self) {
-        self.pipeline.set_state(gstreamer::State::Null).unwrap();
+        if let Err(e) = self.pipeline.set_state(gstreamer::State::Null) {
+            log_error!("Failed to stop pipeline: {e}");
+        }
     }
 
     // Resets object to default values
@@ -145,7 +276,7 @@ impl VideoPipeline {
         let position = match self.pipeline.query_position::<gstreamer::ClockTime>() {
             Some(pos) => pos,
             None => {
-                eprintln!("Unable to get current position");
+                log_warn!("Unable to get current position");
                 return false;
             }
         };
@@ -173,51 +304,59 @@ impl VideoPipeline {
     }
 
     // Sets video playback to inputted ClockTime
-    pub fn seek_position(&self, position: gstreamer::ClockTime) -> Result<(), Box<dyn std::error::Error>> {
-        let duration = self.pipeline.query_duration::<ClockTime>().ok_or("failed to get pipeline position")?;
+    pub fn seek_position(&self, position: gstreamer::ClockTime) -> Result<(), Error> {
+        let duration = self.pipeline.query_duration::<ClockTime>()
+            .ok_or_else(|| Error::Pipeline("Failed to get pipeline duration".to_string()))?;
         if position == duration {
-            let frame_time = match self.frame_duration.get() {
-                Some(duration) => *duration,
-                None => self.set_frame_duration().unwrap(),
-            };
+            let frame_time = self.frame_duration.get().copied()
+                .or_else(|| self.set_frame_duration())
+                .ok_or_else(|| Error::Pipeline("Failed to get frame duration".to_string()))?;
             let seek_position = position - ClockTime::from_nseconds(frame_time);
-            self.pipeline.seek_simple(gstreamer::SeekFlags::FLUSH, seek_position)?;
-            println!("seeked safely to end: (1 frame before): Position: {position}, safe position: {seek_position}");
+            self.seek_to(gstreamer::SeekFlags::FLUSH, seek_position)?;
+            log_debug!("Seeked one frame before the end: position {position}, seeked to {seek_position}");
         } else {
-            self.pipeline.seek_simple(gstreamer::SeekFlags::FLUSH, position)?;
-            println!("seeked to {position}");
+            self.seek_to(gstreamer::SeekFlags::FLUSH, position)?;
+            log_debug!("Seeked to {position}");
         }
         Ok(())
     }
 
-    // Retrieves the percent complete the video playback is at
-    pub fn position_to_percent(&self) -> Result<f64, glib::Error> {
-        let position = match self.pipeline.query_position::<gstreamer::ClockTime>() {
-            Some(pos) => pos,
-            None => {
-                eprintln!("Failed to get pipeline position");
-                return Err(glib::Error::new(glib::FileError::Failed, "Failed to get pipeline position"));
-            }
-        };
-
-        let total_duration = match self.pipeline.query_duration::<gstreamer::ClockTime>() {
-            Some(dur) => dur,
-            None => {
-                eprintln!("Unable to get current duration");
-                return Err(glib::Error::new(glib::FileError::Failed, "Unable to get pipeline duration"));
+    //Seeks forward playback to a position
+    //While clamped the seek is a segment seek that stops at the end clamp, posting SEGMENT_DONE when reached
+    fn seek_to(&self, flags: SeekFlags, position: ClockTime) -> Result<(), Error> {
+        let end = self.clamp.lock().unwrap().as_ref().map(|clamp| clamp.end_time);
+        let seek_event = match end {
+            Some(end) => {
+                //An empty segment would never preroll so seeking to the end shows the last frame before it
+                let frame_duration = self.frame_duration.get().copied().unwrap_or(1_000_000);
+                let last_frame = end.saturating_sub(ClockTime::from_nseconds(frame_duration));
+                Seek::new(
+                    1.0,
+                    flags | SeekFlags::SEGMENT,
+                    SeekType::Set,
+                    position.min(last_frame),
+                    SeekType::Set,
+                    end,
+                )
             }
+            None => Seek::new(
+                1.0,
+                flags,
+                SeekType::Set,
+                position,
+                SeekType::None,
+                ClockTime::NONE,
+            ),
         };
-
-        let position_ns = position.nseconds();
-        let duration_ns = total_duration.nseconds();
-
-        let percent = position_ns as f64 / duration_ns as f64 * 100.0;
-        
-        Ok(percent)
+        if self.pipeline.send_event(seek_event) {
+            Ok(())
+        } else {
+            Err(Error::Pipeline(format!("Failed to seek to {position}")))
+        }
     }
 
     //Gets pipeline current progression through the clamped positions
-    pub fn position_to_logical_percent(&self) -> Result<f64, String> {
+    pub fn position_to_logical_percent(&self) -> Result<f64, Error> {
         //Get current pipeline position waiting for a stable state
         let mut attempts = 0;
         const MAX_ATTEMPTS: u8 = 20;
@@ -228,7 +367,7 @@ impl VideoPipeline {
                 break;
             } else {
                 attempts += 1;
-                println!("attempts at getting position to logical percent");
+                log_trace!("Waiting for a position to compute the logical percent");
                 if attempts >= MAX_ATTEMPTS {
                     break;
                 }
@@ -238,8 +377,8 @@ impl VideoPipeline {
         let position = match position_opt {
             Some(pos) => pos,
             None => {
-                eprintln!("Failed to get pipeline position");
-                return Err("Failed to get pipeline position".to_string());
+                log_warn!("Failed to get pipeline position");
+                return Err(Error::Pipeline("Failed to get pipeline position".to_string()));
             }
         };
         //Calculates current progression 
@@ -251,93 +390,68 @@ impl VideoPipeline {
         Ok(percent)
     }
 
-    // Retrieves the position as the percentage of the total duration of the video playback
-    pub fn percent_to_position(&self, percent: f64) -> Result<u64, glib::Error> {
-        let total_duration = match self.pipeline.query_duration::<gstreamer::ClockTime>() {
-            Some(dur) => dur.nseconds(),
-            None => {
-                eprintln!("Unable to get current duration");
-                return Err(glib::Error::new(glib::FileError::Failed, "Unable to get pipeline duration"));
-            }
-        };
-
-        println!("Duration: {total_duration}");
-        println!("Percent: {percent}");
-
-        let new_position = (total_duration as f64 * percent) as u64;
-        Ok(new_position)
-    }
-
     // Sets up video pipeline
-    pub fn build_pipeline(&self, path: Option<&str>) {
-        let path = path.unwrap();
-        println!("building pipeline from {path}");
+    pub fn build_pipeline(&self, path: Option<&str>) -> Result<(), Error> {
+        let path = path.ok_or_else(|| Error::Pipeline("No file to build the pipeline from".to_string()))?;
+        log_info!("Building pipeline from {path}");
         
         // Sets up pipeline elements
-        // let source = gstreamer::ElementFactory::make("uridecodebin")
-        //     .name("source")
-        //     .property("uri", uri)
-        //     .build()
-        //     .expect("Failed to build source element");
-        let source = gstreamer::ElementFactory::make("filesrc")
-            .name("source")
-            .property("location", path)
-            .build()
-            .expect("Failed to build source element");
-        let decodebin = gstreamer::ElementFactory::make("decodebin")
-            .name("decodebin")
-            .build()
-            .expect("Could not create decodebin");
-
-        let audio_convert = gstreamer::ElementFactory::make("audioconvert")
-            .name("audio_convert")
-            .build()
-            .expect("Failed to build audioconvert element");
-        let volume = gstreamer::ElementFactory::make("volume")
-            .name("volume")
-            .build()
-            .expect("Failed to build volume element");
-        let audio_resample = gstreamer::ElementFactory::make("audioresample")
-            .name("audio_resample")
-            .build()
-            .expect("Failed to build audio resampler element");
-        let audio_sink = gstreamer::ElementFactory::make("autoaudiosink")
-            .name("audio_sink")
-            .build()
-            .expect("Failed to build audiosink element");
-        let video_convert = gstreamer::ElementFactory::make("videoconvert")
-            .name("video_convert")
-            .build()
-            .expect("Failed to vuild video convert element");
-        let video_rate = gstreamer::ElementFactory::make("videorate")
-            .name("video_rate")
-            .build()
-            .expect("Failed to build video rate elements");
-        let video_scale = gstreamer::ElementFactory::make("videoscale")
-            .name("video_scale")
-            .build()
-            .expect("Failed to build video scale element");
-
+        let source = make_element("filesrc", "source")?;
+        source.set_property("location", path);
+        let decodebin = make_element("decodebin", "decodebin")?;
+
+        let audio_convert = make_element("audioconvert", "audio_convert")?;
+        let volume = make_element("volume", "volume")?;
+        let audio_resample = make_element("audioresample", "audio_resample")?;
+        let audio_sink = make_element("autoaudiosink", "audio_sink")?;
+        let video_convert = make_element("videoconvert", "video_convert")?;
+        let video_rate = make_element("videorate", "video_rate")?;
+        let video_scale = make_element("videoscale", "video_scale")?;
 
         // Connects elements in pipeline
-        self.pipeline.add_many([&source, &decodebin, &audio_convert, &volume, &audio_resample, &audio_sink, &video_convert, &video_rate, &video_scale, &self.gtksink]).unwrap();
-        source.link(&decodebin).unwrap();
+        self.pipeline.add_many([&source, &decodebin, &audio_convert, &volume, &audio_resample, &audio_sink, &video_convert, &video_rate, &video_scale, &self.gtksink])
+            .map_err(|e| Error::Pipeline(format!("Failed to add elements to pipeline: {e}")))?;
+        source.link(&decodebin)
+            .map_err(|e| Error::Pipeline(format!("Failed to link source: {e}")))?;
         gstreamer::Element::link_many([&audio_convert, &volume, &audio_resample, &audio_sink])
-            .expect("Failed to link audio elements");
+            .map_err(|e| Error::Pipeline(format!("Failed to link audio elements: {e}")))?;
         gstreamer::Element::link_many([&video_convert, &video_rate, &video_scale, &self.gtksink])
-            .expect("Failed to link video elements");
+            .map_err(|e| Error::Pipeline(format!("Failed to link video elements: {e}")))?;
+
+        //Records the frame on screen, and keeps frames shown while paused or stepping so stepping backward doesn't need to decode again
+        if let Some(sink_pad) = self.gtksink.static_pad("sink") {
+            let frame_cache = Arc::clone(&self.frame_cache);
+            sink_pad.add_probe(gstreamer::PadProbeType::BUFFER, move |pad, info| {
+                if let Some(gstreamer::PadProbeData::Buffer(ref buffer)) = info.data {
+                    if let Some(pts) = buffer.pts() {
+                        let mut frame_cache = frame_cache.lock().unwrap();
+                        frame_cache.set_current(pts.nseconds());
+                        let playing = pad.parent_element().is_some_and(|sink| sink.current_state() == gstreamer::State::Playing);
+                        if let (false, Some(caps)) = (playing, pad.current_caps()) {
+                            //A copy is kept so the decoder's buffers go back to its pool
+                            let sample = gstreamer::Sample::builder()
+                                .buffer(&buffer.copy_deep().unwrap_or_else(|_| buffer.copy()))
+                                .caps(&caps)
+                                .build();
+                            frame_cache.insert(pts.nseconds(), sample);
+                        }
+                    }
+                }
+                gstreamer::PadProbeReturn::Ok
+            });
+        }
 
         let audio_convert_weak = audio_convert.downgrade();
         let video_convert_weak = video_convert.downgrade();
         
         // Connects source pads to video and audio sink
         decodebin.connect_pad_added(move |src, src_pad| {
-            println!("Recieved new pad {} from {}", src_pad.name(), src.name());
+            log_debug!("Received new pad {} from {}", src_pad.name(), src.name());
 
             let audio_convert = match audio_convert_weak.upgrade() {
                 Some(audio_convert) => audio_convert,
                 None => {
-                    println!("Audio convert element has been dropped");
+                    log_warn!("Audio convert element has been dropped");
                     return;
                 }
             };
@@ -345,46 +459,120 @@ impl VideoPipeline {
             let video_convert = match video_convert_weak.upgrade() {
                 Some(video_convert) => video_convert,
                 None => {
-                    println!("Video convert element has been dropped");
+                    log_warn!("Video convert element has been dropped");
                     return;
                 }
             };
 
-            let has_caps = src_pad.current_caps().is_some();
-            let pad_type = src_pad.current_caps()
-                .expect("Failed to get caps of new pad")
-                .structure(0)
-                .expect("Failed to get first strcuture")
-                .name();
+            let pad_type = match src_pad.current_caps().and_then(|caps| caps.structure(0).map(|s| s.name().to_string())) {
+                Some(pad_type) => pad_type,
+                None => {
+                    log_warn!("New pad {} has no caps. Ignoring", src_pad.name());
+                    return;
+                }
+            };
 
             // Links audio pad
             if let Some(audio_sink_pad) = audio_convert.static_pad("sink") {
                 if audio_sink_pad.is_linked() {
-                    println!("Audio pad is already linked. Ignoring");
+                    log_debug!("Audio pad is already linked. Ignoring");
                     return;
                 }
-                if has_caps && src_pad.link(&audio_sink_pad).is_ok() {
-                    println!("{} pad linked successfully!", pad_type);
+                if src_pad.link(&audio_sink_pad).is_ok() {
+                    log_debug!("{} pad linked", pad_type);
                 } else {
-                    println!("Failed to link {} to audio pad", pad_type);
+                    log_debug!("Failed to link {} to audio pad", pad_type);
                 }
             }
             
             // Link video pad
             if let Some(video_sink_pad) = video_convert.static_pad("sink") {
                 if video_sink_pad.is_linked() {
-                    println!("Video pad is already linked. Ignoring");
+                    log_debug!("Video pad is already linked. Ignoring");
                     return;
                 }
-                if has_caps && src_pad.link(&video_sink_pad).is_ok() {
-                    println!("{} pad linked successfully!", pad_type);
+                if src_pad.link(&video_sink_pad).is_ok() {
+                    log_debug!("{} pad linked", pad_type);
                 } else {
-                    println!("Failed to link {} to video pad", pad_type);
+                    log_debug!("Failed to link {} to video pad", pad_type);
                 }
             }
         });
 
-        println!("pipeline built");
+        log_debug!("Pipeline built");
+
+        //Forwards bus messages to the owner of the pipeline
+        //Segment seeks post SEGMENT_DONE instead of EOS when playback reaches the end clamp
+        let pipeline_weak = self.pipeline.downgrade();
+        let event_callback = Rc::clone(&self.event_callback);
+        let qos_stats = Rc::clone(&self.qos_stats);
+        let bus = self.pipeline.bus().ok_or_else(|| Error::Pipeline("Pipeline has no bus".to_string()))?;
+        let bus_watch = bus.add_watch_local(move |_, message| {
+            let pipeline = match pipeline_weak.upgrade() {
+                Some(p) => p,
+                None => return glib::ControlFlow::Break,
+            };
+            let event = match message.view() {
+                MessageView::SegmentDone(..) => {
+                    log_debug!("End clamp reached, pausing");
+                    if let Err(e) = pipeline.set_state(gstreamer::State::Paused) {
+                        log_error!("Failed to pause at end clamp: {e}");
+                    }
+                    Some(PipelineEvent::ClampEndReached)
+                }
+                MessageView::Eos(..) => {
+                    log_debug!("End of stream reached");
+                    Some(PipelineEvent::EndOfStream)
+                }
+                MessageView::Error(err) => {
+                    log_error!("Pipeline error: {} ({:?})", err.error(), err.debug());
+                    Some(PipelineEvent::Error {
+                        message: err.error().to_string(),
+                        debug: err.debug().map(|debug| debug.to_string()),
+                    })
+                }
+                MessageView::Warning(warning) => {
+                    log_warn!("Pipeline warning: {} ({:?})", warning.error(), warning.debug());
+                    Some(PipelineEvent::Warning {
+                        message: warning.error().to_string(),
+                        debug: warning.debug().map(|debug| debug.to_string()),
+                    })
+                }
+                MessageView::Qos(qos) => {
+                    //Kept for the diagnostics window, the sink already handles late frames
+                    let (jitter, proportion, quality) = qos.values();
+                    let (processed, dropped) = qos.stats();
+                    let mut stats = qos_stats.borrow_mut();
+                    stats.messages += 1;
+                    stats.jitter = jitter;
+                    stats.proportion = proportion;
+                    stats.quality = quality;
+                    stats.processed = processed.value().max(0) as u64;
+                    stats.dropped = dropped.value().max(0) as u64;
+                    None
+                }
+                MessageView::Element(element) => {
+                    //Decodebin posts a missing-plugin message when it has no decoder for a stream
+                    element.structure()
+                        .filter(|structure| structure.name() == "missing-plugin")
+                        .map(|structure| {
+                            let name = structure.get::<String>("name")
+                                .ok()
+                                .or_else(|| structure.get::<gstreamer::Caps>("detail").ok().map(|caps| caps.to_string()))
+                                .unwrap_or_else(|| "unknown plugin".to_string());
+                            PipelineEvent::MissingPlugin { name }
+                        })
+                }
+                _ => None,
+            };
+            if let Some(event) = event {
+                if let Some(callback) = event_callback.borrow().as_ref() {
+                    callback(event);
+                }
+            }
+            glib::ControlFlow::Continue
+        }).map_err(|e| Error::Pipeline(format!("Failed to watch pipeline bus: {e}")))?;
+        *self.bus_watch.borrow_mut() = Some(bus_watch);
 
         //Set initial volumne control
         *self.volume_control.lock().unwrap() = Some(VolumeControl {
@@ -396,11 +584,48 @@ impl VideoPipeline {
         //Sets initial state to paused
         self.pipeline
             .set_state(gstreamer::State::Paused)
-            .expect("Failed to set pipeline state to paused");
+            .map_err(|e| Error::Pipeline(format!("Failed to open {path}: {e}")))?;
         
         let _ = self.pipeline.state(ClockTime::from_seconds(2));
 
         self.set_frame_duration();
+        Ok(())
+    }
+
+    //Sets the function called with errors, warnings and other pipeline bus messages
+    pub fn set_event_callback<F>(&self, callback: F)
+    where F: Fn(PipelineEvent) + 'static {
+        *self.event_callback.borrow_mut() = Some(Box::new(callback));
+    }
+
+    //Switches the file being decoded while keeping the position and play state
+    //Used to swap between a video and its proxy, which share the same timeline
+    pub fn set_source(&self, path: &str) -> Result<(), Error> {
+        let source = self.pipeline.by_name("source")
+            .ok_or_else(|| Error::Pipeline("Pipeline has not been built".to_string()))?;
+        let position = self.pipeline.query_position::<ClockTime>();
+        let was_playing = self.pipeline.current_state() == gstreamer::State::Playing;
+        log_info!("Switching source to {path}");
+
+        //Decodebin drops its pads when stopped and relinks the new streams once paused again
+        self.pipeline.set_state(gstreamer::State::Ready)
+            .map_err(|e| Error::Pipeline(format!("Failed to stop pipeline: {e}")))?;
+        source.set_property("location", path);
+        *self.frame_cache.lock().unwrap() = FrameCache::new();
+        self.pipeline.set_state(gstreamer::State::Paused)
+            .map_err(|e| Error::Pipeline(format!("Failed to pause pipeline: {e}")))?;
+        self.pipeline.state(ClockTime::from_seconds(5)).0
+            .map_err(|e| Error::Pipeline(format!("Pipeline did not preroll: {e}")))?;
+
+        if let Some(position) = position {
+            self.seek_to(SeekFlags::FLUSH | SeekFlags::ACCURATE, position)
+                .map_err(|e| Error::Pipeline(format!("Failed to restore position: {e}")))?;
+        }
+        if was_playing {
+            self.pipeline.set_state(gstreamer::State::Playing)
+                .map_err(|e| Error::Pipeline(format!("Failed to resume playback: {e}")))?;
+        }
+        Ok(())
     }
 
     // Returns paintable object for gtk widget
@@ -418,69 +643,88 @@ impl VideoPipeline {
         self.pipeline.bus()
     }
 
-    pub fn play_video(&self) {
+    pub fn play_video(&self) -> Result<(), Error> {
         let state = self.state.borrow();
         if state.direction == PlaybackDirection::Reverse {
             drop(state);
             self.set_direction_forward();
         }
-        println!("new state: Playing");
-        self.pipeline.set_state(gstreamer::State::Playing).expect("Failed to set state");
+        log_debug!("new state: Playing");
+        self.pipeline.set_state(gstreamer::State::Playing)
+            .map_err(|e| Error::Pipeline(format!("Failed to play video: {e}")))?;
+        Ok(())
     }
 
     // Sets the video to the paused state
-    pub fn pause_video(&self) {
-        println!("new state: Paused");
-        self.pipeline.set_state(gstreamer::State::Paused).expect("Failed to set pipeline state to Paused");
+    pub fn pause_video(&self) -> Result<(), Error> {
+        log_debug!("new state: Paused");
+        self.pipeline.set_state(gstreamer::State::Paused)
+            .map_err(|e| Error::Pipeline(format!("Failed to pause video: {e}")))?;
+        Ok(())
     }
 
     // Sets the video to the Null state
-    pub fn stop_video(&self) {
-        self.pipeline
-            .set_state(gstreamer::State::Null)
-            .expect("Failed to set pipeline state to Null");
+    pub fn stop_video(&self) -> Result<(), Error> {
+        self.pipeline.set_state(gstreamer::State::Null)
+            .map_err(|e| Error::Pipeline(format!("Failed to stop video: {e}")))?;
+        Ok(())
+    }
+
+    //Changes the playback speed without flushing, used to let a drifting video catch up to the others
+    pub fn set_playback_rate(&self, rate: f64) -> Result<(), Error> {
+        let seek_event = Seek::new(
+            rate,
+            SeekFlags::INSTANT_RATE_CHANGE,
+            SeekType::None,
+            ClockTime::NONE,
+            SeekType::None,
+            ClockTime::NONE,
+        );
+        if self.pipeline.send_event(seek_event) {
+            Ok(())
+        } else {
+            Err(Error::Pipeline(format!("Failed to change playback rate to {rate}")))
+        }
     }
 
     pub fn set_direction_forward(&self) {
         let position = match self.pipeline.query_position::<gstreamer::ClockTime>() {
             Some(pos) => pos,
             None => {
-                eprintln!("Unable to get current position");
+                log_warn!("Unable to get current position");
                 return;
             }
         };
         let mut state = self.state.borrow_mut();
         state.direction = PlaybackDirection::Forward;
         drop(state);
-        let seek_event =
-            Seek::new(
-                1.0,
-                SeekFlags::FLUSH | SeekFlags::ACCURATE,
-                SeekType::Set,
-                position,
-                SeekType::End,
-                ClockTime::NONE,
-            );
-        self.pipeline.send_event(seek_event);
+        if let Err(e) = self.seek_to(SeekFlags::FLUSH | SeekFlags::ACCURATE, position) {
+            log_error!("{e}");
+        }
     }
 
     pub fn set_direction_backward(&self) {
         let position = match self.pipeline.query_position::<gstreamer::ClockTime>() {
             Some(pos) => pos,
             None => {
-                eprintln!("Unable to get current position");
+                log_warn!("Unable to get current position");
                 return;
             }
         };
         let mut state = self.state.borrow_mut();
         state.direction = PlaybackDirection::Reverse;
         drop(state);
+        //Reverse playback stops at the start clamp
+        let (start, flags) = match self.clamp.lock().unwrap().as_ref() {
+            Some(clamp) => (clamp.start_time, SeekFlags::FLUSH | SeekFlags::ACCURATE | SeekFlags::SEGMENT),
+            None => (ClockTime::ZERO, SeekFlags::FLUSH | SeekFlags::ACCURATE),
+        };
         let seek_event =
             Seek::new(
                 -1.0,
-                SeekFlags::FLUSH | SeekFlags::ACCURATE,
+                flags,
                 SeekType::Set,
-                ClockTime::ZERO,
+                start,
                 SeekType::Set,
                 position,
             );
@@ -488,10 +732,10 @@ impl VideoPipeline {
     }
 
     // Moves video one frame forward
-    pub fn frame_forward(&self) {
+    pub fn frame_forward(&self) -> Result<(), Error> {
         if self.pipeline.current_state() != gstreamer::State::Paused {
-            eprintln!("Can't step 1 frame forward. Video is not paused");
-            return;
+            log_warn!("Can't step 1 frame forward. Video is not paused");
+            return Ok(());
         }
 
         let state = self.state.borrow();
@@ -501,50 +745,168 @@ impl VideoPipeline {
         }
 
         let step_event = Step::new(gstreamer::format::Buffers::ONE, 1.0, true, false);
-        println!("Attempting to move one frame forward");
-        let success = self.pipeline.send_event(step_event);
-        if !success {
-            eprintln!("Failed to move one frame forward");
+        log_debug!("Attempting to move one frame forward");
+        if self.pipeline.send_event(step_event) {
+            Ok(())
+        } else {
+            Err(Error::Pipeline("Failed to move one frame forward".to_string()))
         }
     }
 
     // Moves video one frame backward
-    pub fn frame_backward(&self) {
+    pub fn frame_backward(&self) -> Result<(), Error> {
         if self.pipeline.current_state() != gstreamer::State::Paused {
-            eprintln!("Can't step 1 frame backward. Video is not paused");
-            return;
+            log_warn!("Can't step 1 frame backward. Video is not paused");
+            return Ok(());
         }
-        // Set video direction backward
+        // Steps are always taken from a forward playing pipeline
         let state = self.state.borrow();
-        if state.direction == PlaybackDirection::Forward {
+        if state.direction == PlaybackDirection::Reverse {
             drop(state);
-            self.set_direction_backward();
+            self.set_direction_forward();
         }
-        // Step 1 frame
-        let step_event = Step::new(gstreamer::format::Buffers::ONE, 1.0, true, false);
-        println!("Attempting to move one frame backward");
-        let success = self.pipeline.send_event(step_event);
-        if !success {
-            eprintln!("Failed to move one frame backward");
+        log_debug!("Attempting to move one frame backward");
+        self.step_backward(ClockTime::ZERO)?;
+        Ok(())
+    }
+
+    //Moves to the exact previous frame
+    //Shows the frame from the cache right away when it was already decoded,
+    //otherwise decodes forward from the previous keyframe up to the frame
+    //Input: earliest time the pipeline can step back to
+    //Output: true if the pipeline stepped, false if the previous frame is before the lower bound
+    fn step_backward(&self, lower_bound: ClockTime) -> Result<bool, Error> {
+        let mut frame_cache = self.frame_cache.lock().unwrap();
+        let current = match frame_cache.current_pts {
+            Some(pts) => pts,
+            None => self.pipeline.query_position::<ClockTime>()
+                .ok_or_else(|| Error::Pipeline("Could not get video position".to_string()))?
+                .nseconds(),
+        };
+        if current == 0 {
+            return Ok(false);
         }
+
+        //The frame index knows exactly where the previous frame starts, even for variable frame rate videos
+        let indexed_previous = self.lookup_frame_index(|index| index.previous_frame_time(current));
+        let cached = match indexed_previous {
+            Some(previous) => frame_cache.get(previous).map(|sample| (previous, sample)),
+            None => {
+                let frame_duration = self.frame_duration.get().copied()
+                    .or_else(|| self.set_frame_duration())
+                    .ok_or_else(|| Error::Pipeline("Could not get frame duration".to_string()))?;
+                //Allow some slack between frames for timestamps that aren't exactly evenly spaced
+                frame_cache.previous(current, frame_duration + frame_duration / 2)
+            }
+        };
+
+        match cached {
+            Some((pts, sample)) => {
+                if pts < lower_bound.nseconds() {
+                    return Ok(false);
+                }
+                frame_cache.current_pts = Some(pts);
+                frame_cache.pending_pts = Some(pts);
+                drop(frame_cache);
+                if let Some(callback) = self.cached_frame_callback.borrow().as_ref() {
+                    callback(sample);
+                }
+                self.seek_to(SeekFlags::FLUSH | SeekFlags::ACCURATE, ClockTime::from_nseconds(pts))?;
+            }
+            None => {
+                //Without an index an accurate seek just before the current frame lands on the start of the previous frame
+                let target = indexed_previous.unwrap_or(current - 1);
+                if target < lower_bound.nseconds() {
+                    return Ok(false);
+                }
+                frame_cache.pending_pts = None;
+                drop(frame_cache);
+                self.seek_to(SeekFlags::FLUSH | SeekFlags::ACCURATE, ClockTime::from_nseconds(target))?;
+            }
+        }
+        Ok(true)
     }
 
-    // Prints the currect frame the video is on
-    pub fn get_current_frame(&self) {
-        let current_time = self.pipeline.query_position::<gstreamer::format::Time>().unwrap();
-        
-        let video_sink = self.pipeline.by_name("video_convert").unwrap();
-        let sink_pads = video_sink.static_pad("sink").unwrap();
-        let caps = sink_pads.current_caps().unwrap();
-        let structure = caps.structure(0).unwrap();
-        if let Ok(fps_fraction) = structure.get::<gstreamer::Fraction>("framerate") {
-            let fps = fps_fraction.numer();
-            let time_per_frame = 1_000_000_000 / fps as u64;
-            let current_frame = current_time.nseconds() / time_per_frame;
-            println!("Current_frame: {current_frame}");
-        } else {
-            println!("Can't get the framerate");
+    //Sets the function called with a cached frame when stepping backward shows it before the pipeline catches up
+    pub fn set_cached_frame_callback<F>(&self, callback: F)
+    where F: Fn(gstreamer::Sample) + 'static {
+        *self.cached_frame_callback.borrow_mut() = Some(Box::new(callback));
+    }
+
+    //Checks if a cached frame is being shown while the pipeline seeks to it
+    pub fn is_showing_cached_frame(&self) -> bool {
+        self.frame_cache.lock().unwrap().pending_pts.is_some()
+    }
+
+    //Sets the frame timestamp index of the loaded file
+    pub fn set_frame_index(&self, index: Arc<Mutex<FrameIndex>>) {
+        *self.frame_index.borrow_mut() = Some(index);
+    }
+
+    //Looks something up in the frame index once it has finished building
+    fn lookup_frame_index<T>(&self, lookup: impl FnOnce(&FrameIndex) -> Option<T>) -> Option<T> {
+        let index = self.frame_index.borrow();
+        let index = index.as_ref()?.lock().unwrap();
+        if !index.is_ready() {
+            return None;
         }
+        lookup(&index)
+    }
+
+    //Checks if the loaded file has frames that aren't evenly spaced
+    pub fn is_variable_frame_rate(&self) -> bool {
+        self.lookup_frame_index(|index| Some(index.variable_rate)).unwrap_or(false)
+    }
+
+    //Gets the number of the frame shown at a time
+    //Uses the frame index when available, otherwise the frame rate of the stream
+    pub fn frame_number_at(&self, time: u64) -> Option<u64> {
+        if let Some(frame) = self.lookup_frame_index(|index| index.frame_at(time)) {
+            return Some(frame);
+        }
+        let framerate = self.get_framerate()?;
+        let frame = time as u128 * framerate.numer() as u128 / (framerate.denom() as u128 * 1_000_000_000);
+        Some(frame as u64)
+    }
+
+    //Gets the start of the frame shown at a time
+    pub fn snap_to_frame(&self, time: u64) -> Option<u64> {
+        if let Some(snapped) = self.lookup_frame_index(|index| index.snap(time)) {
+            return Some(snapped);
+        }
+        let framerate = self.get_framerate()?;
+        let frame = self.frame_number_at(time)? as u128;
+        let snapped = (frame * framerate.denom() as u128 * 1_000_000_000).div_ceil(framerate.numer() as u128);
+        Some(snapped as u64)
+    }
+
+    //Gets the start of the frame a number of frames away from the frame shown at a time
+    //Input: time in nanoseconds, frames to move, negative to move backward
+    pub fn offset_by_frames(&self, time: u64, frames: i64) -> Option<u64> {
+        let frame = (self.frame_number_at(time)? as i64 + frames).max(0) as u64;
+        if let Some(frame_time) = self.lookup_frame_index(|index| index.frame_time(frame)) {
+            return Some(frame_time);
+        }
+        let framerate = self.get_framerate()?;
+        let frame_time = (frame as u128 * framerate.denom() as u128 * 1_000_000_000).div_ceil(framerate.numer() as u128);
+        Some(frame_time as u64)
+    }
+
+    //Gets the number of the frame the video is currently on
+    pub fn get_current_frame(&self) -> Option<u64> {
+        let current_time = self.frame_cache.lock().unwrap().current_pts
+            .or_else(|| self.pipeline.query_position::<ClockTime>().map(|position| position.nseconds()))?;
+        self.frame_number_at(current_time)
+    }
+
+    //Gets the frame rate of the video stream
+    fn get_framerate(&self) -> Option<gstreamer::Fraction> {
+        let caps = self.gtksink.static_pad("sink")?.current_caps()?;
+        let framerate = caps.structure(0)?.get::<gstreamer::Fraction>("framerate").ok()?;
+        if framerate.numer() <= 0 || framerate.denom() <= 0 {
+            return None;
+        }
+        Some(framerate)
     }
 
     //Gets duration of the pipeline
@@ -552,16 +914,16 @@ impl VideoPipeline {
         match self.pipeline.state(Some(ClockTime::from_seconds(5))) {
             (Ok(_state_change_success), _, _) => {
                 if let Some(duration) = self.pipeline.query_duration::<ClockTime>() {
-                    println!("Got duration from get_length");
+                    log_trace!("Got duration from get_length");
                     Some(duration.nseconds())
                 } else {
-                    println!("Didnt get duration but state was success");
+                    log_warn!("Pipeline prerolled but has no duration");
                     let _ = self.pipeline.set_state(gstreamer::State::Null);
                     None
                 }
             }
             _ => {
-                println!("State change not successful");
+                log_warn!("Pipeline failed to preroll");
                 let _ = self.pipeline.set_state(gstreamer::State::Null);
                 None
             }
@@ -600,105 +962,161 @@ impl VideoPipeline {
         None
     }
 
+    //Gets the current state, timing and frame statistics of the pipeline without waiting on state changes
+    pub fn get_diagnostics(&self) -> PipelineDiagnostics {
+        let (_, state, pending_state) = self.pipeline.state(Some(ClockTime::ZERO));
+        let mut segment_query = gstreamer::query::Segment::new(gstreamer::Format::Time);
+        let rate = if self.pipeline.query(&mut segment_query) {
+            Some(segment_query.result().0)
+        } else {
+            None
+        };
+        //Base sinks count every frame they rendered or dropped for being late
+        let sink_stats = self.gtksink.property::<gstreamer::Structure>("stats");
+        let clamp = self.clamp.lock().unwrap().as_ref().map(|clamp| (clamp.start_time, clamp.end_time));
+        PipelineDiagnostics {
+            state,
+            pending_state,
+            clock: self.pipeline.clock(),
+            base_time: self.pipeline.base_time(),
+            position: self.pipeline.query_position::<ClockTime>(),
+            duration: self.pipeline.query_duration::<ClockTime>(),
+            rate,
+            direction: self.state.borrow().direction,
+            clamp,
+            frame_duration: self.frame_duration.get().copied(),
+            rendered_frames: sink_stats.get::<u64>("rendered").ok(),
+            dropped_frames: sink_stats.get::<u64>("dropped").ok(),
+            qos: self.qos_stats.borrow().clone(),
+        }
+    }
+
     //Returns pipeline object
     pub fn pipeline(&self) -> Option<Pipeline> {
         return Some(self.pipeline.clone());
     }
 
     //Gets length between the clamp positions
-    pub fn get_logical_duration(&self) -> Result<ClockTime, String> {
+    pub fn get_logical_duration(&self) -> Result<ClockTime, Error> {
         if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             Ok(clamp.end_time - clamp.start_time)
         } else {
-            Err("Video is not currently clamped".to_string())
+            Err(Error::Pipeline("Video is not currently clamped".to_string()))
         }
     }
 
     //Gets the position of the starting clamp position
-    pub fn get_start(&self) -> Result<ClockTime, String> {
+    pub fn get_start(&self) -> Result<ClockTime, Error> {
         if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             Ok(clamp.start_time)
         } else {
-            Err("Video not is currently clamped".to_string())
+            Err(Error::Pipeline("Video is not currently clamped".to_string()))
         }
     }
 
     //Gets the position of the ending clamp position
-    pub fn get_end(&self) -> Result<ClockTime, String> {
+    pub fn get_end(&self) -> Result<ClockTime, Error> {
         if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             Ok(clamp.end_time)
         } else {
-            Err("Video not is currently clamped".to_string())
+            Err(Error::Pipeline("Video is not currently clamped".to_string()))
         }
     }
 
     //Reset the clamp positions
-    pub fn reset_clamps(&mut self) -> Result<(), String> {
-        *self.clamp.lock().unwrap() = None;
-        self.stop_position_monitor();
+    pub fn reset_clamps(&mut self) -> Result<(), Error> {
+        if self.clamp.lock().unwrap().take().is_none() {
+            return Ok(());
+        }
+        //Replaces the segment seek so playback no longer stops at the old end clamp
+        if let Some(position) = self.pipeline.query_position::<ClockTime>() {
+            self.seek_to(SeekFlags::FLUSH | SeekFlags::ACCURATE, position)?;
+        }
         Ok(())
     }
 
     //Toggle if the video should repsect the clamping times
-    pub fn apply_clamp(&mut self, start: ClockTime, end: ClockTime) -> Result<(), String> {
+    pub fn apply_clamp(&mut self, start: ClockTime, end: ClockTime) -> Result<(), Error> {
         if start > end {
-            return Err("start exceeds end clamp".to_string());
+            return Err(Error::Pipeline("start exceeds end clamp".to_string()));
         }
-        println!("clamping start: {start}, end: {end}");
+        log_debug!("Clamping start: {start}, end: {end}");
         let clamp = VideoClamp::new(start, end);
         
         *self.clamp.lock().unwrap() = Some(clamp);
 
-        //Start monitoring pipeline position to ensure pipeline respects the clamp times
-        if !self.monitor_active.load(std::sync::atomic::Ordering::Relaxed) {
-            self.start_position_monitor();
-        }
-
+        //Seeking to the start sets up the segment that stops playback at the end clamp
         self.seek_to_start()?;
         Ok(())
     }
 
+    //Moves the clamp times without seeking, the next seek stops playback at the new end
+    //Inputs: new start and end clamps, None keeps the current one
+    pub fn set_clamp(&self, start: Option<ClockTime>, end: Option<ClockTime>) -> Result<(), Error> {
+        let mut clamp = self.clamp.lock().unwrap();
+        let clamp = clamp.as_mut()
+            .ok_or_else(|| Error::Pipeline("Video is not currently clamped".to_string()))?;
+        let start = start.unwrap_or(clamp.start_time);
+        let end = end.unwrap_or(clamp.end_time);
+        if start > end {
+            return Err(Error::Pipeline(format!("Start clamp {start} is after end clamp {end}")));
+        }
+        clamp.start_time = start;
+        clamp.end_time = end;
+        Ok(())
+    }
+
     //Seek pipeline to the starting clamp position
-    fn seek_to_start(&self) -> Result<(), String> {
+    fn seek_to_start(&self) -> Result<(), Error> {
         let position = if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             clamp.start_time.clone()
         } else {
             ClockTime::ZERO
         };
         self.seek_clamped(position)
-            .map_err(|e| format!("Failed to see to start: {e}"))
     }
 
     //Seek pipeline to the ending clamp position
-    fn seek_to_end(&self) -> Result<(), String> {
+    fn seek_to_end(&self) -> Result<(), Error> {
         let position = if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             clamp.end_time.clone()
         } else if let Some(duration) = self.pipeline.query_duration::<ClockTime>() {
             duration
         } else {
-            Err("Could not get video duration".to_string())?
+            return Err(Error::Pipeline("Could not get video duration".to_string()));
         };
         self.seek_clamped(position)
-            .map_err(|e| format!("Failed to seek to end: {e}"))
     }
 
-    //Perform seek operation while respecting clamp times
-    pub fn seek_clamped(&self, position: ClockTime) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
-            let clamped_pos = clamp.clamp_position(position);
-            self.seek_position(clamped_pos)?
-        } else {
-            self.seek_position(position)?
+    //Gets the position a seek would end up at after applying the clamp times
+    pub fn get_clamped_position(&self, position: ClockTime) -> ClockTime {
+        match self.clamp.lock().unwrap().as_ref() {
+            Some(clamp) => clamp.clamp_position(position),
+            None => position,
         }
-        Ok(())
+    }
+
+    //Perform seek operation while respecting clamp times
+    pub fn seek_clamped(&self, position: ClockTime) -> Result<(), Error> {
+        //The clamp lock is released before seeking since seeking reads the clamp again
+        let clamped_pos = self.get_clamped_position(position);
+        self.seek_position(clamped_pos)
     }
 
     //Perform frame forward operation while respecting clamp times
-    pub fn frame_forward_clamped(&self) -> Result<bool, String> {
+    pub fn frame_forward_clamped(&self) -> Result<bool, Error> {
         //Check if next position is outside clamp window
         if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
             if let Some(position) = self.pipeline.query_position::<ClockTime>() {
-                let next_position = position + ClockTime::from_nseconds(*self.frame_duration.get().unwrap());
+                let next_position = match self.lookup_frame_index(|index| index.next_frame_time(position.nseconds())) {
+                    Some(next) => ClockTime::from_nseconds(next),
+                    None => {
+                        let frame_duration = self.frame_duration.get().copied()
+                            .or_else(|| self.set_frame_duration())
+                            .ok_or_else(|| Error::Pipeline("Could not get frame duration".to_string()))?;
+                        position + ClockTime::from_nseconds(frame_duration)
+                    }
+                };
                 if next_position > clamp.end_time {
                     return Ok(false);
                 }
@@ -707,7 +1125,7 @@ impl VideoPipeline {
         }
         
         if self.pipeline.current_state() != gstreamer::State::Paused {
-            eprintln!("Can't step 1 frame forward. Video is not paused");
+            log_warn!("Can't step 1 frame forward. Video is not paused");
             return Ok(false);
         }
 
@@ -718,88 +1136,70 @@ impl VideoPipeline {
         }
 
         let step_event = Step::new(gstreamer::format::Buffers::ONE, 1.0, true, false);
-        println!("Attempting to move one frame forward");
+        log_debug!("Attempting to move one frame forward");
         if self.pipeline.send_event(step_event) {
             Ok(true)
         } else {
-            eprintln!("Failed to move one frame forward");
-            Err("failed to send step event".to_string())
+            log_error!("Failed to move one frame forward");
+            Err(Error::Pipeline("Failed to send step event".to_string()))
         }
     }
 
     //Perform frame backward operation while respecting clamp times
-    pub fn frame_backward_clamped(&self) -> Result<bool, String> {
-        //Check if previous position is outside clamp window
-        if let Some(position) = self.pipeline.query_position::<ClockTime>() {
-            let prev_position = position.saturating_sub(ClockTime::from_nseconds(*self.frame_duration.get().unwrap()));
-            if let Some(clamp) = self.clamp.lock().unwrap().as_ref() {
-                if prev_position < clamp.start_time {
-                    return Ok(false);
-                }
-            }
-            
-            if self.pipeline.current_state() != gstreamer::State::Paused {
-                eprintln!("Can't step 1 frame forward. Video is not paused");
-                return Ok(false);
-            }
-    
-            self.seek_clamped(prev_position)
-                .map_err(|e| format!("Failed to step backward: {e}"))?;
-            Ok(true)
-        } else {
-            Err("Could not get video position".to_string())
+    pub fn frame_backward_clamped(&self) -> Result<bool, Error> {
+        if self.pipeline.current_state() != gstreamer::State::Paused {
+            log_warn!("Can't step 1 frame backward. Video is not paused");
+            return Ok(false);
         }
-    }
 
-    //Monitors position of the pipeline to ensure clamped positioning
-    fn start_position_monitor(&mut self) {
-        if self.monitor_active.load(std::sync::atomic::Ordering::Relaxed) {
-            return;
+        let state = self.state.borrow();
+        if state.direction == PlaybackDirection::Reverse {
+            drop(state);
+            self.set_direction_forward();
         }
 
-        self.monitor_active.store(true,std::sync::atomic::Ordering::Relaxed);
+        //Previous frame can't be before the start of the clamp window
+        let lower_bound = match self.clamp.lock().unwrap().as_ref() {
+            Some(clamp) => clamp.start_time,
+            None => ClockTime::ZERO,
+        };
+        self.step_backward(lower_bound)
+    }
 
-        let pipeline_weak = self.pipeline.downgrade();
-        let clamp_ref = Arc::clone(&self.clamp);
-        let active_flag = Arc::clone(&self.monitor_active);
-        
-        self.monitor_thread = Some(thread::spawn(move || {
-            let mut last_position = ClockTime::ZERO;
-
-            while active_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                thread::sleep(Duration::from_millis(50));
-
-                if let Some(pipeline) = pipeline_weak.upgrade() {
-                    if let Some(clamp) = clamp_ref.lock().unwrap().as_ref() {
-                        if let Ok(was_clamped) = clamp.check_and_clamp_position(&pipeline) {
-                            if was_clamped {
-                                if let Some(current_pos) = pipeline.query_position::<ClockTime>() {
-                                    if current_pos <= clamp.start_time && last_position > clamp.start_time {
-                                        println!("Start boundary reached, position clamped");
-                                    } else if current_pos >= clamp.end_time && last_position < clamp.end_time {
-                                        println!("End boundary reached, video paused and position clamped");
-                                    }
-                                    last_position = current_pos;
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    break;
-                }
-            }
-        }));
+    //Sets the audio volume of the pipeline (0.0 - 1.0)
+    pub fn set_volume(&self, volume: f64) {
+        let guard = self.volume_control.lock().unwrap();
+        if let Some(volume_control) = guard.as_ref() {
+            volume_control.set_volume(volume);
+        }
     }
 
-    //Stops the pipeline position monitor if clamping is disabled
-    fn stop_position_monitor(&mut self) {
-        self.monitor_active.store(false, std::sync::atomic::Ordering::Relaxed);
+    //Gets the audio volume of the pipeline
+    pub fn get_volume(&self) -> f64 {
+        let guard = self.volume_control.lock().unwrap();
+        guard.as_ref().map_or(1.0, |volume_control| volume_control.volume.get())
+    }
 
-        if let Some(handle) = self.monitor_thread.take() {
-            let _ = handle.join();
+    //Mutes or unmutes the pipeline audio
+    pub fn set_muted(&self, muted: bool) {
+        let guard = self.volume_control.lock().unwrap();
+        let volume_control = match guard.as_ref() {
+            Some(vc) => vc,
+            None => return,
+        };
+        if muted {
+            volume_control.mute_audio();
+        } else {
+            volume_control.unmute_audio();
         }
     }
 
+    //Checks if the pipeline audio is muted
+    pub fn is_muted(&self) -> bool {
+        let guard = self.volume_control.lock().unwrap();
+        guard.as_ref().map_or(false, |volume_control| volume_control.is_muted.get())
+    }
+
     //Toggles pipeline audio mute on/off
     pub fn toggle_mute(&self) {
         let guard = self.volume_control.lock().unwrap();
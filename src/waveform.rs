@@ -0,0 +1,174 @@
+use gstreamer::{prelude::*, ClockTime, MessageView};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::background_job::{link_first_stream, spawn_job, BackgroundJob, JobResult};
+
+//Sample rate the audio is converted to before measuring peaks
+const ANALYSIS_RATE: u64 = 8000;
+//Length of audio each peak value covers
+const BUCKET_DURATION_NS: u64 = 10_000_000;
+
+//Audio peaks of a video, filled in by a background thread
+pub struct WaveformData {
+    pub bucket_duration: u64,
+    pub peaks: Vec<f32>,
+    pub complete: bool,
+    pub cancelled: bool,
+}
+
+impl WaveformData {
+    pub fn new() -> Self {
+        WaveformData {
+            bucket_duration: BUCKET_DURATION_NS,
+            peaks: Vec::new(),
+            complete: false,
+            cancelled: false,
+        }
+    }
+
+    //Gets the largest peak between two times
+    //Output: peak between 0.0 and 1.0, None if no audio has been analyzed for that range
+    pub fn peak_between(&self, start: u64, end: u64) -> Option<f32> {
+        let first = (start / self.bucket_duration) as usize;
+        let last = ((end / self.bucket_duration) as usize).max(first + 1);
+        if first >= self.peaks.len() {
+            return None;
+        }
+        self.peaks[first..last.min(self.peaks.len())]
+            .iter()
+            .cloned()
+            .reduce(f32::max)
+    }
+}
+
+impl Default for WaveformData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackgroundJob for WaveformData {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn complete(&mut self) {
+        self.complete = true;
+    }
+}
+
+//Starts computing the waveform of a file in the background
+//Input: path of the video file
+//Output: waveform data that fills in as the audio is decoded
+pub fn generate_waveform(path: &str) -> Arc<Mutex<WaveformData>> {
+    let path = path.to_string();
+    spawn_job(format!("generate waveform for {path}"), WaveformData::new(), move |data| run_waveform_pipeline(&path, data))
+}
+
+//Decodes the audio of a file as fast as possible and records the peak of each bucket
+fn run_waveform_pipeline(path: &str, data: &Arc<Mutex<WaveformData>>) -> JobResult {
+    let pipeline = gstreamer::Pipeline::new();
+    let source = gstreamer::ElementFactory::make("filesrc")
+        .property("location", path)
+        .build()?;
+    let decodebin = gstreamer::ElementFactory::make("decodebin").build()?;
+    let audio_convert = gstreamer::ElementFactory::make("audioconvert").build()?;
+    let audio_resample = gstreamer::ElementFactory::make("audioresample").build()?;
+    let caps = gstreamer::Caps::builder("audio/x-raw")
+        .field("format", "F32LE")
+        .field("layout", "interleaved")
+        .field("channels", 1i32)
+        .field("rate", ANALYSIS_RATE as i32)
+        .build();
+    let capsfilter = gstreamer::ElementFactory::make("capsfilter")
+        .property("caps", &caps)
+        .build()?;
+    let appsink = gstreamer::ElementFactory::make("appsink")
+        .property("sync", false)
+        .build()?;
+
+    pipeline.add_many([&source, &decodebin, &audio_convert, &audio_resample, &capsfilter, &appsink])?;
+    source.link(&decodebin)?;
+    gstreamer::Element::link_many([&audio_convert, &audio_resample, &capsfilter, &appsink])?;
+
+    //Only the audio stream is decoded
+    link_first_stream(&decodebin, &audio_convert, "audio/");
+
+    //Set once every stream is known and none of them is audio
+    let no_audio = Arc::new(AtomicBool::new(false));
+    let no_audio_clone = no_audio.clone();
+    let audio_convert_weak = audio_convert.downgrade();
+    decodebin.connect_no_more_pads(move |_| {
+        let linked = audio_convert_weak.upgrade()
+            .and_then(|audio_convert| audio_convert.static_pad("sink"))
+            .is_some_and(|sink_pad| sink_pad.is_linked());
+        if !linked {
+            no_audio_clone.store(true, Ordering::SeqCst);
+        }
+    });
+
+    pipeline.set_state(gstreamer::State::Playing)?;
+    let bus = pipeline.bus().ok_or("Pipeline has no bus")?;
+
+    //Pulls decoded buffers until the end of the stream
+    //Waits are bounded so a file without audio or a streaming error can't block the thread forever
+    let mut failure: Option<String> = None;
+    loop {
+        if data.lock().unwrap().is_cancelled() {
+            break;
+        }
+        let timeout = ClockTime::from_mseconds(200);
+        let sample = match appsink.emit_by_name::<Option<gstreamer::Sample>>("try-pull-sample", &[&timeout]) {
+            Some(s) => s,
+            None => {
+                if appsink.property::<bool>("eos") {
+                    break;
+                }
+                if no_audio.load(Ordering::SeqCst) {
+                    log_info!("{path} has no audio, no waveform to show");
+                    break;
+                }
+                let mut stop = false;
+                while let Some(message) = bus.pop() {
+                    match message.view() {
+                        MessageView::Eos(..) => stop = true,
+                        MessageView::Error(err) => {
+                            failure = Some(err.error().to_string());
+                            stop = true;
+                        }
+                        _ => {}
+                    }
+                }
+                if stop {
+                    break;
+                }
+                continue;
+            }
+        };
+        let buffer = match sample.buffer() {
+            Some(b) => b,
+            None => continue,
+        };
+        let start_sample = buffer.pts().unwrap_or(ClockTime::ZERO).nseconds() * ANALYSIS_RATE / 1_000_000_000;
+        let map = buffer.map_readable()?;
+        let samples_per_bucket = ANALYSIS_RATE * BUCKET_DURATION_NS / 1_000_000_000;
+
+        let mut data = data.lock().unwrap();
+        for (i, chunk) in map.as_slice().chunks_exact(4).enumerate() {
+            let value = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]).abs().min(1.0);
+            let bucket = ((start_sample + i as u64) / samples_per_bucket) as usize;
+            if bucket >= data.peaks.len() {
+                data.peaks.resize(bucket + 1, 0.0);
+            }
+            if value > data.peaks[bucket] {
+                data.peaks[bucket] = value;
+            }
+        }
+    }
+
+    pipeline.set_state(gstreamer::State::Null)?;
+    match failure {
+        Some(failure) => Err(failure.into()),
+        None => Ok(()),
+    }
+}
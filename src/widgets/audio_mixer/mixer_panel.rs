@@ -0,0 +1,182 @@
+use gtk::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use gtk::{Box, Label, Scale, ToggleButton, TemplateChild};
+use glib::WeakRef;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::widgets::video_player_widget::video_player::VideoPlayer;
+
+//Controls for a single video player in the mixer
+pub struct MixerChannel {
+    pub video_player: WeakRef<VideoPlayer>,
+    pub row: Box,
+    pub solo_button: ToggleButton,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/mixerpanel/mixerpanel.ui")]
+    pub struct MixerPanel {
+        #[template_child]
+        pub channel_container: TemplateChild<Box>,
+
+        pub channels: RefCell<HashMap<String, MixerChannel>>,
+        pub soloed: RefCell<Option<String>>,
+    }
+
+    #[gtk::glib::object_subclass]
+    impl ObjectSubclass for MixerPanel {
+        const NAME: &'static str = "MixerPanel";
+        type Type = super::MixerPanel;
+        type ParentType = Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MixerPanel {
+        fn dispose(&self) {
+            self.channels.borrow_mut().clear();
+        }
+    }
+    impl WidgetImpl for MixerPanel {}
+    impl BoxImpl for MixerPanel {}
+}
+
+glib::wrapper! {
+    pub struct MixerPanel(ObjectSubclass<imp::MixerPanel>)
+    @extends gtk::Widget,
+    @implements gtk::Buildable;
+}
+
+// Mixer Panel:
+// Volume, mute and solo controls for every video player shown together in sync view
+impl MixerPanel {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    //Adds a channel for a video player
+    //Inputs: video player to control, color assigned to the video player
+    pub fn add_channel(&self, video_player: &VideoPlayer, color: &str) {
+        let imp = self.imp();
+        let video_player_id = video_player.get_id();
+
+        let row = Box::new(gtk::Orientation::Horizontal, 4);
+        row.add_css_class("mixer-channel");
+
+        let label = Label::new(None);
+        label.set_markup(&format!("<span foreground='{color}'>●</span> Video {video_player_id}"));
+
+        //Volume slider is kept in sync with the video players own slider through the volume property
+        let volume_scale = Scale::with_range(gtk::Orientation::Horizontal, 0.0, 1.0, 0.05);
+        volume_scale.set_width_request(120);
+        volume_scale.set_draw_value(false);
+        video_player.bind_property("volume", &volume_scale.adjustment(), "value")
+            .flags(glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE)
+            .build();
+
+        let mute_button = ToggleButton::with_label("M");
+        mute_button.set_tooltip_text(Some("Mute"));
+        video_player.bind_property("muted", &mute_button, "active")
+            .flags(glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE)
+            .build();
+
+        let solo_button = ToggleButton::with_label("S");
+        solo_button.set_tooltip_text(Some("Solo"));
+        solo_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[strong] video_player_id,
+            move |_| {
+                this.solo(video_player_id.as_str());
+            }
+        ));
+
+        row.append(&label);
+        row.append(&volume_scale);
+        row.append(&mute_button);
+        row.append(&solo_button);
+        imp.channel_container.append(&row);
+
+        imp.channels.borrow_mut().insert(video_player_id, MixerChannel {
+            video_player: video_player.downgrade(),
+            row,
+            solo_button,
+        });
+        self.update_solo_buttons();
+    }
+
+    //Removes the channel of a video player
+    pub fn remove_channel(&self, video_player_id: &str) {
+        let imp = self.imp();
+        if let Some(channel) = imp.channels.borrow_mut().remove(video_player_id) {
+            imp.channel_container.remove(&channel.row);
+        }
+
+        //Removing the soloed video player restores audio to everyone else
+        let was_soloed = imp.soloed.borrow().as_deref() == Some(video_player_id);
+        if was_soloed {
+            self.clear_solo();
+        }
+    }
+
+    //Solos a video player muting all others. Soloing the already soloed video player unmutes everyone
+    pub fn solo(&self, video_player_id: &str) {
+        let imp = self.imp();
+        let already_soloed = imp.soloed.borrow().as_deref() == Some(video_player_id);
+        if already_soloed {
+            self.clear_solo();
+            return;
+        }
+
+        for (id, channel) in imp.channels.borrow().iter() {
+            if let Some(video_player) = channel.video_player.upgrade() {
+                video_player.set_muted(id != video_player_id);
+            }
+        }
+        *imp.soloed.borrow_mut() = Some(video_player_id.to_string());
+        self.update_solo_buttons();
+    }
+
+    //Unmutes every video player and clears the solo state
+    pub fn clear_solo(&self) {
+        let imp = self.imp();
+        for channel in imp.channels.borrow().values() {
+            if let Some(video_player) = channel.video_player.upgrade() {
+                video_player.set_muted(false);
+            }
+        }
+        *imp.soloed.borrow_mut() = None;
+        self.update_solo_buttons();
+    }
+
+    //Gets the id of the currently soloed video player
+    pub fn get_soloed(&self) -> Option<String> {
+        let imp = self.imp();
+        imp.soloed.borrow().clone()
+    }
+
+    //Updates solo buttons to show which video player is soloed
+    fn update_solo_buttons(&self) {
+        let imp = self.imp();
+        let soloed = imp.soloed.borrow();
+        for (id, channel) in imp.channels.borrow().iter() {
+            channel.solo_button.set_active(soloed.as_deref() == Some(id.as_str()));
+        }
+    }
+}
+
+impl Default for MixerPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1 @@
+pub mod mixer_panel;
\ No newline at end of file
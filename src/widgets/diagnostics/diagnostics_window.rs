@@ -0,0 +1,264 @@
+use gtk::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use gtk::{FlowBox, Grid, Label, TemplateChild};
+use glib::WeakRef;
+use gstreamer::prelude::{ClockExt, GstObjectExt};
+use gstreamer::{Clock, ClockTime};
+use std::cell::RefCell;
+use crate::helpers::format::format_clock;
+use crate::helpers::ui::flowbox_children;
+use crate::video_pipeline::{PipelineDiagnostics, PlaybackDirection};
+use crate::widgets::sync::sync_manager::{DriftCorrection, DriftStats, SyncManager};
+use crate::widgets::video_player_widget::video_player::VideoPlayer;
+
+//How often the shown values are refreshed
+const REFRESH_INTERVAL_MS: u64 = 250;
+//Name of each row, one value per row is shown for every video
+const ROWS: [&str; 13] = [
+    "State",
+    "Clock",
+    "Base time",
+    "Position",
+    "Duration",
+    "Rate",
+    "Direction",
+    "Clamp",
+    "Frame duration",
+    "Rendered / dropped",
+    "QoS",
+    "Drift",
+    "Drift correction",
+];
+
+mod imp {
+    use super::*;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/diagnosticswindow/diagnosticswindow.ui")]
+    pub struct DiagnosticsWindow {
+        #[template_child]
+        pub shared_clock_label: TemplateChild<Label>,
+        #[template_child]
+        pub grid: TemplateChild<Grid>,
+
+        pub video_container: RefCell<Option<WeakRef<FlowBox>>>,
+        pub sync_manager: RefCell<Option<WeakRef<SyncManager>>>,
+        //Video player ids shown and the value labels of each, in column order
+        pub columns: RefCell<Vec<(String, Vec<Label>)>>,
+        pub refresh_source: RefCell<Option<glib::SourceId>>,
+    }
+
+    #[gtk::glib::object_subclass]
+    impl ObjectSubclass for DiagnosticsWindow {
+        const NAME: &'static str = "DiagnosticsWindow";
+        type Type = super::DiagnosticsWindow;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DiagnosticsWindow {
+        fn dispose(&self) {
+            if let Some(source) = self.refresh_source.borrow_mut().take() {
+                source.remove();
+            }
+            self.columns.borrow_mut().clear();
+        }
+    }
+    impl WidgetImpl for DiagnosticsWindow {}
+    impl WindowImpl for DiagnosticsWindow {}
+}
+
+glib::wrapper! {
+    pub struct DiagnosticsWindow(ObjectSubclass<imp::DiagnosticsWindow>)
+    @extends gtk::Widget, gtk::Window,
+    @implements gtk::Buildable, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+// Diagnostics Window:
+// Live state, timing and frame statistics of every video pipeline, used to see why synchronized videos drift apart
+impl DiagnosticsWindow {
+    //Input: container holding the video players, sync manager providing the shared clock
+    pub fn new(video_container: &FlowBox, sync_manager: &SyncManager) -> Self {
+        let object = glib::Object::new::<Self>();
+        let imp = object.imp();
+        imp.video_container.replace(Some(video_container.downgrade()));
+        imp.sync_manager.replace(Some(sync_manager.downgrade()));
+        object.refresh();
+        object.start_refresh();
+        object
+    }
+
+    fn start_refresh(&self) {
+        let source = glib::timeout_add_local(std::time::Duration::from_millis(REFRESH_INTERVAL_MS), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                if this.is_visible() {
+                    this.refresh();
+                }
+                glib::ControlFlow::Continue
+            }
+        ));
+        self.imp().refresh_source.replace(Some(source));
+    }
+
+    //Updates every shown value, adding and removing columns as video players come and go
+    fn refresh(&self) {
+        let imp = self.imp();
+        let video_container = match imp.video_container.borrow().as_ref().and_then(|weak| weak.upgrade()) {
+            Some(container) => container,
+            None => return,
+        };
+        let sync_manager = match imp.sync_manager.borrow().as_ref().and_then(|weak| weak.upgrade()) {
+            Some(sync_manager) => sync_manager,
+            None => return,
+        };
+
+        let shared_clock = sync_manager.get_shared_clock();
+        imp.shared_clock_label.set_label(&format!(
+            "Shared clock: {} at {}",
+            shared_clock.name(),
+            shared_clock.time().map_or(String::from("-"), |time| format_clock(time.nseconds()))
+        ));
+
+        let video_players: Vec<VideoPlayer> = flowbox_children(&video_container)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .collect();
+        let ids: Vec<String> = video_players.iter().map(|video_player| video_player.get_id()).collect();
+        let unchanged = imp.columns.borrow().iter().map(|(id, _)| id).eq(ids.iter());
+        if !unchanged {
+            self.rebuild_columns(&video_players);
+        }
+
+        for (video_player, (_, labels)) in video_players.iter().zip(imp.columns.borrow().iter()) {
+            let pipeline = match video_player.pipeline().upgrade() {
+                Some(p) => p,
+                None => continue,
+            };
+            //A pipeline busy seeking or stepping keeps its previous values until the next refresh
+            let diagnostics = match pipeline.try_lock() {
+                Ok(pipeline) => pipeline.get_diagnostics(),
+                Err(_) => continue,
+            };
+            let drift = sync_manager.get_drift(&video_player.get_id());
+            let drift_stats = sync_manager.get_drift_stats(&video_player.get_id());
+            let values = describe(&diagnostics, drift, drift_stats, &shared_clock);
+            for (label, value) in labels.iter().zip(values.iter()) {
+                label.set_label(value);
+            }
+        }
+    }
+
+    //Recreates the grid with a row name column and one column for each video player
+    fn rebuild_columns(&self, video_players: &[VideoPlayer]) {
+        let imp = self.imp();
+        while let Some(child) = imp.grid.first_child() {
+            imp.grid.remove(&child);
+        }
+
+        for (row, name) in ROWS.iter().enumerate() {
+            let label = Label::new(Some(*name));
+            label.set_xalign(0.0);
+            label.add_css_class("dim-label");
+            imp.grid.attach(&label, 0, row as i32 + 1, 1, 1);
+        }
+
+        let mut columns = Vec::new();
+        for (column, video_player) in video_players.iter().enumerate() {
+            let column = column as i32 + 1;
+            let header = Label::new(None);
+            header.set_markup(&format!("<span foreground='{}'>●</span> Video {}", video_player.get_color(), video_player.get_id()));
+            header.set_xalign(0.0);
+            imp.grid.attach(&header, column, 0, 1, 1);
+
+            let labels: Vec<Label> = (0..ROWS.len()).map(|row| {
+                let label = Label::new(Some("-"));
+                label.set_xalign(0.0);
+                label.set_selectable(true);
+                imp.grid.attach(&label, column, row as i32 + 1, 1, 1);
+                label
+            }).collect();
+            columns.push((video_player.get_id(), labels));
+        }
+        imp.columns.replace(columns);
+    }
+}
+
+//Turns a pipeline snapshot into the text of each row
+fn describe(diagnostics: &PipelineDiagnostics, drift: Option<i64>, drift_stats: Option<DriftStats>, shared_clock: &Clock) -> Vec<String> {
+    let time = |time: Option<ClockTime>| time.map_or(String::from("-"), |time| format_clock(time.nseconds()));
+    let milliseconds = |nanoseconds: i64| format!("{:+.1} ms", nanoseconds as f64 / 1_000_000.0);
+
+    let state = if diagnostics.pending_state == gstreamer::State::VoidPending {
+        format!("{:?}", diagnostics.state)
+    } else {
+        format!("{:?} → {:?}", diagnostics.state, diagnostics.pending_state)
+    };
+    let clock = match &diagnostics.clock {
+        Some(clock) if clock == shared_clock => format!("{} (shared)", clock.name()),
+        Some(clock) => clock.name().to_string(),
+        None => String::from("-"),
+    };
+    let direction = match diagnostics.direction {
+        PlaybackDirection::Forward => "Forward",
+        PlaybackDirection::Reverse => "Reverse",
+    };
+    let clamp = diagnostics.clamp.map_or(String::from("None"), |(start, end)| {
+        format!("{} - {}", format_clock(start.nseconds()), format_clock(end.nseconds()))
+    });
+    let frame_duration = diagnostics.frame_duration.map_or(String::from("-"), |duration| {
+        format!("{:.3} ms ({:.3} fps)", duration as f64 / 1_000_000.0, 1_000_000_000.0 / duration as f64)
+    });
+    let frames = match (diagnostics.rendered_frames, diagnostics.dropped_frames) {
+        (Some(rendered), Some(dropped)) => format!("{rendered} / {dropped}"),
+        _ => String::from("-"),
+    };
+    let qos = &diagnostics.qos;
+    let qos = if qos.messages == 0 {
+        String::from("No reports")
+    } else {
+        format!(
+            "{} reports, jitter {}, proportion {:.3}, quality {}, {} processed, {} dropped",
+            qos.messages, milliseconds(qos.jitter), qos.proportion, qos.quality, qos.processed, qos.dropped
+        )
+    };
+
+    let drift_correction = drift_stats.map_or(String::from("-"), |stats| {
+        let current = match stats.correction {
+            DriftCorrection::None => String::from("none"),
+            DriftCorrection::RateNudge(rate) => format!("playing at {rate:.2}x"),
+            DriftCorrection::Seek => String::from("seeked"),
+        };
+        format!(
+            "{} from the others, worst {}, {} corrections, {current}",
+            milliseconds(stats.relative_drift),
+            milliseconds(stats.max_relative_drift as i64),
+            stats.corrections
+        )
+    });
+
+    vec![
+        state,
+        clock,
+        time(diagnostics.base_time),
+        time(diagnostics.position),
+        time(diagnostics.duration),
+        diagnostics.rate.map_or(String::from("-"), |rate| format!("{rate:.3}")),
+        direction.to_string(),
+        clamp,
+        frame_duration,
+        frames,
+        qos,
+        drift.map_or(String::from("Not playing in sync"), milliseconds),
+        drift_correction,
+    ]
+}
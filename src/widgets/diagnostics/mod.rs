@@ -0,0 +1 @@
+pub mod diagnostics_window;
\ No newline at end of file
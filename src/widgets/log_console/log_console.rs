@@ -0,0 +1,265 @@
+use gtk::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use gtk::{Button, DropDown, Entry, SearchEntry, TextView, ToggleButton, TemplateChild};
+use std::cell::{Cell, RefCell};
+use crate::helpers::ui::show_error;
+use crate::logging::{self, Level, Record};
+
+//How often new messages are fetched
+const REFRESH_INTERVAL_MS: u64 = 250;
+//Lines kept in the view, older lines are dropped from the top
+const MAX_LINES: i32 = 5000;
+
+mod imp {
+    use super::*;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/logconsole/logconsole.ui")]
+    pub struct LogConsole {
+        #[template_child]
+        pub filter_entry: TemplateChild<Entry>,
+        #[template_child]
+        pub level_dropdown: TemplateChild<DropDown>,
+        #[template_child]
+        pub search_entry: TemplateChild<SearchEntry>,
+        #[template_child]
+        pub follow_button: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub copy_button: TemplateChild<Button>,
+        #[template_child]
+        pub save_button: TemplateChild<Button>,
+        #[template_child]
+        pub clear_button: TemplateChild<Button>,
+        #[template_child]
+        pub text_view: TemplateChild<TextView>,
+
+        //Id of the last message fetched from the logger
+        pub last_record_id: Cell<Option<u64>>,
+        pub refresh_source: RefCell<Option<glib::SourceId>>,
+    }
+
+    #[gtk::glib::object_subclass]
+    impl ObjectSubclass for LogConsole {
+        const NAME: &'static str = "LogConsole";
+        type Type = super::LogConsole;
+        type ParentType = gtk::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LogConsole {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.setup_controls();
+            obj.reload();
+            obj.start_refresh();
+        }
+
+        fn dispose(&self) {
+            if let Some(source) = self.refresh_source.borrow_mut().take() {
+                source.remove();
+            }
+        }
+    }
+    impl WidgetImpl for LogConsole {}
+    impl WindowImpl for LogConsole {}
+}
+
+glib::wrapper! {
+    pub struct LogConsole(ObjectSubclass<imp::LogConsole>)
+    @extends gtk::Widget, gtk::Window,
+    @implements gtk::Buildable, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+// Log Console:
+// Shows logged messages as they arrive so they can be filtered, copied or saved for bug reports
+impl LogConsole {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    fn setup_controls(&self) {
+        let imp = self.imp();
+
+        //Changes which messages are recorded, not only which are shown
+        imp.filter_entry.set_text(&logging::get_filter());
+        imp.filter_entry.connect_activate(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |entry| {
+                let spec = entry.text().to_string();
+                match logging::set_filter(&spec) {
+                    Ok(()) => log_info!("Log filter set to \"{spec}\""),
+                    Err(e) => {
+                        show_error(&this, &e);
+                        entry.set_text(&logging::get_filter());
+                    }
+                }
+            }
+        ));
+
+        imp.level_dropdown.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.reload();
+            }
+        ));
+
+        imp.search_entry.connect_search_changed(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.reload();
+            }
+        ));
+
+        imp.follow_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |button| {
+                if button.is_active() {
+                    this.scroll_to_end();
+                }
+            }
+        ));
+
+        imp.copy_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                let buffer = this.imp().text_view.buffer();
+                let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                this.clipboard().set_text(&text);
+            }
+        ));
+
+        imp.save_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.save();
+            }
+        ));
+
+        imp.clear_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                logging::clear_records();
+                this.imp().text_view.buffer().set_text("");
+            }
+        ));
+    }
+
+    //Polls the logger for new messages while the console exists
+    fn start_refresh(&self) {
+        let source = glib::timeout_add_local(std::time::Duration::from_millis(REFRESH_INTERVAL_MS), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                //Hidden consoles catch up when shown again
+                if this.is_visible() {
+                    let records = logging::records_after(this.imp().last_record_id.get());
+                    this.append_records(&records);
+                }
+                glib::ControlFlow::Continue
+            }
+        ));
+        self.imp().refresh_source.replace(Some(source));
+    }
+
+    //Gets the most detailed level selected to be shown
+    fn get_shown_level(&self) -> Level {
+        let selected = self.imp().level_dropdown.selected() as usize;
+        Level::ALL.get(selected).copied().unwrap_or(Level::Trace)
+    }
+
+    //Checks if a message passes the level and search filters
+    fn is_shown(&self, record: &Record, level: Level, search: &str) -> bool {
+        if record.level > level {
+            return false;
+        }
+        search.is_empty()
+            || record.module.to_lowercase().contains(search)
+            || record.message.to_lowercase().contains(search)
+    }
+
+    //Adds messages to the end of the view
+    fn append_records(&self, records: &[Record]) {
+        let imp = self.imp();
+        let last = match records.last() {
+            Some(record) => record.id,
+            None => return,
+        };
+        imp.last_record_id.set(Some(last));
+
+        let level = self.get_shown_level();
+        let search = imp.search_entry.text().to_lowercase();
+        let text: String = records.iter()
+            .filter(|record| self.is_shown(record, level, &search))
+            .map(|record| format!("{record}\n"))
+            .collect();
+        if text.is_empty() {
+            return;
+        }
+
+        let buffer = imp.text_view.buffer();
+        buffer.insert(&mut buffer.end_iter(), &text);
+        let extra_lines = buffer.line_count() - MAX_LINES;
+        if extra_lines > 0 {
+            if let Some(mut cut) = buffer.iter_at_line(extra_lines) {
+                buffer.delete(&mut buffer.start_iter(), &mut cut);
+            }
+        }
+        if imp.follow_button.is_active() {
+            self.scroll_to_end();
+        }
+    }
+
+    //Rebuilds the view from every kept message, used when a filter changes
+    fn reload(&self) {
+        let imp = self.imp();
+        imp.text_view.buffer().set_text("");
+        imp.last_record_id.set(None);
+        self.append_records(&logging::records_after(None));
+    }
+
+    fn scroll_to_end(&self) {
+        let text_view = &self.imp().text_view;
+        let buffer = text_view.buffer();
+        let mark = buffer.create_mark(None, &buffer.end_iter(), false);
+        text_view.scroll_to_mark(&mark, 0.0, false, 0.0, 1.0);
+        buffer.delete_mark(&mark);
+    }
+
+    //Saves every kept message regardless of the shown filters
+    fn save(&self) {
+        let dialog = gtk::FileChooserDialog::builder()
+            .title("Save Log")
+            .action(gtk::FileChooserAction::Save)
+            .modal(true)
+            .transient_for(self)
+            .build();
+        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+        dialog.add_button("Save", gtk::ResponseType::Accept);
+        dialog.set_current_name("multi-video-timing-analysis.log");
+
+        dialog.run_async(|obj, res| {
+            if res == gtk::ResponseType::Accept {
+                if let Some(path) = obj.file().and_then(|file| file.path()) {
+                    let text: String = logging::records_after(None).iter()
+                        .map(|record| format!("{record}\n"))
+                        .collect();
+                    match std::fs::write(&path, text) {
+                        Ok(()) => log_info!("Saved log to {}", path.to_string_lossy()),
+                        Err(e) => log_error!("Failed to save log to {}: {e}", path.to_string_lossy()),
+                    }
+                }
+            }
+            obj.destroy();
+        });
+    }
+}
@@ -0,0 +1 @@
+pub mod log_console;
\ No newline at end of file
@@ -1,4 +1,8 @@
 pub mod video_player_widget;
 pub mod split_panel;
 pub mod seek_bar;
-pub mod sync;
\ No newline at end of file
+pub mod sync;
+pub mod audio_mixer;
+pub mod log_console;
+pub mod diagnostics;
+pub mod nudge_panel;
\ No newline at end of file
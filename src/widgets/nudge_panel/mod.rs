@@ -0,0 +1 @@
+pub mod nudge_panel;
\ No newline at end of file
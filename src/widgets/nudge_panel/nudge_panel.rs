@@ -0,0 +1,176 @@
+use gtk::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use gtk::{Box, Button, Label, SpinButton, TemplateChild};
+use glib::subclass::Signal;
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::helpers::format::format_clock;
+use crate::widgets::split_panel::timeentry::TimeEntry;
+
+//Size of the small time nudge in nanoseconds
+const NUDGE_STEP: i64 = 10_000_000;
+//Largest custom nudge in milliseconds
+const MAX_CUSTOM_NUDGE_MS: f64 = 10_000.0;
+
+//Controls for a single video in the nudge panel
+pub struct NudgeChannel {
+    pub row: Box,
+    pub offset_entry: TimeEntry,
+    pub offset_handler: glib::SignalHandlerId,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/nudgepanel/nudgepanel.ui")]
+    pub struct NudgePanel {
+        #[template_child]
+        pub channel_container: TemplateChild<Box>,
+
+        pub channels: RefCell<HashMap<String, NudgeChannel>>,
+    }
+
+    #[gtk::glib::object_subclass]
+    impl ObjectSubclass for NudgePanel {
+        const NAME: &'static str = "NudgePanel";
+        type Type = super::NudgePanel;
+        type ParentType = Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for NudgePanel {
+        fn signals() -> &'static [Signal] {
+            //Nudge signals pass the video player id and how far to move its start time offset
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("nudge-time")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type(), i64::static_type()])
+                    .build(),
+                    Signal::builder("nudge-frames")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type(), i64::static_type()])
+                    .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn dispose(&self) {
+            for (_, channel) in self.channels.borrow_mut().drain() {
+                channel.offset_entry.disconnect(channel.offset_handler);
+            }
+        }
+    }
+    impl WidgetImpl for NudgePanel {}
+    impl BoxImpl for NudgePanel {}
+}
+
+glib::wrapper! {
+    pub struct NudgePanel(ObjectSubclass<imp::NudgePanel>)
+    @extends gtk::Widget,
+    @implements gtk::Buildable;
+}
+
+// Nudge Panel:
+// Moves the start time offset of a single video by a frame, 10 ms or a typed amount while in sync view
+impl NudgePanel {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    //Adds a channel for a video player
+    //Inputs: video player id, color assigned to the video player, start time offset of the video
+    pub fn add_channel(&self, video_player_id: &str, color: &str, offset_entry: &TimeEntry) {
+        let imp = self.imp();
+        self.remove_channel(video_player_id);
+
+        let row = Box::new(gtk::Orientation::Horizontal, 4);
+        row.add_css_class("nudge-channel");
+
+        let label = Label::new(None);
+        label.set_markup(&format!("<span foreground='{color}'>●</span> Video {video_player_id}"));
+
+        //Current offset follows the split table
+        let offset_label = Label::new(Some(&format_clock(offset_entry.get_time())));
+        offset_label.set_width_chars(12);
+        let offset_handler = offset_entry.connect_notify_local(Some("time"), glib::clone!(
+            #[weak] offset_label,
+            move |entry, _| {
+                offset_label.set_label(&format_clock(entry.get_time()));
+            }
+        ));
+
+        row.append(&label);
+        row.append(&offset_label);
+        row.append(&self.nudge_button("-1f", "Show one frame earlier of this video", video_player_id, "nudge-frames", -1));
+        row.append(&self.nudge_button("-10ms", "Move the start time 10 ms earlier", video_player_id, "nudge-time", -NUDGE_STEP));
+        row.append(&self.nudge_button("+10ms", "Move the start time 10 ms later", video_player_id, "nudge-time", NUDGE_STEP));
+        row.append(&self.nudge_button("+1f", "Show one frame later of this video", video_player_id, "nudge-frames", 1));
+
+        //Custom nudge in milliseconds, negative moves the start time earlier
+        let custom_spin = SpinButton::with_range(-MAX_CUSTOM_NUDGE_MS, MAX_CUSTOM_NUDGE_MS, 1.0);
+        custom_spin.set_value(0.0);
+        custom_spin.set_tooltip_text(Some("Milliseconds to move the start time by"));
+        let custom_button = Button::with_label("Nudge");
+        custom_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[weak] custom_spin,
+            #[to_owned] video_player_id,
+            move |_| {
+                let delta = (custom_spin.value() * 1_000_000.0) as i64;
+                if delta != 0 {
+                    this.emit_by_name::<()>("nudge-time", &[&video_player_id, &delta]);
+                }
+            }
+        ));
+        row.append(&custom_spin);
+        row.append(&custom_button);
+        imp.channel_container.append(&row);
+
+        imp.channels.borrow_mut().insert(video_player_id.to_string(), NudgeChannel {
+            row,
+            offset_entry: offset_entry.clone(),
+            offset_handler,
+        });
+    }
+
+    //Removes the channel of a video player
+    pub fn remove_channel(&self, video_player_id: &str) {
+        let imp = self.imp();
+        if let Some(channel) = imp.channels.borrow_mut().remove(video_player_id) {
+            channel.offset_entry.disconnect(channel.offset_handler);
+            imp.channel_container.remove(&channel.row);
+        }
+    }
+
+    //Creates a button that emits a nudge signal with a fixed amount
+    fn nudge_button(&self, label: &str, tooltip: &str, video_player_id: &str, signal: &'static str, amount: i64) -> Button {
+        let button = Button::with_label(label);
+        button.set_tooltip_text(Some(tooltip));
+        button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[to_owned] video_player_id,
+            move |_| {
+                this.emit_by_name::<()>(signal, &[&video_player_id, &amount]);
+            }
+        ));
+        button
+    }
+}
+
+impl Default for NudgePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
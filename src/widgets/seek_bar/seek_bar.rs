@@ -2,12 +2,59 @@ use gtk::prelude::*;
 use gtk::glib;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
-use gtk::{Label, Overlay, Box, Scale, TemplateChild, Fixed};
+use gtk::{gdk, Label, Overlay, Box, Scale, TemplateChild, Fixed, DrawingArea, Picture, Popover, SingleSelection};
+use gio::ListStore;
+use glib::WeakRef;
 use std::{cell::{RefCell, Cell}, collections::HashMap};
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use crate::widgets::split_panel::timeentry::TimeEntry;
+use crate::widgets::split_panel::splits::VideoSegment;
+use crate::waveform::WaveformData;
+use crate::thumbnails::ThumbnailData;
+use crate::helpers::format::format_clock;
+
+//Shortest span of time the seek bar can be zoomed in to
+const MIN_VIEW_LENGTH_NS: u64 = 100_000_000;
+//Spacing in nanoseconds the ruler can use between labelled ticks
+const RULER_STEPS_NS: [u64; 18] = [
+    10_000_000, 50_000_000, 100_000_000, 250_000_000, 500_000_000,
+    1_000_000_000, 2_000_000_000, 5_000_000_000, 10_000_000_000, 15_000_000_000, 30_000_000_000,
+    60_000_000_000, 120_000_000_000, 300_000_000_000, 600_000_000_000, 900_000_000_000, 1_800_000_000_000,
+    3_600_000_000_000,
+];
+
+//Height in pixels of a single segment band lane
+const BAND_LANE_HEIGHT: i32 = 16;
+
+//Height in pixels of a single waveform lane
+const WAVEFORM_LANE_HEIGHT: i32 = 36;
+
+//Audio waveform drawn under the seek bar for one video
+pub struct WaveformLane {
+    pub data: Arc<Mutex<WaveformData>>,
+    pub offset: TimeEntry,
+    pub color: String,
+    pub offset_handler: glib::SignalHandlerId,
+}
+
+//Segment bands drawn under the seek bar for one video
+pub struct BandLane {
+    pub offset: TimeEntry,
+    pub color: String,
+    pub offset_handler: glib::SignalHandlerId,
+}
+
+//Thumbnails of one video shown when hovering the seek bar
+pub struct PreviewSource {
+    pub data: Arc<Mutex<ThumbnailData>>,
+    pub offset: TimeEntry,
+    pub color: String,
+}
 
 mod imp {
+    use glib::subclass::Signal;
+    use once_cell::sync::Lazy;
 
     use super::*;
     
@@ -19,6 +66,18 @@ mod imp {
         pub timeline_dirty_flag: RefCell<bool>,
         pub auto_length_from_marks: RefCell<bool>,
         pub last_width: Cell<i32>,
+        pub waveform_lanes: RefCell<Vec<(String, WaveformLane)>>,
+        pub preview_sources: RefCell<Vec<(String, PreviewSource)>>,
+        pub preview_popover: RefCell<Option<Popover>>,
+        pub preview_box: RefCell<Option<Box>>,
+        pub filmstrip_enabled: Cell<bool>,
+        pub view_start: Cell<u64>,
+        pub view_length: Cell<u64>,
+        pub last_set_time: Cell<u64>,
+        pub pointer_x: Cell<f64>,
+        pub band_lanes: RefCell<Vec<(String, BandLane)>>,
+        pub band_segments: RefCell<Option<WeakRef<ListStore>>>,
+        pub band_selection: RefCell<Option<WeakRef<SingleSelection>>>,
 
         #[template_child]
         pub scale: TemplateChild<Scale>,
@@ -28,6 +87,18 @@ mod imp {
 
         #[template_child]
         pub overlay: TemplateChild<Overlay>,
+
+        #[template_child]
+        pub ruler: TemplateChild<DrawingArea>,
+
+        #[template_child]
+        pub bands_area: TemplateChild<DrawingArea>,
+
+        #[template_child]
+        pub filmstrip: TemplateChild<Box>,
+
+        #[template_child]
+        pub waveform_area: TemplateChild<DrawingArea>,
     }
     
     #[gtk::glib::object_subclass]
@@ -46,7 +117,40 @@ mod imp {
     }
     
     impl ObjectImpl for SeekBar {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_waveform_area();
+            self.obj().setup_preview_popover();
+            self.obj().setup_zoom();
+            self.obj().setup_bands_area();
+        }
+
+        fn signals() -> &'static [Signal] {
+            //Mark signals pass the video player id, segment id and the split time in the video
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("mark-clicked")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type(), String::static_type(), u64::static_type(), u64::static_type()])
+                    .build(),
+                    Signal::builder("mark-dragged")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type(), String::static_type(), u64::static_type()])
+                    .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
         fn dispose(&self) {
+            if let Some(popover) = self.preview_popover.borrow_mut().take() {
+                popover.unparent();
+            }
+            for (_, lane) in self.waveform_lanes.borrow_mut().drain(..) {
+                lane.offset.disconnect(lane.offset_handler);
+            }
+            for (_, lane) in self.band_lanes.borrow_mut().drain(..) {
+                lane.offset.disconnect(lane.offset_handler);
+            }
         }
     }
     impl WidgetImpl for SeekBar {}
@@ -88,8 +192,9 @@ impl SeekBar {
     }
     
     //Add new mark to seek bar
-    //Inputs: Id - (video_player_id and segment_id), TimeEntry - split table cell entre, color - video player's associated mark color, offset - start time offset for the video
-    pub fn add_mark(&self, id: String, time_entry: TimeEntry, color: &str, offset: TimeEntry) {
+    //Inputs: Id - (video_player_id and segment_id), TimeEntry - split table cell entre, color - video player's associated mark color, offset - start time offset for the video,
+    //        video_player_id - video the split belongs to, segment - split table row of the split
+    pub fn add_mark(&self, id: String, time_entry: TimeEntry, color: &str, offset: TimeEntry, video_player_id: &str, segment: &VideoSegment) {
         let imp = imp::SeekBar::from_obj(self);
         
         //Setup mark widget
@@ -98,10 +203,13 @@ impl SeekBar {
         mark.set_halign(gtk::Align::Center);
         mark.set_valign(gtk::Align::Center);
         mark.set_visible(false);
+        mark.set_cursor_from_name(Some("grab"));
 
         //Set default position while not visible
         imp.fixed.put(&mark, 0.0, 0.0);
 
+        self.setup_mark_interaction(&mark, &time_entry, &offset, video_player_id, segment);
+
 
         let entry_time = if time_entry.get_time() == u64::MAX { u64::MAX } else { time_entry.get_time() - offset.get_time()};
 
@@ -112,10 +220,8 @@ impl SeekBar {
 
         //Updates mark position if associated time entry changes
         time_entry.connect_notify_local(Some("time"), glib::clone!(
-            #[strong(rename_to = fixed_overlay)] imp.fixed.clone(),
             #[strong(rename_to = mark_clone)] mark.clone(),
             #[strong(rename_to = timeline_length)] imp.timeline_length,
-            #[strong(rename_to = scale)] imp.scale,
             #[strong(rename_to = dirty_flag)] imp.timeline_dirty_flag,
             #[weak(rename_to = this)] self,
             #[strong(rename_to = auto_length)] imp.auto_length_from_marks,
@@ -124,7 +230,6 @@ impl SeekBar {
                 //Retrieve new and old time position
                 let old_time = if time_entry.get_old_time() == u64::MAX { 0 } else { time_entry.get_old_time() - offset.get_time()};
                 let time = time_entry.get_time().checked_sub(offset.get_time());
-                let width = scale.width();
                 
                 //Update timeline length dirt flag if the new time would affect timeline length and auto timeline length handling is enabled
                 if (old_time == *timeline_length.borrow() || time.unwrap() > *timeline_length.borrow()) && *auto_length.borrow() {
@@ -136,26 +241,13 @@ impl SeekBar {
                 }
                 
                 if *timeline_length.borrow() == 0 {
-                    eprintln!("Timeline_length is still 0");
+                    log_warn!("Timeline length is still 0");
                     return;
                 }
                 //Removes mark from view if time entry is unset
-                if time_entry.get_time() == u64::MAX || time.is_none() {
-                    mark_clone.set_visible(false);
-                    fixed_overlay.move_(&mark_clone, 0.0, 25.0);
-                } else {
-                    //Displays mark
-                    mark_clone.set_visible(true);
-
-                    //Finds mark position based on time entries time and adjusts precise position based on the marks widget size.
-                    let (_min, natural, _min_b, _nat_b) = mark_clone.measure(gtk::Orientation::Horizontal, -1);
-                    let widget_width = natural;
-                    let percent = time.unwrap() as f64 / *timeline_length.borrow() as f64;
-                    let x = percent * (width - 4) as f64;
-                    
-                    //Sets the mark position
-                    fixed_overlay.move_(&mark_clone, x - (widget_width as f64 / 2.0) + 2.0, 25.0);
-                }
+                let time = if time_entry.get_time() == u64::MAX { None } else { time };
+                this.position_mark(mark_clone.upcast_ref(), time);
+                this.imp().bands_area.queue_draw();
             }
         ));
         
@@ -163,6 +255,89 @@ impl SeekBar {
         imp.marks.borrow_mut().insert(id.clone(), (time_entry, offset, mark.clone().upcast()));
     }
 
+    //Adds a tooltip, click to seek and drag to adjust to a mark
+    //Inputs: mark widget, split time entry, start time offset for the video, video player id, split table row of the split
+    fn setup_mark_interaction(&self, mark: &Label, time_entry: &TimeEntry, offset: &TimeEntry, video_player_id: &str, segment: &VideoSegment) {
+        let video_player_id = video_player_id.to_string();
+        let segment_id = segment.get_segment_id();
+
+        //Tooltip is built when shown so it reflects the current name and time
+        mark.set_has_tooltip(true);
+        mark.connect_query_tooltip(glib::clone!(
+            #[weak] segment,
+            #[strong] time_entry,
+            #[strong] video_player_id,
+            #[upgrade_or] false,
+            move |_, _, _, _, tooltip| {
+                let time = time_entry.get_time();
+                if time == u64::MAX {
+                    return false;
+                }
+                let relative_time = time.saturating_sub(segment.get_offset(video_player_id.as_str()));
+                tooltip.set_text(Some(&format!("{}\nVideo {}\n{}", segment.get_name(), video_player_id, format_clock(relative_time))));
+                true
+            }
+        ));
+
+        //Center of the mark when the drag started
+        let drag_origin = Rc::new(Cell::new(0.0));
+        let drag = gtk::GestureDrag::new();
+        drag.connect_drag_begin(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[weak] mark,
+            #[strong] drag_origin,
+            move |_, _, _| {
+                let (x, _) = this.imp().fixed.child_position(&mark);
+                drag_origin.set(x + mark.width() as f64 / 2.0);
+                mark.set_cursor_from_name(Some("grabbing"));
+            }
+        ));
+        drag.connect_drag_update(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[weak] mark,
+            #[strong] drag_origin,
+            move |_, offset_x, _| {
+                let imp = this.imp();
+                let center = (drag_origin.get() + offset_x).clamp(2.0, (imp.scale.width() - 2) as f64);
+                imp.fixed.move_(&mark, center - mark.width() as f64 / 2.0, 0.0);
+            }
+        ));
+        drag.connect_drag_end(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[weak] mark,
+            #[strong] drag_origin,
+            #[strong] time_entry,
+            #[strong] offset,
+            #[strong] video_player_id,
+            #[strong] segment_id,
+            move |_, offset_x, _| {
+                let imp = this.imp();
+                mark.set_cursor_from_name(Some("grab"));
+                let offset_time = if offset.get_time() == u64::MAX { 0 } else { offset.get_time() };
+                let split_time = time_entry.get_time();
+
+                if offset_x.abs() < 3.0 {
+                    //Barely moved so it is treated as a click
+                    if split_time != u64::MAX {
+                        let timeline_time = split_time.saturating_sub(offset_time);
+                        this.emit_by_name::<()>("mark-clicked", &[&video_player_id, &segment_id, &split_time, &timeline_time]);
+                    }
+                } else {
+                    let center = (drag_origin.get() + offset_x).clamp(2.0, (imp.scale.width() - 2) as f64);
+                    if let Some(timeline_time) = this.time_at_x(center, imp.scale.width()) {
+                        let new_time = timeline_time.min(*imp.timeline_length.borrow()) + offset_time;
+                        this.emit_by_name::<()>("mark-dragged", &[&video_player_id, &segment_id, &new_time]);
+                    }
+                }
+
+                //Returns the mark to its time in case the new time was rejected
+                let time = if time_entry.get_time() == u64::MAX { None } else { time_entry.get_time().checked_sub(offset_time) };
+                this.position_mark(mark.upcast_ref(), time);
+            }
+        ));
+        mark.add_controller(drag);
+    }
+
     //Removes mark for seek bar
     pub fn remove_mark(&self, id: &str) {
         let imp = imp::SeekBar::from_obj(self);
@@ -176,25 +351,34 @@ impl SeekBar {
     pub fn update_mark_positions(&self) {
         let imp = imp::SeekBar::from_obj(self);
         //Loops through each mark
-        for (time_entry, offset, widget) in imp.marks.borrow().values() {
-            let time = time_entry.get_time().checked_sub(offset.get_time());
-            if *imp.timeline_length.borrow() == 0 {
-                return;
+        if *imp.timeline_length.borrow() != 0 {
+            for (time_entry, offset, widget) in imp.marks.borrow().values() {
+                //Hides mark if time entry unset
+                let time = if time_entry.get_time() == u64::MAX { None } else { time_entry.get_time().checked_sub(offset.get_time()) };
+                self.position_mark(widget, time);
             }
-            //Hides mark if time entry unset
-            if time_entry.get_time() == u64::MAX || time.is_none() {
-                widget.set_visible(false);
-                imp.fixed.move_(widget, 0.0, 25.0);
-            } else {
-                //Displays mark and finds precise positioning 
+        }
+        //Ruler, bands, waveforms and filmstrip follow the same visible window as the marks
+        imp.ruler.queue_draw();
+        imp.bands_area.queue_draw();
+        imp.waveform_area.queue_draw();
+        self.update_filmstrip();
+    }
+
+    //Places a mark at its time or hides it when the time is unset or outside the visible window
+    //Inputs: mark widget, time relative to the start of the timeline
+    fn position_mark(&self, widget: &gtk::Widget, time: Option<u64>) {
+        let imp = self.imp();
+        match time.and_then(|time| self.x_for_time(time, imp.scale.width())) {
+            Some(x) => {
+                //Displays mark and adjusts precise position based on the marks widget size
                 widget.set_visible(true);
                 let (_min, natural, _min_b, _nat_b) = widget.measure(gtk::Orientation::Horizontal, -1);
-                    let widget_width = natural;
-                let percent = time.unwrap() as f64 / *imp.timeline_length.borrow() as f64;
-                let x_pos = percent * (imp.scale.width() - 4) as f64;
-
-                //Sets the marks new position
-                imp.fixed.move_(widget, x_pos - (widget_width as f64 / 2.0), 25.0);
+                imp.fixed.move_(widget, x - (natural as f64 / 2.0), 0.0);
+            }
+            None => {
+                widget.set_visible(false);
+                imp.fixed.move_(widget, 0.0, 0.0);
             }
         }
     }
@@ -210,8 +394,8 @@ impl SeekBar {
         let imp = imp::SeekBar::from_obj(self);
         *imp.timeline_length.borrow_mut() = timeline_length;
 
-        //Update any currently store marks on the seek bar
-        self.update_mark_positions();
+        //Update the visible window and any currently store marks on the seek bar
+        self.update_view();
     }
 
     //Gets the timeline length of the seek bar
@@ -241,8 +425,8 @@ impl SeekBar {
         *imp.timeline_length.borrow_mut() = largest_time;
         *imp.timeline_dirty_flag.borrow_mut() = false;
         
-        //Update any currently store marks on the seek bar
-        self.update_mark_positions();
+        //Update the visible window and any currently store marks on the seek bar
+        self.update_view();
     }
 
     //Sets automatic timeline length handling whenever marks are added or change position 
@@ -258,5 +442,670 @@ impl SeekBar {
         }
         self.update_timeline_length();
     }
-}
 
+    //Add an audio waveform lane under the seek bar
+    //Inputs: Id - video_player_id, waveform data, color - video player's associated color, offset - start time offset for the video
+    pub fn add_waveform_lane(&self, id: String, data: Arc<Mutex<WaveformData>>, color: &str, offset: TimeEntry) {
+        let imp = self.imp();
+        self.remove_waveform_lane(&id);
+
+        //Redraws when the offset of the video changes so the waveform stays aligned
+        let offset_handler = offset.connect_notify_local(Some("time"), glib::clone!(
+            #[weak(rename_to = waveform_area)] imp.waveform_area,
+            move |_, _| {
+                waveform_area.queue_draw();
+            }
+        ));
+
+        //Redraws while the waveform is still being computed in the background
+        glib::timeout_add_local(std::time::Duration::from_millis(250), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[strong] data,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                let imp = this.imp();
+                let still_shown = imp.waveform_lanes.borrow().iter().any(|(_, lane)| Arc::ptr_eq(&lane.data, &data));
+                if !still_shown {
+                    return glib::ControlFlow::Break;
+                }
+                imp.waveform_area.queue_draw();
+                let complete = data.lock().unwrap().complete;
+                if complete {
+                    glib::ControlFlow::Break
+                } else {
+                    glib::ControlFlow::Continue
+                }
+            }
+        ));
+
+        imp.waveform_lanes.borrow_mut().push((id, WaveformLane {
+            data,
+            offset,
+            color: color.to_string(),
+            offset_handler,
+        }));
+        self.update_waveform_area_size();
+    }
+
+    //Removes the waveform lane of a video
+    pub fn remove_waveform_lane(&self, id: &str) {
+        let imp = self.imp();
+        let removed = {
+            let mut lanes = imp.waveform_lanes.borrow_mut();
+            lanes.iter().position(|(lane_id, _)| lane_id == id).map(|index| lanes.remove(index))
+        };
+        if let Some((_, lane)) = removed {
+            lane.offset.disconnect(lane.offset_handler);
+            self.update_waveform_area_size();
+        }
+    }
+
+    //Removes every waveform lane
+    pub fn reset_waveform_lanes(&self) {
+        let imp = self.imp();
+        for (_, lane) in imp.waveform_lanes.borrow_mut().drain(..) {
+            lane.offset.disconnect(lane.offset_handler);
+        }
+        self.update_waveform_area_size();
+    }
+
+    //Resizes the waveform area to fit every lane and hides it when there are none
+    fn update_waveform_area_size(&self) {
+        let imp = self.imp();
+        let lane_count = imp.waveform_lanes.borrow().len() as i32;
+        imp.waveform_area.set_content_height(lane_count * WAVEFORM_LANE_HEIGHT);
+        imp.waveform_area.set_visible(lane_count > 0);
+        imp.waveform_area.queue_draw();
+    }
+
+    fn setup_waveform_area(&self) {
+        let imp = self.imp();
+        imp.waveform_area.set_draw_func(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, cr, width, _height| {
+                this.draw_waveforms(cr, width);
+            }
+        ));
+    }
+
+    //Gets the timeline time at a horizontal position, matching the positioning used for marks
+    //Inputs: x position, width of the scale
+    //Output: time in nanoseconds, None if the position is before the start of the timeline
+    fn time_at_x(&self, x: f64, width: i32) -> Option<u64> {
+        let (start, length) = self.get_visible_window();
+        let percent = (x - 2.0) / (width - 4) as f64;
+        if percent < 0.0 {
+            return None;
+        }
+        Some(start + (percent * length as f64) as u64)
+    }
+
+    //Converts a horizontal position over the overlay to one on the scale, so the scale padding set by the style is taken into account
+    //Inputs: x position in overlay coordinates
+    //Output: x position in scale coordinates, None if the widgets are not placed yet
+    fn scale_x(&self, overlay_x: f64) -> Option<f64> {
+        let imp = self.imp();
+        imp.overlay.compute_point(&*imp.scale, &gtk::graphene::Point::new(overlay_x as f32, 0.0)).map(|point| point.x() as f64)
+    }
+
+    //Gets the horizontal position of a timeline time
+    //Inputs: time in nanoseconds, width of the scale
+    //Output: x position, None if the time is outside the visible window
+    fn x_for_time(&self, time: u64, width: i32) -> Option<f64> {
+        let (start, length) = self.get_visible_window();
+        if length == 0 || time < start || time > start + length {
+            return None;
+        }
+        let percent = (time - start) as f64 / length as f64;
+        Some(percent * (width - 4) as f64 + 2.0)
+    }
+
+    //Draws each waveform lane as vertical peak lines
+    fn draw_waveforms(&self, cr: &gtk::cairo::Context, width: i32) {
+        let imp = self.imp();
+        if *imp.timeline_length.borrow() == 0 || width <= 4 {
+            return;
+        }
+
+        let lane_height = WAVEFORM_LANE_HEIGHT as f64;
+        for (index, (_, lane)) in imp.waveform_lanes.borrow().iter().enumerate() {
+            let top = index as f64 * lane_height;
+            let middle = top + lane_height / 2.0;
+
+            //Lane background
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.05);
+            cr.rectangle(0.0, top + 1.0, width as f64, lane_height - 2.0);
+            let _ = cr.fill();
+
+            let color = gdk::RGBA::parse(lane.color.as_str()).unwrap_or(gdk::RGBA::WHITE);
+            cr.set_source_rgba(color.red() as f64, color.green() as f64, color.blue() as f64, 0.9);
+            cr.set_line_width(1.0);
+
+            //Timeline time plus the offset is the position in the video
+            let offset = if lane.offset.get_time() == u64::MAX { 0 } else { lane.offset.get_time() };
+            let data = lane.data.lock().unwrap();
+            for x in 0..width {
+                let (start, end) = match (self.time_at_x(x as f64, width), self.time_at_x(x as f64 + 1.0, width)) {
+                    (Some(start), Some(end)) => (start + offset, end + offset),
+                    _ => continue,
+                };
+                let peak = match data.peak_between(start, end) {
+                    Some(peak) => peak as f64,
+                    None => continue,
+                };
+                let half_height = (peak * (lane_height - 4.0) / 2.0).max(0.5);
+                cr.move_to(x as f64 + 0.5, middle - half_height);
+                cr.line_to(x as f64 + 0.5, middle + half_height);
+            }
+            let _ = cr.stroke();
+        }
+    }
+
+    //Adds thumbnails shown when hovering over the seek bar
+    //Inputs: Id - video_player_id, thumbnail data, color - video player's associated color, offset - start time offset for the video
+    pub fn add_preview_source(&self, id: String, data: Arc<Mutex<ThumbnailData>>, color: &str, offset: TimeEntry) {
+        let imp = self.imp();
+        self.remove_preview_source(&id);
+
+        //Refreshes the filmstrip while thumbnails are still being generated
+        glib::timeout_add_local(std::time::Duration::from_millis(500), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[strong] data,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                let still_shown = this.imp().preview_sources.borrow().iter().any(|(_, source)| Arc::ptr_eq(&source.data, &data));
+                if !still_shown {
+                    return glib::ControlFlow::Break;
+                }
+                this.update_filmstrip();
+                let complete = data.lock().unwrap().complete;
+                if complete {
+                    glib::ControlFlow::Break
+                } else {
+                    glib::ControlFlow::Continue
+                }
+            }
+        ));
+
+        imp.preview_sources.borrow_mut().push((id, PreviewSource {
+            data,
+            offset,
+            color: color.to_string(),
+        }));
+        self.update_filmstrip();
+    }
+
+    //Removes the thumbnails of a video
+    pub fn remove_preview_source(&self, id: &str) {
+        let imp = self.imp();
+        imp.preview_sources.borrow_mut().retain(|(source_id, _)| source_id != id);
+        self.update_filmstrip();
+    }
+
+    //Shows or hides the filmstrip row of thumbnails under the seek bar
+    pub fn set_filmstrip_visible(&self, visible: bool) {
+        let imp = self.imp();
+        imp.filmstrip_enabled.set(visible);
+        imp.filmstrip.set_visible(visible);
+        self.update_filmstrip();
+    }
+
+    fn setup_preview_popover(&self) {
+        let imp = self.imp();
+
+        let preview_box = Box::new(gtk::Orientation::Horizontal, 6);
+        let popover = Popover::new();
+        popover.set_child(Some(&preview_box));
+        popover.set_autohide(false);
+        popover.set_has_arrow(true);
+        popover.set_can_target(false);
+        popover.set_position(gtk::PositionType::Top);
+        popover.set_parent(&imp.overlay.get());
+
+        //Shows thumbnails for the time under the cursor
+        let motion_controller = gtk::EventControllerMotion::new();
+        motion_controller.connect_motion(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, x, _y| {
+                this.imp().pointer_x.set(x);
+                this.show_preview(x);
+            }
+        ));
+        motion_controller.connect_leave(glib::clone!(
+            #[weak] popover,
+            move |_| {
+                popover.popdown();
+            }
+        ));
+        imp.overlay.add_controller(motion_controller);
+
+        *imp.preview_popover.borrow_mut() = Some(popover);
+        *imp.preview_box.borrow_mut() = Some(preview_box);
+    }
+
+    //Fills and positions the hover preview
+    //Input: x position of the cursor on the overlay
+    fn show_preview(&self, x: f64) {
+        let imp = self.imp();
+        let popover = match imp.preview_popover.borrow().clone() {
+            Some(p) => p,
+            None => return,
+        };
+        let preview_box = match imp.preview_box.borrow().clone() {
+            Some(b) => b,
+            None => return,
+        };
+
+        let time = match self.scale_x(x).and_then(|x| self.time_at_x(x, imp.scale.width())) {
+            Some(t) if t <= *imp.timeline_length.borrow() && *imp.timeline_length.borrow() != 0 => t,
+            _ => {
+                popover.popdown();
+                return;
+            }
+        };
+
+        while let Some(child) = preview_box.first_child() {
+            preview_box.remove(&child);
+        }
+
+        let show_colors = imp.preview_sources.borrow().len() > 1;
+        for (_, source) in imp.preview_sources.borrow().iter() {
+            let offset = if source.offset.get_time() == u64::MAX { 0 } else { source.offset.get_time() };
+            let data = source.data.lock().unwrap();
+            let thumbnail = match data.closest(time + offset) {
+                Some(t) => t,
+                None => continue,
+            };
+
+            let frame = Box::new(gtk::Orientation::Vertical, 2);
+            let picture = Picture::for_paintable(&thumbnail.to_texture());
+            picture.set_size_request(thumbnail.width, thumbnail.height);
+            frame.append(&picture);
+            if show_colors {
+                let label = Label::new(None);
+                label.set_markup(&format!("<span foreground='{}'>●</span>", source.color));
+                frame.append(&label);
+            }
+            preview_box.append(&frame);
+        }
+
+        //Nothing to preview until thumbnails have been generated
+        if preview_box.first_child().is_none() {
+            popover.popdown();
+            return;
+        }
+        preview_box.append(&Label::new(Some(&format_clock(time))));
+
+        popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, 0, 1, 1)));
+        popover.popup();
+    }
+
+    //Fills the filmstrip with thumbnails of the first preview source spread across the timeline
+    fn update_filmstrip(&self) {
+        let imp = self.imp();
+        if !imp.filmstrip_enabled.get() {
+            return;
+        }
+
+        while let Some(child) = imp.filmstrip.first_child() {
+            imp.filmstrip.remove(&child);
+        }
+
+        let width = imp.scale.width();
+        let timeline_length = *imp.timeline_length.borrow();
+        let sources = imp.preview_sources.borrow();
+        let (_, source) = match sources.first() {
+            Some(s) => s,
+            None => return,
+        };
+        if timeline_length == 0 || width <= 4 {
+            return;
+        }
+
+        let offset = if source.offset.get_time() == u64::MAX { 0 } else { source.offset.get_time() };
+        let data = source.data.lock().unwrap();
+        let frame_count = (width / 100).max(1);
+        let frame_width = width as f64 / frame_count as f64;
+        for i in 0..frame_count {
+            let picture = Picture::new();
+            picture.set_can_shrink(true);
+            if let Some(time) = self.time_at_x((i as f64 + 0.5) * frame_width, width) {
+                if let Some(thumbnail) = data.closest(time + offset) {
+                    picture.set_paintable(Some(&thumbnail.to_texture()));
+                }
+            }
+            imp.filmstrip.append(&picture);
+        }
+    }
+
+    //Gets the current position of the scale
+    //Output: time in nanoseconds relative to the start of the timeline
+    pub fn get_time(&self) -> u64 {
+        let imp = self.imp();
+        imp.scale.value().max(0.0) as u64
+    }
+
+    //Moves the scale to a time, scrolling the zoomed window to follow the position while it changes
+    //Input: time in nanoseconds relative to the start of the timeline
+    pub fn set_time(&self, time: u64) {
+        let imp = self.imp();
+        let (start, length) = self.get_visible_window();
+        let is_zoomed = imp.view_length.get() != 0;
+        if is_zoomed && time != imp.last_set_time.get() && (time < start || time > start + length) {
+            imp.view_start.set(time.saturating_sub(length / 10));
+            self.update_view();
+        }
+        imp.last_set_time.set(time);
+        imp.scale.set_value(time as f64);
+    }
+
+    //Gets the part of the timeline currently shown
+    //Output: (start, length) in nanoseconds
+    pub fn get_visible_window(&self) -> (u64, u64) {
+        let imp = self.imp();
+        let timeline_length = *imp.timeline_length.borrow();
+        let length = imp.view_length.get();
+        if length == 0 || length >= timeline_length {
+            return (0, timeline_length);
+        }
+        let start = imp.view_start.get().min(timeline_length - length);
+        (start, length)
+    }
+
+    //Zooms the timeline around a time
+    //Inputs: factor to multiply the visible length by (below 1.0 zooms in), time to keep under the cursor
+    pub fn zoom(&self, factor: f64, anchor: u64) {
+        let imp = self.imp();
+        let timeline_length = *imp.timeline_length.borrow();
+        let (start, length) = self.get_visible_window();
+        if timeline_length == 0 || length == 0 {
+            return;
+        }
+
+        let new_length = ((length as f64 * factor) as u64).max(MIN_VIEW_LENGTH_NS);
+        if new_length >= timeline_length {
+            self.reset_zoom();
+            return;
+        }
+
+        //Keeps the anchor at the same relative position in the window
+        let anchor_fraction = (anchor.saturating_sub(start) as f64 / length as f64).clamp(0.0, 1.0);
+        let new_start = anchor.saturating_sub((anchor_fraction * new_length as f64) as u64);
+        imp.view_start.set(new_start.min(timeline_length - new_length));
+        imp.view_length.set(new_length);
+        self.update_view();
+    }
+
+    //Scrolls the zoomed window
+    //Input: amount to move as a fraction of the visible length, negative moves earlier
+    pub fn pan(&self, fraction: f64) {
+        let imp = self.imp();
+        let timeline_length = *imp.timeline_length.borrow();
+        let (start, length) = self.get_visible_window();
+        if imp.view_length.get() == 0 {
+            return;
+        }
+        let new_start = (start as f64 + fraction * length as f64).max(0.0) as u64;
+        imp.view_start.set(new_start.min(timeline_length.saturating_sub(length)));
+        self.update_view();
+    }
+
+    //Shows the whole timeline again
+    pub fn reset_zoom(&self) {
+        let imp = self.imp();
+        imp.view_start.set(0);
+        imp.view_length.set(0);
+        self.update_view();
+    }
+
+    //Updates the scale range and everything placed on the timeline to the visible window
+    fn update_view(&self) {
+        let imp = self.imp();
+        let (start, length) = self.get_visible_window();
+        let adjustment = imp.scale.adjustment();
+        let value = adjustment.value().clamp(start as f64, (start + length) as f64);
+        adjustment.configure(value, start as f64, (start + length) as f64, length as f64 / 1000.0, length as f64 / 10.0, 0.0);
+        self.update_mark_positions();
+    }
+
+    fn setup_zoom(&self) {
+        let imp = self.imp();
+
+        //Ctrl + wheel zooms around the cursor, Shift + wheel scrolls the zoomed window
+        let scroll_controller = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::BOTH_AXES);
+        scroll_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        scroll_controller.connect_scroll(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::Propagation::Proceed,
+            move |controller, dx, dy| {
+                let state = controller.current_event_state();
+                if state.contains(gdk::ModifierType::CONTROL_MASK) {
+                    let imp = this.imp();
+                    let anchor = this.scale_x(imp.pointer_x.get()).and_then(|x| this.time_at_x(x, imp.scale.width())).unwrap_or_else(|| this.get_time());
+                    this.zoom(if dy < 0.0 { 0.8 } else { 1.25 }, anchor);
+                    glib::Propagation::Stop
+                } else if state.contains(gdk::ModifierType::SHIFT_MASK) {
+                    let delta = if dx != 0.0 { dx } else { dy };
+                    this.pan(delta * 0.1);
+                    glib::Propagation::Stop
+                } else {
+                    glib::Propagation::Proceed
+                }
+            }
+        ));
+        self.add_controller(scroll_controller);
+
+        //Double clicking the ruler shows the whole timeline
+        let ruler_click = gtk::GestureClick::new();
+        ruler_click.connect_pressed(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, n_press, _x, _y| {
+                if n_press == 2 {
+                    this.reset_zoom();
+                }
+            }
+        ));
+        imp.ruler.add_controller(ruler_click);
+
+        imp.ruler.set_draw_func(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, cr, width, height| {
+                this.draw_ruler(cr, width, height);
+            }
+        ));
+    }
+
+    //Draws labelled ticks for the visible window
+    fn draw_ruler(&self, cr: &gtk::cairo::Context, width: i32, height: i32) {
+        let (start, length) = self.get_visible_window();
+        if length == 0 || width <= 4 {
+            return;
+        }
+
+        //Picks the smallest spacing that leaves room for the labels
+        let pixels_per_ns = (width - 4) as f64 / length as f64;
+        let step = RULER_STEPS_NS.iter()
+            .cloned()
+            .find(|step| *step as f64 * pixels_per_ns >= 90.0)
+            .unwrap_or(RULER_STEPS_NS[RULER_STEPS_NS.len() - 1]);
+        let minor_step = step / 5;
+
+        cr.set_source_rgba(0.8, 0.8, 0.8, 0.8);
+        cr.set_line_width(1.0);
+        cr.select_font_face("Sans", gtk::cairo::FontSlant::Normal, gtk::cairo::FontWeight::Normal);
+        cr.set_font_size(10.0);
+
+        let mut tick = start - start % minor_step;
+        while tick <= start + length {
+            if let Some(x) = self.x_for_time(tick, width) {
+                let x = x.round() + 0.5;
+                if tick % step == 0 {
+                    cr.move_to(x, height as f64 - 8.0);
+                    cr.line_to(x, height as f64);
+                    let _ = cr.stroke();
+                    cr.move_to(x + 3.0, height as f64 - 8.0);
+                    let _ = cr.show_text(&format_clock(tick));
+                } else {
+                    cr.move_to(x, height as f64 - 4.0);
+                    cr.line_to(x, height as f64);
+                    let _ = cr.stroke();
+                }
+            }
+            tick += minor_step;
+        }
+    }
+
+    //Sets the split table rows the segment bands are built from
+    //Inputs: split table liststore, split table selection used to highlight the selected segment
+    pub fn set_band_source(&self, segments: &ListStore, selection: &SingleSelection) {
+        let imp = self.imp();
+        segments.connect_items_changed(glib::clone!(
+            #[weak(rename_to = bands_area)] imp.bands_area,
+            move |_, _, _, _| {
+                bands_area.queue_draw();
+            }
+        ));
+        selection.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = bands_area)] imp.bands_area,
+            move |_| {
+                bands_area.queue_draw();
+            }
+        ));
+        *imp.band_segments.borrow_mut() = Some(segments.downgrade());
+        *imp.band_selection.borrow_mut() = Some(selection.downgrade());
+        imp.bands_area.queue_draw();
+    }
+
+    //Adds a lane of segment bands for a video
+    //Inputs: Id - video_player_id, color - video player's associated color, offset - start time offset for the video
+    pub fn add_band_lane(&self, id: String, color: &str, offset: TimeEntry) {
+        let imp = self.imp();
+        self.remove_band_lane(&id);
+
+        //Redraws when the offset of the video changes so the bands stay aligned
+        let offset_handler = offset.connect_notify_local(Some("time"), glib::clone!(
+            #[weak(rename_to = bands_area)] imp.bands_area,
+            move |_, _| {
+                bands_area.queue_draw();
+            }
+        ));
+
+        imp.band_lanes.borrow_mut().push((id, BandLane {
+            offset,
+            color: color.to_string(),
+            offset_handler,
+        }));
+        self.update_bands_area_size();
+    }
+
+    //Removes the segment bands of a video
+    pub fn remove_band_lane(&self, id: &str) {
+        let imp = self.imp();
+        let removed = {
+            let mut lanes = imp.band_lanes.borrow_mut();
+            lanes.iter().position(|(lane_id, _)| lane_id == id).map(|index| lanes.remove(index))
+        };
+        if let Some((_, lane)) = removed {
+            lane.offset.disconnect(lane.offset_handler);
+            self.update_bands_area_size();
+        }
+    }
+
+    //Resizes the bands area to fit every lane and hides it when there are none
+    fn update_bands_area_size(&self) {
+        let imp = self.imp();
+        let lane_count = imp.band_lanes.borrow().len() as i32;
+        imp.bands_area.set_content_height(lane_count * BAND_LANE_HEIGHT);
+        imp.bands_area.set_visible(lane_count > 0);
+        imp.bands_area.queue_draw();
+    }
+
+    fn setup_bands_area(&self) {
+        let imp = self.imp();
+        imp.bands_area.set_draw_func(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, cr, width, _height| {
+                this.draw_bands(cr, width);
+            }
+        ));
+    }
+
+    //Draws a band for each segment between its previous split and its own split
+    fn draw_bands(&self, cr: &gtk::cairo::Context, width: i32) {
+        let imp = self.imp();
+        let segments = match imp.band_segments.borrow().as_ref().and_then(|s| s.upgrade()) {
+            Some(s) => s,
+            None => return,
+        };
+        let selected = imp.band_selection.borrow()
+            .as_ref()
+            .and_then(|s| s.upgrade())
+            .map(|s| s.selected())
+            .unwrap_or(gtk::INVALID_LIST_POSITION);
+        if *imp.timeline_length.borrow() == 0 || width <= 4 {
+            return;
+        }
+
+        cr.select_font_face("Sans", gtk::cairo::FontSlant::Normal, gtk::cairo::FontWeight::Normal);
+        cr.set_font_size(10.0);
+
+        let lane_height = BAND_LANE_HEIGHT as f64;
+        for (index, (video_player_id, lane)) in imp.band_lanes.borrow().iter().enumerate() {
+            let top = index as f64 * lane_height + 1.0;
+            let color = gdk::RGBA::parse(lane.color.as_str()).unwrap_or(gdk::RGBA::WHITE);
+            let lane_offset = if lane.offset.get_time() == u64::MAX { 0 } else { lane.offset.get_time() };
+
+            let mut previous_time: Option<u64> = None;
+            for i in 0..segments.n_items() {
+                let segment = match segments.item(i).and_downcast::<VideoSegment>() {
+                    Some(s) => s,
+                    None => continue,
+                };
+                let split_time = segment.get_time(video_player_id.as_str());
+                if split_time == u64::MAX {
+                    continue;
+                }
+                //First segment starts at the start time offset of the video
+                let start_time = previous_time.unwrap_or_else(|| segment.get_offset(video_player_id.as_str()));
+                previous_time = Some(split_time);
+
+                //Converts to timeline times and clips to the visible window
+                let (window_start, window_length) = self.get_visible_window();
+                let start = start_time.saturating_sub(lane_offset).max(window_start);
+                let end = match split_time.checked_sub(lane_offset) {
+                    Some(end) => end.min(window_start + window_length),
+                    None => continue,
+                };
+                if end <= start {
+                    continue;
+                }
+                let (x_start, x_end) = match (self.x_for_time(start, width), self.x_for_time(end, width)) {
+                    (Some(x_start), Some(x_end)) => (x_start, x_end),
+                    _ => continue,
+                };
+
+                //Alternates shading so neighbouring segments can be told apart and highlights the selected segment
+                let alpha = if i == selected { 0.9 } else if i % 2 == 0 { 0.35 } else { 0.55 };
+                cr.set_source_rgba(color.red() as f64, color.green() as f64, color.blue() as f64, alpha);
+                cr.rectangle(x_start, top, x_end - x_start, lane_height - 2.0);
+                let _ = cr.fill();
+                if i == selected {
+                    cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+                    cr.set_line_width(1.0);
+                    cr.rectangle(x_start + 0.5, top + 0.5, x_end - x_start - 1.0, lane_height - 3.0);
+                    let _ = cr.stroke();
+                }
+
+                //Labels the band with the segment name when it fits
+                let name = segment.get_name();
+                if let Ok(extents) = cr.text_extents(&name) {
+                    if extents.width() + 6.0 < x_end - x_start {
+                        cr.set_source_rgba(1.0, 1.0, 1.0, 0.95);
+                        cr.move_to(x_start + 3.0, top + lane_height / 2.0 + 3.0);
+                        let _ = cr.show_text(&name);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use gtk::prelude::*;
 use gtk::glib;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
-use gtk::{Box, TemplateChild, Button, FlowBox, ColumnView, SingleSelection, FlowBoxChild};
+use gtk::{Box, TemplateChild, Button, FlowBox, ColumnView, SingleSelection, Entry, DropDown, SpinButton, ToggleButton};
 use crate::widgets::seek_bar::seek_bar::SeekBar;
 use crate::widgets::sync::sync_manager::SyncEvent;
 use crate::widgets::video_player_widget::video_player::VideoPlayer;
@@ -12,13 +12,18 @@ use crate::widgets::split_panel::splits::VideoSegment;
 use crate::widgets::split_panel::timeentry::TimeEntry;
 use crate::widgets::split_panel::splittable::SplitTable;
 use crate::widgets::sync::sync_manager::SyncManager;
+use crate::widgets::audio_mixer::mixer_panel::MixerPanel;
+use crate::widgets::nudge_panel::nudge_panel::NudgePanel;
+use crate::error::Error;
+use crate::helpers::parse::string_to_nseconds;
+use crate::helpers::format::format_clock;
 
 use gstreamer::ClockTime;
 use std::cell::{RefCell, Cell};
 use std::rc::Rc;
 use glib::WeakRef;
 use std::time::Instant;
-use crate::helpers::ui::flowbox_children;
+use crate::helpers::ui::{flowbox_children, show_error};
 use std::time::Duration;
 use glib::timeout_add_local;
 use std::sync::{Arc, Mutex};
@@ -28,6 +33,34 @@ use gstreamer::prelude::ClockExt;
 use crate::helpers::data::get_data;
 
 
+//How far before its split time a video can stop and still count as having reached the split
+const SPLIT_REACHED_TOLERANCE: u64 = 100_000_000;
+//How often the countdown shown over the videos is updated
+const COUNTDOWN_INTERVAL_MS: u64 = 50;
+
+//How the videos are kept together during shared playback
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PlaybackMode {
+    //Videos are aligned once at the start time offsets or the selected segment
+    #[default]
+    StartAligned,
+    //Whenever the first video reaches a split every video restarts the next segment from its own split time
+    SplitAligned,
+    //Videos hold on each split until every video reaches it, then play the next segment together
+    FreezeAtSplit,
+}
+
+impl PlaybackMode {
+    //Gets the mode for a position in the playback mode drop down
+    fn from_index(index: u32) -> Self {
+        match index {
+            1 => PlaybackMode::SplitAligned,
+            2 => PlaybackMode::FreezeAtSplit,
+            _ => PlaybackMode::StartAligned,
+        }
+    }
+}
+
 mod imp {
     use super::*;
     
@@ -44,6 +77,22 @@ mod imp {
         pub next_frame_button: TemplateChild<Button>,
         #[template_child]
         pub jump_to_segment_button: TemplateChild<Button>,
+        #[template_child]
+        pub playback_mode_dropdown: TemplateChild<DropDown>,
+        #[template_child]
+        pub countdown_button: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub go_to_time_entry: TemplateChild<Entry>,
+        #[template_child]
+        pub go_to_reference_dropdown: TemplateChild<DropDown>,
+        #[template_child]
+        pub split_number_spin: TemplateChild<SpinButton>,
+        #[template_child]
+        pub go_to_split_button: TemplateChild<Button>,
+        #[template_child]
+        pub mixer_panel: TemplateChild<MixerPanel>,
+        #[template_child]
+        pub nudge_panel: TemplateChild<NudgePanel>,
 
         pub video_player_container: RefCell<Option<WeakRef<FlowBox>>>,
         pub split_table_column_view: RefCell<Option<WeakRef<ColumnView>>>,
@@ -60,6 +109,12 @@ mod imp {
         pub last_click: Rc<RefCell<Option<Instant>>>,
         pub seek_bar_update_timeout: Rc<RefCell<Option<glib::SourceId>>>,
         pub selected_segment: Rc<Cell<u32>>,
+        pub playback_mode: Cell<PlaybackMode>,
+        //Split number the videos are playing towards in split aligned and freeze at split playback
+        pub next_split: Cell<u32>,
+        //Videos holding on the next split and when each reached it
+        pub frozen_videos: RefCell<HashMap<String, Instant>>,
+        pub countdown_source: RefCell<Option<glib::SourceId>>,
     }
 
     #[gtk::glib::object_subclass]
@@ -79,37 +134,32 @@ mod imp {
     
     impl SharedSeekBar {
         pub fn setup_buttons(&self, obj: &super::SharedSeekBar) {
-            println!("------------------setting up buttons");
+            log_trace!("Setting up shared seek bar buttons");
             self.previous_frame_button.connect_clicked(glib::clone!(
                 #[strong(rename_to = video_player_container_weak)] self.video_player_container,
                 #[strong(rename_to = seek_bar)] self.seek_bar,
                 #[strong(rename_to = sync_manager_weak)] self.sync_manager,
+                #[weak(rename_to = this)] obj,
                 move |_| {
                     let sync_manager = borrow_asref_upgrade(&sync_manager_weak).ok().unwrap();
-                    sync_manager.frame_backward();
+                    if let Err(e) = sync_manager.frame_backward() {
+                        show_error(&this, &e);
+                    }
                 }
             ));
             self.play_button.connect_clicked(glib::clone!(
-                #[strong(rename_to = seek_bar)] self.seek_bar,
                 #[strong(rename_to = is_paused)] self.is_paused,
-                #[strong(rename_to = sync_manager_weak)] self.sync_manager,
                 #[strong(rename_to = last_click)] self.last_click,
                 #[strong(rename_to = debounce_duration)] self.debounce_duration,
-                #[strong(rename_to = split_table_weak)] self.split_table,
-                #[strong(rename_to = starting_segment)] self.selected_segment,
-                #[strong(rename_to = split_table_liststore_weak)] self.split_table_liststore,
+                #[weak(rename_to = this)] obj,
                 move |_| {
-                    let sync_manager = borrow_asref_upgrade(&sync_manager_weak).ok().unwrap();
-                    let split_table = borrow_asref_upgrade(&split_table_weak).ok().unwrap();
-                    let split_table_liststore = borrow_asref_upgrade(&split_table_liststore_weak).ok().unwrap();
-
                     let now = Instant::now();
                     let mut last_click = last_click.borrow_mut();
 
                     //Debounce if time since last click is less than debounce duration
                     if let Some(last_time) = *last_click {
                         if now.duration_since(last_time) < *debounce_duration.borrow() {
-                            println!("debouncing shared seek bar play button");
+                            log_trace!("Debouncing shared seek bar play button");
                             return;
                         }
                     }
@@ -118,41 +168,26 @@ mod imp {
                     *last_click = Some(now);
                     drop(last_click);
                     
-                    let state = is_paused.get();
-                    
                     //toggle being playing and pausing all videos
-                    if state == true {
-                        //Record the start time offset for each video
-                        let mut offsets: HashMap<String, u64> = HashMap::new();
-                        let offsets_row_map = split_table.get_start_time_offset_row_map();
-                        let start_segment = starting_segment.get();
-                        for (video_player_id, offset_time_entry) in offsets_row_map.borrow().iter() {
-                            let selected_segment_start_time = if start_segment == 0 { offset_time_entry.get_time() } else { 
-                                split_table_liststore.item(start_segment
-                                    .saturating_sub(1))
-                                    .and_downcast::<VideoSegment>()
-                                    .unwrap()
-                                    .get_time(video_player_id.as_str()
-                                )
-                            };
-                            offsets.insert(video_player_id.to_string(), selected_segment_start_time);
+                    if is_paused.get() {
+                        if let Err(e) = this.play_all() {
+                            show_error(&this, &e);
                         }
-                        
-                        sync_manager.play_videos(offsets);
-                    } else {
-                        sync_manager.pause_videos();
+                    } else if let Err(e) = this.pause_all() {
+                        show_error(&this, &e);
                     }
-                    //update is_paused flag
-                    is_paused.set(!is_paused.get());
                 }
             ));
             self.next_frame_button.connect_clicked(glib::clone!(
                 #[strong(rename_to = video_player_container_weak)] self.video_player_container,
                 #[strong(rename_to = seek_bar)] self.seek_bar,
                 #[strong(rename_to = sync_manager_weak)] self.sync_manager,
+                #[weak(rename_to = this)] obj,
                 move |_| {
                     let sync_manager = borrow_asref_upgrade(&sync_manager_weak).ok().unwrap();
-                    sync_manager.frame_forward();
+                    if let Err(e) = sync_manager.frame_forward() {
+                        show_error(&this, &e);
+                    }
                 }
             ));
 
@@ -187,17 +222,7 @@ mod imp {
 
                     // Gets the time of the currently selected segment i.e. the displayed time of the previous segment and stores that time in the offset_times hashmap
                     for child in flowbox_children(&video_player_container) {
-                        let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                            Some(c) => c,
-                            None => continue,
-                        };
-
-                        let content = match fb_child.child() {
-                            Some(c) => c,
-                            None => continue,
-                        };
-
-                        let video_player = match content.downcast_ref::<VideoPlayer>() {
+                        let video_player = match VideoPlayer::from_flowbox_child(&child) {
                             Some(vp) => vp,
                             None => continue,
                         };
@@ -216,7 +241,7 @@ mod imp {
                         };
                         
                         if start_time_for_syncing == u64::MAX {
-                            println!("Error: No select split time not found for video id: {video_player_id}");
+                            log_warn!("No time recorded for the selected split of video {video_player_id}");
                             break
                         }
 
@@ -227,23 +252,13 @@ mod imp {
 
                     // Check to make sure each time was found for the selected segment
                     if count != offset_times.len() {
-                        eprintln!("Error: Select split time missing");
+                        log_warn!("Not every video has a time for the selected split");
                         return;
                     }
 
                     // Seeks to the offset time found for each video
                     for child in flowbox_children(&video_player_container) {
-                        let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                            Some(c) => c,
-                            None => continue,
-                        };
-
-                        let content = match fb_child.child() {
-                            Some(c) => c,
-                            None => continue,
-                        };
-
-                        let video_player = match content.downcast_ref::<VideoPlayer>() {
+                        let video_player = match VideoPlayer::from_flowbox_child(&child) {
                             Some(vp) => vp,
                             None => continue,
                         };
@@ -253,7 +268,7 @@ mod imp {
                         let arc = match video_player.pipeline().upgrade() {
                             Some(a) => a,
                             None => {
-                                eprintln!("Shared jump to segment: Pipeline dropped");
+                                log_warn!("Shared jump to segment: Pipeline dropped");
                                 continue
                             }
                         };
@@ -261,15 +276,16 @@ mod imp {
                         let pipeline = match arc.lock() {
                             Ok(g) => g,
                             Err(_) => {
-                                eprintln!("Shared jump to segment: Failed to lock pipeline mutex");
+                                log_error!("Shared jump to segment: Failed to lock pipeline mutex");
                                 continue
                             }
                         };
                         
                         let start_time_for_syncing = offset_times[&video_player_id];
-                        println!("JUMP TO SEGMENT {video_player_id}: start_time_for_syncing (starting_segment_time): {start_time_for_syncing}");
+                        log_debug!("Jumping video {video_player_id} to the selected segment at {start_time_for_syncing}");
                         if let Err(e) = pipeline.seek_position(ClockTime::from_nseconds(start_time_for_syncing)) {
-                            eprintln!("Player {video_player_id} error setting position: {e}");
+                            drop(pipeline);
+                            video_player.report_error(&e);
                         }
                     }
 
@@ -288,10 +304,13 @@ mod imp {
                 #[weak(rename_to = is_paused)] self.is_paused,
                 #[strong(rename_to = video_player_container_weak)] self.video_player_container,
                 #[strong(rename_to = sync_manager_weak)] self.sync_manager,
+                #[weak(rename_to = this)] self.obj(),
                 move |_,_,_x,_y| {
                     let sync_manager = borrow_asref_upgrade(&sync_manager_weak).ok().unwrap();
                     if !is_paused.get() {
-                        sync_manager.pause_videos();
+                        if let Err(e) = sync_manager.pause_videos() {
+                            show_error(&this, &e);
+                        }
                     }
                     is_paused.set(true);
                     is_dragging.set(true);
@@ -302,46 +321,15 @@ mod imp {
             gesture.connect_released(glib::clone!(
                 #[weak(rename_to = is_dragging)] self.is_dragging,
                 #[weak(rename_to = seek_bar)] self.seek_bar,
-                #[strong(rename_to = sync_manager_weak)] self.sync_manager,
-                #[strong(rename_to = split_table_weak)] self.split_table,
-                #[strong(rename_to = starting_segment)] self.selected_segment,
-                #[strong(rename_to = split_table_liststore_weak)] self.split_table_liststore,
+                #[weak(rename_to = this)] self.obj(),
                 move |_,_,_x,_y| {
-                    let sync_manager = borrow_asref_upgrade(&sync_manager_weak).ok().unwrap();
-                    let split_table = borrow_asref_upgrade(&split_table_weak).ok().unwrap();
-                    let split_table_liststore = borrow_asref_upgrade(&split_table_liststore_weak).ok().unwrap();
-                    //Record the absolute positions for each video to use to perform the seek
-                    let mut clock_positions: HashMap<String, ClockTime> = HashMap::new();
-                    let start_time_offset_row_map = split_table.get_start_time_offset_row_map();
-                    let start_segment = starting_segment.get();
-                    for (video_player_id, offset) in start_time_offset_row_map.borrow().iter() {
-                        let offset_time = offset.get_time();
-                        
-                        let selected_segment_start_time = if start_segment == 0 { offset_time } else { 
-                            split_table_liststore.item(start_segment
-                                .saturating_sub(1))
-                                .and_downcast::<VideoSegment>()
-                                .unwrap()
-                                .get_time(video_player_id.as_str()
-                            )
-                        };
-
-                        let percent_position = seek_bar.get_scale().value() / 100.0;
-                        let position = (percent_position * seek_bar.get_timeline_length() as f64) as u64;
-                        
-                        //Relative position + start time offset
-                        let clock_time_position = ClockTime::from_nseconds(position + selected_segment_start_time);
-                        clock_positions.insert(video_player_id.to_string(), clock_time_position);
-                    }
-                    
-                    //Perform seek operation on all video passing in the absolute positions
-                    sync_manager.seek(clock_positions);
+                    this.seek_all(seek_bar.get_time());
                     is_dragging.set(false);
                 }
                 
             ));
             gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
-            self.seek_bar.add_controller(gesture);
+            shared_scale.add_controller(gesture);
         }
 
     }
@@ -378,33 +366,688 @@ impl SharedSeekBar {
         imp.split_table.borrow_mut().replace(Downgrade::downgrade(split_table));
         *imp.debounce_duration.borrow_mut() = Duration::from_millis(200);
         imp.selected_segment.set(0);
+        if let Some(selection_model) = split_table_column_view.model().and_downcast::<SingleSelection>() {
+            imp.seek_bar.set_band_source(split_table_liststore, &selection_model);
+        }
         imp.setup_buttons(&widget);
         imp.setup_seek_bar_control();
+        widget.setup_mark_control();
+        widget.setup_nudge_control();
+        widget.setup_go_to_control();
+        widget.setup_playback_mode_control();
         widget.set_controls(false);
         widget
     }
 
-    //Connect split table row to seek bar mark manager
-    //Input: split table row index to add
-    pub fn connect_row(&self, row_index: u32) {
+    //Plays every video from its current position, aligned by the start time offsets or the selected segment
+    pub fn play_all(&self) -> Result<(), Error> {
         let imp = self.imp();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
         let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
         let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
-        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
 
-        let row = split_table_liststore.item(row_index).and_downcast::<VideoSegment>().unwrap();
-        for child in flowbox_children(&video_player_container) {
-            let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                Some(c) => c,
+        if imp.playback_mode.get() != PlaybackMode::StartAligned {
+            self.prepare_split_playback()?;
+        }
+
+        //Record the start time offset for each video
+        let mut offsets: HashMap<String, u64> = HashMap::new();
+        let offsets_row_map = split_table.get_start_time_offset_row_map();
+        let start_segment = imp.selected_segment.get();
+        for (video_player_id, offset_time_entry) in offsets_row_map.borrow().iter() {
+            let selected_segment_start_time = if start_segment == 0 { offset_time_entry.get_time() } else { 
+                split_table_liststore.item(start_segment
+                    .saturating_sub(1))
+                    .and_downcast::<VideoSegment>()
+                    .unwrap()
+                    .get_time(video_player_id.as_str()
+                )
+            };
+            offsets.insert(video_player_id.to_string(), selected_segment_start_time);
+        }
+        
+        if let Err(e) = sync_manager.play_videos(offsets) {
+            //Stops any videos that did start so the videos stay in step
+            let _ = sync_manager.pause_videos();
+            return Err(e);
+        }
+        imp.is_paused.set(false);
+        Ok(())
+    }
+
+    //Pauses every video under shared control
+    pub fn pause_all(&self) -> Result<(), Error> {
+        let imp = self.imp();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        let result = sync_manager.pause_videos();
+        imp.is_paused.set(true);
+        self.clear_frozen();
+        result
+    }
+
+    //Checks if the videos are playing under shared control
+    pub fn is_playing(&self) -> bool {
+        let imp = self.imp();
+        imp.has_control.get() && !imp.is_paused.get()
+    }
+
+    //Gets the id and position of the first video, which the shared timeline follows
+    pub fn get_reference_position(&self) -> Option<(String, u64)> {
+        self.get_video_positions().into_iter().next()
+    }
+
+    //Connects the playback mode drop down
+    fn setup_playback_mode_control(&self) {
+        let imp = self.imp();
+        imp.playback_mode_dropdown.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |dropdown| {
+                let imp = this.imp();
+                let mode = PlaybackMode::from_index(dropdown.selected());
+                imp.playback_mode.set(mode);
+                this.clear_frozen();
+                if !imp.has_control.get() {
+                    return;
+                }
+                //Clamps for the new mode are set up the next time the videos play
+                let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+                let mut result = Ok(());
+                if !imp.is_paused.get() {
+                    result = sync_manager.pause_videos();
+                    imp.is_paused.set(true);
+                }
+                if mode == PlaybackMode::StartAligned {
+                    let split_count = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap().n_items();
+                    result = result
+                        .and_then(|_| this.clamp_to_split(split_count + 1))
+                        .and_then(|_| this.seek_all_in_place());
+                }
+                if let Err(e) = result {
+                    show_error(&this, &e);
+                }
+            }
+        ));
+
+        imp.countdown_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.update_countdown();
+            }
+        ));
+    }
+
+    //Clamps every video to the split the videos are playing towards so each stops exactly on it
+    fn prepare_split_playback(&self) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let split_count = split_table_liststore.n_items();
+
+        self.clear_frozen();
+        let next_split_after = |video_player_id: &str, position: u64| {
+            (1..=split_count).find(|split_number| {
+                let split_time = split_table_liststore.item(split_number - 1)
+                    .and_downcast::<VideoSegment>()
+                    .map_or(u64::MAX, |row| row.get_time(video_player_id));
+                split_time != u64::MAX && split_time > position
+            }).unwrap_or(split_count + 1)
+        };
+        let positions = self.get_video_positions();
+        let next_split = match imp.playback_mode.get() {
+            //Videos holding on a split wait for the ones furthest behind
+            PlaybackMode::FreezeAtSplit => positions.iter()
+                .map(|(video_player_id, position)| next_split_after(video_player_id, *position))
+                .min(),
+            //The first video decides which segment is playing, the others were aligned with it at the last split
+            _ => positions.first().map(|(video_player_id, position)| next_split_after(video_player_id, *position)),
+        }.unwrap_or(split_count + 1);
+        log_debug!("{:?} playback towards split {next_split}", imp.playback_mode.get());
+        imp.next_split.set(next_split);
+        self.clamp_to_split(next_split)?;
+        self.seek_all_in_place()
+    }
+
+    //Gets the id and position of every video that has a position, in display order
+    fn get_video_positions(&self) -> Vec<(String, u64)> {
+        let video_player_container = match borrow_asref_upgrade(&self.imp().video_player_container) {
+            Ok(container) => container,
+            Err(_) => return Vec::new(),
+        };
+        flowbox_children(&video_player_container)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .filter_map(|video_player| {
+                let pipeline = video_player.pipeline().upgrade()?;
+                let position = pipeline.lock().unwrap().get_position()?;
+                Some((video_player.get_id(), position.nseconds()))
+            })
+            .collect()
+    }
+
+    //Sets the end clamp of every video to its time for a split
+    //Input: split number starting at 1, past the last split to play to the last split or the end of the video
+    fn clamp_to_split(&self, split_number: u32) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+        let split_count = split_table_liststore.n_items();
+        let row = if split_number >= 1 && split_number <= split_count {
+            split_table_liststore.item(split_number - 1).and_downcast::<VideoSegment>()
+        } else {
+            None
+        };
+
+        let mut failures = Vec::new();
+        for video_player in flowbox_children(&video_player_container).filter_map(|child| VideoPlayer::from_flowbox_child(&child)) {
+            let video_player_id = video_player.get_id();
+            let pipeline = match video_player.pipeline().upgrade() {
+                Some(p) => p,
                 None => continue,
             };
+            let pipeline = pipeline.lock().unwrap();
+            //Same end as taking shared control when there is no time for the split
+            let end = row.as_ref()
+                .map(|row| row.get_time(video_player_id.as_str()))
+                .filter(|time| *time != u64::MAX)
+                .or_else(|| split_table.get_previous_time(video_player_id.as_str(), split_count))
+                .or_else(|| pipeline.get_length());
+            let result = match end {
+                Some(end) => pipeline.set_clamp(None, Some(ClockTime::from_nseconds(end))),
+                None => Err(Error::Pipeline("Could not get video length to clamp it".to_string())),
+            };
+            if let Err(e) = result {
+                failures.push(format!("{video_player_id}: {}", e.message()));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Sync(format!("Failed to clamp to split {split_number} {}", failures.join("; "))))
+        }
+    }
+
+    //Seeks every video to where it already is so new clamps take effect
+    fn seek_all_in_place(&self) -> Result<(), Error> {
+        let sync_manager = borrow_asref_upgrade(&self.imp().sync_manager).ok().unwrap();
+        let positions = self.get_video_positions().into_iter()
+            .map(|(video_player_id, position)| (video_player_id, ClockTime::from_nseconds(position)))
+            .collect();
+        sync_manager.seek(positions)
+    }
+
+    //Reacts to a video stopping at its end clamp during shared playback
+    //Input: id of the video that stopped
+    pub fn handle_clamp_end_reached(&self, video_player_id: &str) {
+        let imp = self.imp();
+        if !imp.has_control.get() || imp.is_paused.get() {
+            return;
+        }
+        let result = match imp.playback_mode.get() {
+            PlaybackMode::StartAligned => Ok(()),
+            PlaybackMode::SplitAligned => self.align_at_split(video_player_id),
+            PlaybackMode::FreezeAtSplit => self.freeze_at_split(video_player_id),
+        };
+        if let Err(e) = result {
+            show_error(self, &e);
+        }
+    }
 
-            let content = match fb_child.child() {
-                Some(c) => c,
+    //Checks if a video stopped on its time for a split rather than somewhere before it
+    fn has_reached_split(&self, video_player_id: &str, split_number: u32) -> bool {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let split_time = match split_table_liststore.item(split_number.saturating_sub(1)).and_downcast::<VideoSegment>() {
+            Some(row) => row.get_time(video_player_id),
+            None => return false,
+        };
+        let position = self.find_video_player(video_player_id)
+            .and_then(|video_player| video_player.pipeline().upgrade())
+            .and_then(|pipeline| {
+                let position = pipeline.lock().unwrap().get_position();
+                position
+            });
+        match position {
+            Some(position) => split_time != u64::MAX && position.nseconds() + SPLIT_REACHED_TOLERANCE >= split_time,
+            None => false,
+        }
+    }
+
+    //Restarts every video together from its own time for the split the first video reached
+    fn align_at_split(&self, video_player_id: &str) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_count = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap().n_items();
+        let split_number = imp.next_split.get();
+
+        //Past the last split the videos just play to their end
+        if split_number > split_count {
+            return Ok(());
+        }
+        //Videos that were already stopped by the previous alignment report their clamp late
+        if !self.has_reached_split(video_player_id, split_number) {
+            return Ok(());
+        }
+        log_debug!("Video {video_player_id} reached split {split_number}, aligning every video");
+        self.restart_from_split(split_number)
+    }
+
+    //Holds a video on the split it reached until every video with a time for the split has reached it
+    fn freeze_at_split(&self, video_player_id: &str) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_count = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap().n_items();
+        let split_number = imp.next_split.get();
+
+        if split_number > split_count {
+            return Ok(());
+        }
+        if !self.has_reached_split(video_player_id, split_number) {
+            return Ok(());
+        }
+        imp.frozen_videos.borrow_mut().entry(video_player_id.to_string()).or_insert_with(Instant::now);
+
+        let waiting: Vec<String> = self.get_video_ids_with_split_time(split_number).into_iter()
+            .filter(|id| !imp.frozen_videos.borrow().contains_key(id))
+            .collect();
+        if !waiting.is_empty() {
+            log_debug!("Video {video_player_id} holding on split {split_number}, waiting for {}", waiting.join(", "));
+            self.start_countdown();
+            return Ok(());
+        }
+        log_debug!("Every video reached split {split_number}, continuing together");
+        self.clear_frozen();
+        self.restart_from_split(split_number)
+    }
+
+    //Pauses every video and plays them together again from their own time for a split
+    fn restart_from_split(&self, split_number: u32) -> Result<(), Error> {
+        let imp = self.imp();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        sync_manager.pause_videos()?;
+        imp.is_paused.set(true);
+        //The next clamp is set before seeking so the videos can show the split frame itself
+        self.clamp_to_split(split_number + 1)?;
+        self.go_to_split(split_number)?;
+        self.play_all()
+    }
+
+    //Gets the ids of the videos that have a time recorded for a split
+    fn get_video_ids_with_split_time(&self, split_number: u32) -> Vec<String> {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+        let row = match split_table_liststore.item(split_number.saturating_sub(1)).and_downcast::<VideoSegment>() {
+            Some(row) => row,
+            None => return Vec::new(),
+        };
+        flowbox_children(&video_player_container)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .map(|video_player| video_player.get_id())
+            .filter(|video_player_id| row.get_time(video_player_id.as_str()) != u64::MAX)
+            .collect()
+    }
+
+    //Starts updating the countdown over the videos while some are holding on a split
+    fn start_countdown(&self) {
+        let imp = self.imp();
+        self.update_countdown();
+        if imp.countdown_source.borrow().is_some() {
+            return;
+        }
+        let source = timeout_add_local(Duration::from_millis(COUNTDOWN_INTERVAL_MS), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                this.update_countdown();
+                glib::ControlFlow::Continue
+            }
+        ));
+        imp.countdown_source.replace(Some(source));
+    }
+
+    //Shows how far ahead each holding video is, and how long until each other video reaches the split
+    fn update_countdown(&self) {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+        let split_number = imp.next_split.get();
+        let row = split_table_liststore.item(split_number.saturating_sub(1)).and_downcast::<VideoSegment>();
+        let frozen_videos = imp.frozen_videos.borrow();
+        let show = imp.countdown_button.is_active() && !frozen_videos.is_empty();
+        let now = Instant::now();
+        //Time lost by a video still playing counts from when the first video reached the split
+        let first_frozen = frozen_videos.values().min().copied();
+
+        for video_player in flowbox_children(&video_player_container).filter_map(|child| VideoPlayer::from_flowbox_child(&child)) {
+            if !show {
+                video_player.set_overlay_text(None);
+                continue;
+            }
+            let video_player_id = video_player.get_id();
+            if let Some(frozen_at) = frozen_videos.get(&video_player_id) {
+                let ahead = now.duration_since(*frozen_at).as_nanos() as u64;
+                video_player.set_overlay_text(Some(&format!("Split {split_number} reached\n+{} ahead", format_clock(ahead))));
+                continue;
+            }
+            let split_time = row.as_ref()
+                .map_or(u64::MAX, |row| row.get_time(video_player_id.as_str()));
+            let pipeline = match video_player.pipeline().upgrade() {
+                Some(p) => p,
                 None => continue,
             };
+            //A pipeline busy seeking keeps its previous text until the next update
+            let position = match pipeline.try_lock() {
+                Ok(pipeline) => pipeline.get_position(),
+                Err(_) => continue,
+            };
+            let text = match (position, first_frozen) {
+                (Some(position), Some(first_frozen)) if split_time != u64::MAX => {
+                    let remaining = split_time.saturating_sub(position.nseconds());
+                    let behind = now.duration_since(first_frozen).as_nanos() as u64;
+                    Some(format!("Split {split_number} in {}\n-{} behind", format_clock(remaining), format_clock(behind)))
+                }
+                _ => None,
+            };
+            video_player.set_overlay_text(text.as_deref());
+        }
+    }
+
+    //Forgets which videos are holding on a split and hides the countdown
+    fn clear_frozen(&self) {
+        let imp = self.imp();
+        imp.frozen_videos.borrow_mut().clear();
+        if let Some(source) = imp.countdown_source.borrow_mut().take() {
+            source.remove();
+        }
+        self.update_countdown();
+    }
+
+    //Seeks every video to a position on the shared timeline
+    //Input: time in nanoseconds relative to the start of the selected segment
+    pub fn seek_all(&self, position: u64) {
+        let imp = self.imp();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+
+        //Record the absolute positions for each video to use to perform the seek
+        let mut clock_positions: HashMap<String, ClockTime> = HashMap::new();
+        let start_time_offset_row_map = split_table.get_start_time_offset_row_map();
+        let start_segment = imp.selected_segment.get();
+        for (video_player_id, offset) in start_time_offset_row_map.borrow().iter() {
+            let offset_time = offset.get_time();
+            
+            let selected_segment_start_time = if start_segment == 0 { offset_time } else { 
+                split_table_liststore.item(start_segment
+                    .saturating_sub(1))
+                    .and_downcast::<VideoSegment>()
+                    .unwrap()
+                    .get_time(video_player_id.as_str()
+                )
+            };
+            
+            //Relative position + start time offset
+            let clock_time_position = ClockTime::from_nseconds(position + selected_segment_start_time);
+            clock_positions.insert(video_player_id.to_string(), clock_time_position);
+        }
+        
+        //Perform seek operation on all video passing in the absolute positions
+        if let Err(e) = sync_manager.seek(clock_positions) {
+            show_error(self, &e);
+        }
+    }
+
+    //Connects the typed time entry and the go to split button
+    fn setup_go_to_control(&self) {
+        let imp = self.imp();
+
+        imp.go_to_time_entry.connect_activate(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |entry| {
+                let from_segment = this.imp().go_to_reference_dropdown.selected() == 1;
+                let result = string_to_nseconds(&entry.text().to_string())
+                    .and_then(|time| this.go_to_time(time, from_segment));
+                if let Err(e) = result {
+                    show_error(&this, &e);
+                }
+            }
+        ));
+
+        imp.go_to_split_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                let split_number = this.imp().split_number_spin.value_as_int().max(1) as u32;
+                if let Err(e) = this.go_to_split(split_number) {
+                    show_error(&this, &e);
+                }
+            }
+        ));
+    }
+
+    //Gets where a segment starts in every video
+    //Input: segment index, 0 for the start time offsets
+    //Output: start time of each video, an error naming the videos without a time
+    fn get_segment_start_times(&self, segment: u32) -> Result<HashMap<String, u64>, Error> {
+        let imp = self.imp();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let previous_split = if segment == 0 {
+            None
+        } else {
+            let row = split_table_liststore.item(segment - 1)
+                .and_downcast::<VideoSegment>()
+                .ok_or_else(|| Error::Sync(format!("Split {segment} does not exist")))?;
+            Some(row)
+        };
+
+        let mut start_times = HashMap::new();
+        let mut missing = Vec::new();
+        for (video_player_id, offset) in split_table.get_start_time_offset_row_map().borrow().iter() {
+            let start_time = match &previous_split {
+                Some(row) => row.get_time(video_player_id.as_str()),
+                None => offset.get_time(),
+            };
+            if start_time == u64::MAX {
+                missing.push(video_player_id.clone());
+            } else {
+                start_times.insert(video_player_id.clone(), start_time);
+            }
+        }
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(Error::Sync(format!("No time recorded for split {segment} of video {}", missing.join(", "))));
+        }
+        Ok(start_times)
+    }
+
+    //Moves every video to a typed time
+    //Input: time in nanoseconds, true to count from the start of the selected segment instead of the start time offsets
+    pub fn go_to_time(&self, time: u64, from_segment: bool) -> Result<(), Error> {
+        let segment = if from_segment { self.imp().selected_segment.get() } else { 0 };
+        let positions = self.get_segment_start_times(segment)?.into_iter()
+            .map(|(video_player_id, start_time)| (video_player_id, ClockTime::from_nseconds(start_time.saturating_add(time))))
+            .collect();
+        self.seek_all_to_positions(positions)
+    }
+
+    //Moves every video to its own time for a split
+    //Input: split number starting at 1
+    pub fn go_to_split(&self, split_number: u32) -> Result<(), Error> {
+        let split_count = borrow_asref_upgrade(&self.imp().split_table_liststore).ok().unwrap().n_items();
+        if split_number == 0 || split_number > split_count {
+            return Err(Error::Sync(format!("Split {split_number} does not exist, there are {split_count} splits")));
+        }
+        let positions = self.get_segment_start_times(split_number)?.into_iter()
+            .map(|(video_player_id, split_time)| (video_player_id, ClockTime::from_nseconds(split_time)))
+            .collect();
+        self.seek_all_to_positions(positions)
+    }
+
+    //Pauses and seeks every video to its own position, then moves the shared seek bar to match
+    //Input: HashMap<video_player_id, position in the video>
+    fn seek_all_to_positions(&self, positions: HashMap<String, ClockTime>) -> Result<(), Error> {
+        let imp = self.imp();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        if !imp.is_paused.get() {
+            sync_manager.pause_videos()?;
+            imp.is_paused.set(true);
+        }
+
+        //The shared seek bar counts from the selected segment, using the first video that has a time for it
+        let selected_start_times = self.get_segment_start_times(imp.selected_segment.get()).unwrap_or_default();
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+        let timeline_time = flowbox_children(&video_player_container)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .find_map(|video_player| {
+                let video_player_id = video_player.get_id();
+                let position = positions.get(&video_player_id)?;
+                let start_time = selected_start_times.get(&video_player_id)?;
+                Some(position.nseconds().saturating_sub(*start_time))
+            });
+
+        sync_manager.seek(positions)?;
+        if let Some(timeline_time) = timeline_time {
+            imp.seek_bar.set_time(timeline_time);
+        }
+        Ok(())
+    }
+
+    //Connects split mark clicks and drags on the shared seek bar
+    fn setup_mark_control(&self) {
+        let imp = self.imp();
+
+        //Clicking a mark moves every video to that split
+        imp.seek_bar.connect_local("mark-clicked", false, glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] None,
+            move |values| {
+                let imp = this.imp();
+                if !imp.has_control.get() {
+                    return None;
+                }
+                let timeline_time = values[4].get::<u64>().unwrap();
+                if !imp.is_paused.get() {
+                    let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+                    if let Err(e) = sync_manager.pause_videos() {
+                        show_error(&this, &e);
+                    }
+                    imp.is_paused.set(true);
+                }
+                imp.seek_bar.set_time(timeline_time);
+                this.seek_all(timeline_time);
+                None
+            }
+        ));
+
+        //Dragging a mark moves the split in the split table
+        imp.seek_bar.connect_local("mark-dragged", false, glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] None,
+            move |values| {
+                let video_player_id = values[1].get::<String>().unwrap();
+                let segment_id = values[2].get::<String>().unwrap();
+                let time = values[3].get::<u64>().unwrap();
+                let split_table = borrow_asref_upgrade(&this.imp().split_table).ok().unwrap();
+                if let Err(e) = split_table.set_split_time(video_player_id.as_str(), segment_id.as_str(), time) {
+                    show_error(&this, &e);
+                }
+                None
+            }
+        ));
+    }
+
+    //Connects the nudge panel buttons to the start time offsets
+    fn setup_nudge_control(&self) {
+        let imp = self.imp();
+
+        imp.nudge_panel.connect_local("nudge-time", false, glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] None,
+            move |values| {
+                let video_player_id = values[1].get::<String>().unwrap();
+                let delta = values[2].get::<i64>().unwrap();
+                let split_table = borrow_asref_upgrade(&this.imp().split_table).ok().unwrap();
+                let offset = split_table.get_offset_time_entry(video_player_id.as_str()).get_time();
+                let new_offset = (offset as i64 + delta).max(0) as u64;
+                if let Err(e) = this.move_start_time_offset(video_player_id.as_str(), new_offset) {
+                    show_error(&this, &e);
+                }
+                None
+            }
+        ));
+
+        imp.nudge_panel.connect_local("nudge-frames", false, glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] None,
+            move |values| {
+                let video_player_id = values[1].get::<String>().unwrap();
+                let frames = values[2].get::<i64>().unwrap();
+                let split_table = borrow_asref_upgrade(&this.imp().split_table).ok().unwrap();
+                let offset = split_table.get_offset_time_entry(video_player_id.as_str()).get_time();
+                let pipeline = this.find_video_player(video_player_id.as_str())
+                    .and_then(|video_player| video_player.pipeline().upgrade());
+                let new_offset = match &pipeline {
+                    Some(pipeline) => pipeline.lock().unwrap().offset_by_frames(offset, frames),
+                    None => None,
+                };
+                let result = match new_offset {
+                    Some(new_offset) => this.move_start_time_offset(video_player_id.as_str(), new_offset),
+                    None => Err(Error::Sync(format!("Frame rate of video {video_player_id} is not known yet"))),
+                };
+                if let Err(e) = result {
+                    show_error(&this, &e);
+                }
+                None
+            }
+        ));
+    }
 
-            let video_player = match content.downcast_ref::<VideoPlayer>() {
+    //Changes a video's start time offset without leaving shared control
+    //The video is seeked by the same amount so it lines up with the others at the new offset
+    //Input: video id, new start time offset in nanoseconds
+    fn move_start_time_offset(&self, video_player_id: &str, new_offset: u64) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        let offset_entry = split_table.get_offset_time_entry(video_player_id);
+        let old_offset = offset_entry.get_time();
+        split_table.set_start_time_offset(video_player_id, new_offset)?;
+        //The split table may have snapped the offset to a frame
+        let new_offset = offset_entry.get_time();
+        if new_offset == old_offset {
+            return Ok(());
+        }
+
+        let video_player = self.find_video_player(video_player_id)
+            .ok_or_else(|| Error::Sync(format!("Video {video_player_id} not found")))?;
+        let pipeline_arc = video_player.pipeline().upgrade()
+            .ok_or_else(|| Error::Sync(format!("Video {video_player_id} was closed")))?;
+        let pipeline = pipeline_arc.lock().unwrap();
+        pipeline.set_clamp(Some(ClockTime::from_nseconds(new_offset)), None)?;
+        let position = pipeline.get_position()
+            .ok_or_else(|| Error::Sync(format!("Video {video_player_id} has no position")))?;
+        drop(pipeline);
+        let target = (position.nseconds() as i64 + new_offset as i64 - old_offset as i64).max(0) as u64;
+        log_debug!("Moving start time offset of video {video_player_id} from {old_offset} to {new_offset}");
+        sync_manager.seek_video(video_player_id, ClockTime::from_nseconds(target))
+    }
+
+    //Gets a video player in the container by id
+    fn find_video_player(&self, video_player_id: &str) -> Option<VideoPlayer> {
+        let video_player_container = borrow_asref_upgrade(&self.imp().video_player_container).ok()?;
+        flowbox_children(&video_player_container)
+            .filter_map(|child| VideoPlayer::from_flowbox_child(&child))
+            .find(|video_player| video_player.get_id() == video_player_id)
+    }
+
+    //Connect split table row to seek bar mark manager
+    //Input: split table row index to add
+    pub fn connect_row(&self, row_index: u32) {
+        let imp = self.imp();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+
+        let row = split_table_liststore.item(row_index).and_downcast::<VideoSegment>().unwrap();
+        for child in flowbox_children(&video_player_container) {
+            let video_player = match VideoPlayer::from_flowbox_child(&child) {
                 Some(vp) => vp,
                 None => continue,
             };
@@ -430,15 +1073,15 @@ impl SharedSeekBar {
             let test_time = time.get_time();
             if test_time != u64::MAX {
                 let c_time = ClockTime::from_nseconds(test_time);
-                println!("{c_time}");
+                log_trace!("Mark time: {c_time}");
             }
             let offset_time_test = selected_segment_time_entry.get_time();
             if offset_time_test != u64::MAX {
                 let of_time = ClockTime::from_nseconds(offset_time_test);
-                println!("{of_time}");
+                log_trace!("Selected segment time: {of_time}");
             }
 
-            imp.seek_bar.add_mark(format!("video-{video_player_id}, segment-{segment_id}"), time, color.as_str(), selected_segment_time_entry);
+            imp.seek_bar.add_mark(format!("video-{video_player_id}, segment-{segment_id}"), time, color.as_str(), selected_segment_time_entry, video_player_id.as_str(), &row);
         }
     }
 
@@ -466,17 +1109,39 @@ impl SharedSeekBar {
                 )
             };
 
-            imp.seek_bar.add_mark(format!("video-{video_player_id}, segment-{segment_id}"), time, color, selected_segment_time_entry);
+            imp.seek_bar.add_mark(format!("video-{video_player_id}, segment-{segment_id}"), time, color, selected_segment_time_entry, video_player_id, &row);
         }
+        self.connect_band_lane(video_player_id, color);
+    }
+
+    //Adds a lane of segment bands for a video aligned by the selected segment
+    //Inputs: video_player_id: Id of the video, Color: color assigned to the video player
+    fn connect_band_lane(&self, video_player_id: &str, color: &str) {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let selected_segment = imp.selected_segment.get();
+
+        let selected_segment_time_entry = if selected_segment == 0 { split_table.get_offset_time_entry(video_player_id) } else {
+            split_table_liststore.item(selected_segment.saturating_sub(1))
+                .and_downcast::<VideoSegment>()
+                .unwrap()
+                .get_time_entry_copy(video_player_id)
+        };
+        imp.seek_bar.add_band_lane(video_player_id.to_string(), color, selected_segment_time_entry);
     }
 
-    //Updates the seek bar max value
-    //i.e. Scale: 0.0 - 100.0 -> 0 ns - Video Duration in ns
+    //Updates the seek bar length in nanoseconds to reach the furthest split mark
     pub fn update_timeline_length(&self) {
         let imp = self.imp();
         imp.seek_bar.update_timeline_length();
     }
 
+    //Gets the length of the shared seek bar, counted from the start time offsets
+    pub fn get_timeline_length(&self) -> u64 {
+        self.imp().seek_bar.get_timeline_length()
+    }
+
     //Toggles between individual and synced video control
     pub fn toggle_has_control(&self) {
         let imp = self.imp();
@@ -503,9 +1168,17 @@ impl SharedSeekBar {
         }
         imp.seek_bar.set_sensitive(status);
         imp.jump_to_segment_button.set_sensitive(status);
+        imp.playback_mode_dropdown.set_sensitive(status);
+        imp.countdown_button.set_sensitive(status);
+        imp.go_to_time_entry.set_sensitive(status);
+        imp.go_to_reference_dropdown.set_sensitive(status);
+        imp.split_number_spin.set_sensitive(status);
+        imp.go_to_split_button.set_sensitive(status);
         imp.next_frame_button.set_sensitive(status);
         imp.play_button.set_sensitive(status);
         imp.previous_frame_button.set_sensitive(status);
+        imp.mixer_panel.set_visible(status);
+        imp.nudge_panel.set_visible(status);
     }
 
     //Remove marks from shared seek bar
@@ -522,6 +1195,7 @@ impl SharedSeekBar {
             //Remove mark
             imp.seek_bar.remove_mark(&format!("video-{video_player_id}, segment-{segment_id}"));
         }
+        imp.seek_bar.remove_band_lane(video_player_id);
 
         //Updates length of seek bar to reflect new mark state
         self.update_timeline_length();
@@ -537,25 +1211,16 @@ impl SharedSeekBar {
         
         //Loops through each video player
         for child in flowbox_children(&video_player_container) {
-            let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                Some(c) => c,
-                None => continue,
-            };
-
-            let content = match fb_child.child() {
-                Some(c) => c,
-                None => continue,
-            };
-
-            let video_player = match content.downcast_ref::<VideoPlayer>() {
+            let video_player = match VideoPlayer::from_flowbox_child(&child) {
                 Some(vp) => vp,
                 None => continue,
             };
+            video_player.set_shared_control(true);
 
             let arc = match video_player.pipeline().upgrade() {
                 Some(a) => a,
                 None => {
-                    eprintln!("Shared jump to segment: Pipeline dropped");
+                    log_warn!("Shared jump to segment: Pipeline dropped");
                     continue
                 }
             };
@@ -563,11 +1228,15 @@ impl SharedSeekBar {
             let mut pipeline = match arc.lock() {
                 Ok(g) => g,
                 Err(_) => {
-                    eprintln!("Shared jump to segment: Failed to lock pipeline mutex");
+                    log_error!("Shared jump to segment: Failed to lock pipeline mutex");
                     continue
                 }
             };
-            pipeline.pause_video();
+            if let Err(e) = pipeline.pause_video() {
+                drop(pipeline);
+                video_player.report_error(&e);
+                continue;
+            }
 
             //Find start and end times to clamp video
             //Start: start time offset
@@ -575,7 +1244,14 @@ impl SharedSeekBar {
             let video_player_id = video_player.get_id().to_string();
             let offset = split_table.get_offset_time_entry(video_player_id.as_str());
             let start_time = gstreamer::ClockTime::from_nseconds(offset.get_time());
-            let mut end_time = pipeline.get_length().unwrap();
+            let mut end_time = match pipeline.get_length() {
+                Some(length) => length,
+                None => {
+                    drop(pipeline);
+                    video_player.report_error(&Error::Pipeline("Could not get video length to clamp it".to_string()));
+                    continue;
+                }
+            };
             if split_table_liststore.n_items() > 0 {
                 match split_table.get_previous_time(video_player_id.as_str(), split_table_liststore.n_items()) {
                     Some(time) => { end_time = time },
@@ -593,12 +1269,12 @@ impl SharedSeekBar {
                 }
             }
 
-            //Applies the clamp to the pipeline
-            let _ = pipeline.apply_clamp(start_time, ClockTime::from_nseconds(end_time));
-
-            //Seeks to the start time offset
-            if let Err(e) = pipeline.seek_position(start_time) {
-                eprintln!("Player {video_player_id} error setting position: {e}");
+            //Applies the clamp to the pipeline and seeks to the start time offset
+            let result = pipeline.apply_clamp(start_time, ClockTime::from_nseconds(end_time))
+                .and_then(|_| pipeline.seek_position(start_time));
+            drop(pipeline);
+            if let Err(e) = result {
+                video_player.report_error(&e);
             }
 
             //Disable individual user control while in shared control
@@ -606,7 +1282,7 @@ impl SharedSeekBar {
         }
 
         //Update seek bar value to match starting position of the videos
-        imp.seek_bar.get_scale().set_value(0.0);
+        imp.seek_bar.set_time(0);
 
         imp.is_paused.set(true);
         imp.has_control.set(true);
@@ -618,20 +1294,11 @@ impl SharedSeekBar {
         let imp = self.imp();
         let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
         let sync_manager = borrow_asref_upgrade(&imp.sync_manager).ok().unwrap();
+        self.clear_frozen();
         
         //Loops through each of the video players
         for child in flowbox_children(&video_player_container) {
-            let fb_child = match child.downcast_ref::<FlowBoxChild>() {
-                Some(c) => c,
-                None => continue,
-            };
-
-            let content = match fb_child.child() {
-                Some(c) => c,
-                None => continue,
-            };
-
-            let video_player = match content.downcast_ref::<VideoPlayer>() {
+            let video_player = match VideoPlayer::from_flowbox_child(&child) {
                 Some(vp) => vp,
                 None => continue,
             };
@@ -639,7 +1306,7 @@ impl SharedSeekBar {
             let arc = match video_player.pipeline().upgrade() {
                 Some(a) => a,
                 None => {
-                    eprintln!("Shared jump to segment: Pipeline dropped");
+                    log_warn!("Shared jump to segment: Pipeline dropped");
                     continue
                 }
             };
@@ -647,14 +1314,17 @@ impl SharedSeekBar {
             let mut pipeline = match arc.lock() {
                 Ok(g) => g,
                 Err(_) => {
-                    eprintln!("Shared jump to segment: Failed to lock pipeline mutex");
+                    log_error!("Shared jump to segment: Failed to lock pipeline mutex");
                     continue
                 }
             };
             //Pause and reset clamps
-            pipeline.pause_video();
-            let _ = pipeline.reset_clamps();
+            let result = pipeline.pause_video()
+                .and_then(|_| pipeline.reset_clamps());
             drop(pipeline);
+            if let Err(e) = result {
+                video_player.report_error(&e);
+            }
 
             //Enables individual user controls for video player
             video_player.set_controls(true);
@@ -662,7 +1332,14 @@ impl SharedSeekBar {
 
         //Unsync the pipelines clocks
         if let Err(e) = sync_manager.unsync_clocks() {
-            eprintln!("Error unsyncing clocks {e}");  
+            show_error(self, &e);
+        }
+
+        //Sources can be switched again once the clocks are the videos' own
+        for child in flowbox_children(&video_player_container) {
+            if let Some(video_player) = VideoPlayer::from_flowbox_child(&child) {
+                video_player.set_shared_control(false);
+            }
         }
 
         imp.is_paused.set(true);
@@ -689,11 +1366,9 @@ impl SharedSeekBar {
                     if current_time >= base_time {
                         //Calculate absolute scale position
                         let media_time = current_time - base_time + scale_position;
-                        let position = media_time.nseconds() as f64;
-                        let new_scale_position = (position / seek_bar.get_timeline_length() as f64) * 100.0;
                         
                         //Set scale position
-                        seek_bar.get_scale().set_value(new_scale_position);
+                        seek_bar.set_time(media_time.nseconds());
 
                         //Stop updating when video reaches the end
                         if media_time >= timeline_length {
@@ -719,23 +1394,28 @@ impl SharedSeekBar {
     pub fn handle_sync_event(&self, event: SyncEvent) {
         match event {
             SyncEvent::SyncEnabled { base_time } => {
-                println!("SyncEnabled: Videos synced base_time {base_time}");
+                log_debug!("SyncEnabled: Videos synced base_time {base_time}");
             },
             SyncEvent::SyncDisabled => {
-                println!("SyncDisabled: Video unsynced");
+                log_debug!("SyncDisabled: Video unsynced");
             },
             SyncEvent::PlaybackStarted { base_time, scale_position} => {
                 //Start scale position updating
-                println!("PlaybackStarted: base_time = {base_time}, scale_position = {scale_position}");
+                log_debug!("PlaybackStarted: base_time = {base_time}, scale_position = {scale_position}");
                 self.start_progress(base_time, scale_position);
             },
             SyncEvent::PlaybackPaused => {
                 //Stop scale position updating
-                println!("PlaybackPaused");
+                log_debug!("PlaybackPaused");
                 self.stop_progress();
             },
             SyncEvent::Seeked => {
-                println!("Seeked");
+                log_debug!("Seeked");
+            },
+            SyncEvent::DriftReport { stats } => {
+                for (video_player_id, stats) in stats.iter() {
+                    log_trace!("Drift of video {video_player_id}: {} ns, {} ns from the others", stats.drift, stats.relative_drift);
+                }
             },
         }
     }
@@ -744,27 +1424,99 @@ impl SharedSeekBar {
         let imp = self.imp();
 
         let previous_selected_segment = imp.selected_segment.get();
-        println!("previous selected segment: {previous_selected_segment}");
+        log_trace!("previous selected segment: {previous_selected_segment}");
         imp.selected_segment.set(segment_index);
 
         let new_selected_segment = imp.selected_segment.get();
-        println!("new selected segment: {new_selected_segment}");
+        log_debug!("Selected segment: {new_selected_segment}");
 
-        println!("remove all marks");
+        log_trace!("Removing all marks");
         imp.seek_bar.reset_all_marks();
 
-        println!("readding marks");
+        log_trace!("Adding marks for the selected segment");
         let row_count = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap().n_items();
         for i in 0..row_count {
-            println!("connecting row index: {i}");
+            log_trace!("Connecting row index: {i}");
             self.connect_row(i);
         }
         imp.seek_bar.update_timeline_length();
+
+        //Realigns segment bands, waveforms and previews to the new selected segment
+        let video_player_container = borrow_asref_upgrade(&imp.video_player_container).ok().unwrap();
+        for child in flowbox_children(&video_player_container) {
+            if let Some(video_player) = VideoPlayer::from_flowbox_child(&child) {
+                self.connect_band_lane(video_player.get_id().as_str(), video_player.get_color().as_str());
+                self.connect_video_previews(&video_player);
+            }
+        }
     }
 
     pub fn get_selected_segment(&self) -> u32 {
         let imp = self.imp();
         imp.selected_segment.get()
     }
-}
 
+    //Adds a video player to the sync view mixer
+    pub fn add_mixer_channel(&self, video_player: &VideoPlayer, color: &str) {
+        let imp = self.imp();
+        imp.mixer_panel.add_channel(video_player, color);
+    }
+
+    //Removes a video player from the sync view mixer
+    pub fn remove_mixer_channel(&self, video_player_id: &str) {
+        let imp = self.imp();
+        imp.mixer_panel.remove_channel(video_player_id);
+    }
+
+    //Adds a video player to the sync view start time offset nudge controls
+    pub fn add_nudge_channel(&self, video_player_id: &str, color: &str) {
+        let imp = self.imp();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let offset_entry = split_table.get_offset_time_entry(video_player_id);
+        imp.nudge_panel.add_channel(video_player_id, color, &offset_entry);
+    }
+
+    //Removes a video player from the sync view start time offset nudge controls
+    pub fn remove_nudge_channel(&self, video_player_id: &str) {
+        let imp = self.imp();
+        imp.nudge_panel.remove_channel(video_player_id);
+    }
+
+    //Solos a video player muting all others
+    pub fn solo(&self, video_player_id: &str) {
+        let imp = self.imp();
+        imp.mixer_panel.solo(video_player_id);
+    }
+
+    //Shows a video player's audio waveform and hover thumbnails on the shared seek bar aligned by the selected segment
+    //Input: video player with a loaded file
+    pub fn connect_video_previews(&self, video_player: &VideoPlayer) {
+        let imp = self.imp();
+        let split_table_liststore = borrow_asref_upgrade(&imp.split_table_liststore).ok().unwrap();
+        let split_table = borrow_asref_upgrade(&imp.split_table).ok().unwrap();
+        let video_player_id = video_player.get_id();
+
+        let selected_segment = imp.selected_segment.get();
+        let selected_segment_time_entry = if selected_segment == 0 { split_table.get_offset_time_entry(video_player_id.as_str()) } else {
+            split_table_liststore.item(selected_segment.saturating_sub(1))
+                .and_downcast::<VideoSegment>()
+                .unwrap()
+                .get_time_entry_copy(video_player_id.as_str())
+        };
+
+        let color = video_player.get_color();
+        if let Some(waveform) = video_player.get_waveform() {
+            imp.seek_bar.add_waveform_lane(video_player_id.clone(), waveform, color.as_str(), selected_segment_time_entry.clone());
+        }
+        if let Some(thumbnails) = video_player.get_thumbnails() {
+            imp.seek_bar.add_preview_source(video_player_id, thumbnails, color.as_str(), selected_segment_time_entry);
+        }
+    }
+
+    //Removes a video player's waveform and hover thumbnails from the shared seek bar
+    pub fn remove_video_previews(&self, video_player_id: &str) {
+        let imp = self.imp();
+        imp.seek_bar.remove_waveform_lane(video_player_id);
+        imp.seek_bar.remove_preview_source(video_player_id);
+    }
+}
@@ -12,6 +12,49 @@ use glib::{value::ToValue, ParamSpecBuilderExt};
 use once_cell::sync::Lazy;
 use gtk::prelude::*;
 
+//Note and tags written during review, such as why a segment was lost
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Annotation {
+    pub note: String,
+    pub tags: Vec<String>,
+}
+
+impl Annotation {
+    //Splits comma separated tags, dropping empty and repeated ones
+    pub fn parse_tags(text: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in text.split(',').map(|tag| tag.trim().to_lowercase()) {
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.note.is_empty() && self.tags.is_empty()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    //Checks if the note or a tag contains the text, ignoring case
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.note.to_lowercase().contains(&text) || self.tags.iter().any(|tag| tag.contains(&text))
+    }
+
+    //Describes the annotation for a tooltip
+    pub fn describe(&self) -> String {
+        match (self.note.is_empty(), self.tags.is_empty()) {
+            (_, true) => self.note.clone(),
+            (true, false) => format!("Tags: {}", self.tags.join(", ")),
+            (false, false) => format!("{}\nTags: {}", self.note, self.tags.join(", ")),
+        }
+    }
+}
+
 mod imp {
     use super::*;
     
@@ -20,6 +63,7 @@ mod imp {
         pub time: TimeEntry,
         pub duration: Option<u64>,
         pub offset: TimeEntry,
+        pub comment: Annotation,
     }
 
     #[derive(Default)]
@@ -27,6 +71,7 @@ mod imp {
         pub name: RefCell<String>,
         pub id: RefCell<String>,
         pub segments: RefCell<HashMap<String, Segment>>,
+        pub note: RefCell<Annotation>,
     }
     
     #[gtk::glib::object_subclass]
@@ -62,7 +107,7 @@ mod imp {
         }
 
         fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
-            println!("Set property id: {_id}, value: {value:?}, pspec: {pspec:?}");
+            log_trace!("Set property id: {_id}, value: {value:?}, pspec: {pspec:?}");
             match pspec.name() {
                 "name" => {
                     let val = value.get::<String>().unwrap();
@@ -103,6 +148,7 @@ glib::wrapper! {
 // Video Segment:
 // Name: Name of the segment
 // Segment: time and duration of the split
+// Note: annotation of the whole segment, each split also keeps its own comment
 impl VideoSegment {
     pub fn new(name: &str) -> Self {
         let video_segment: Self = glib::Object::new::<Self>();
@@ -143,13 +189,14 @@ impl VideoSegment {
             time: TimeEntry::new(u64::MAX),
             duration: None,
             offset: TimeEntry::new(0),
+            comment: Annotation::default(),
         };
         imp.segments.borrow_mut().insert(id.to_string(), new_segment.clone());
         new_segment
     }
 
     pub fn set_time(&self, video_player_id: &str, time: u64) {
-        println!("Setting times to {time}");
+        log_trace!("Setting times to {time}");
         let imp = self.imp();
         imp.segments.borrow().get(video_player_id).unwrap().time.set_time(time);
         let id: &dyn ToValue = &video_player_id.to_string();
@@ -157,8 +204,21 @@ impl VideoSegment {
         //self.set_property(&format!("time-{}", video_player_id), time);
     }
 
+    //Sets the split time along with the frame number it was snapped to
+    pub fn set_time_with_frame(&self, video_player_id: &str, time: u64, frame: Option<u64>) {
+        self.set_time(video_player_id, time);
+        let imp = self.imp();
+        imp.segments.borrow().get(video_player_id).unwrap().time.set_property("frame", frame.unwrap_or(u64::MAX));
+    }
+
+    //Gets the frame number the split time was snapped to
+    pub fn get_frame(&self, video_player_id: &str) -> Option<u64> {
+        let imp = self.imp();
+        imp.segments.borrow().get(video_player_id).unwrap().time.get_frame()
+    }
+
     pub fn set_duration(&self, video_player_id: &str, duration: u64) {
-        println!("Setting duration to {duration}");
+        log_trace!("Setting duration to {duration}");
         let imp = self.imp();
         imp.segments.borrow_mut().get_mut(video_player_id).unwrap().duration = Some(duration);
         let id: &dyn ToValue = &video_player_id.to_string();
@@ -173,7 +233,7 @@ impl VideoSegment {
     }
 
     pub fn set_offset(&self, video_player_id: &str, offset: u64) {
-        println!("Setting {video_player_id} offset to: {offset}");
+        log_trace!("Setting {video_player_id} offset to: {offset}");
         let imp = self.imp();
         imp.segments.borrow().get(video_player_id).unwrap().offset.set_time(offset);
         let id: &dyn ToValue = &video_player_id.to_string();
@@ -199,6 +259,35 @@ impl VideoSegment {
         imp.id.borrow().to_string()
     }
 
+    //Gets the note of the segment, or the comment of one video's split when an id is given
+    pub fn get_annotation(&self, video_player_id: Option<&str>) -> Annotation {
+        let imp = self.imp();
+        match video_player_id {
+            Some(id) => imp.segments.borrow().get(id).map(|segment| segment.comment.clone()).unwrap_or_default(),
+            None => imp.note.borrow().clone(),
+        }
+    }
+
+    pub fn set_annotation(&self, video_player_id: Option<&str>, annotation: Annotation) {
+        let imp = self.imp();
+        match video_player_id {
+            Some(id) => {
+                if let Some(segment) = imp.segments.borrow_mut().get_mut(id) {
+                    segment.comment = annotation;
+                }
+            }
+            None => *imp.note.borrow_mut() = annotation,
+        }
+    }
+
+    //Checks if the name, note or any split comment contains the text, ignoring case
+    pub fn matches(&self, text: &str) -> bool {
+        let imp = self.imp();
+        self.get_name().to_lowercase().contains(&text.to_lowercase())
+            || imp.note.borrow().matches(text)
+            || imp.segments.borrow().values().any(|segment| segment.comment.matches(text))
+    }
+
     pub fn remove_segment(&self, video_player_id: &str) {
         let imp = self.imp();
         let mut segment_borrow = imp.segments.borrow_mut();
@@ -2,18 +2,27 @@
 use gio::ListStore;
 use gtk::glib;
 use gtk::subclass::{prelude::*};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use gtk::{ColumnView, prelude::*, SingleSelection, Entry, ListItem, FlowBox};
-use crate::widgets::split_panel::splits::VideoSegment;
+use crate::widgets::split_panel::splits::{Annotation, VideoSegment};
 use crate::widgets::split_panel::timeentry::TimeEntry;
 use crate::helpers::data::{store_data, get_data};
 use crate::helpers::format::format_clock;
+use crate::error::Error;
 use crate::helpers::parse::{string_to_nseconds, validate_split_table_entry};
+use crate::helpers::ui::show_error;
 use crate::widgets::video_player_widget::video_player::VideoPlayer;
 use std::collections::HashMap;
 use crate::widgets::split_panel::videosegmentproxy::VideoSegmentProxy;
 use gstreamer::ClockTime;
 
+//Icons shown for well known tags, any other annotation shows the note icon
+const TAG_ICONS: [(&str, &str); 2] = [
+    ("mistake", "dialog-warning-symbolic"),
+    ("new route", "mark-location-symbolic"),
+];
+const NOTE_ICON: &str = "document-edit-symbolic";
+
 
 mod imp {
 
@@ -29,6 +38,8 @@ mod imp {
         pub start_time_offset_liststore: RefCell<Option<ListStore>>,
         pub start_time_offset_row_map: RefCell<HashMap<String, TimeEntry>>,
         pub split_table_columns: RefCell<HashMap<String, ColumnViewColumn>>,
+        pub snap_to_frames: Cell<bool>,
+        pub frame_snapper: RefCell<Option<Box<dyn Fn(&str, u64) -> Option<(u64, u64)>>>>,
     }
     
     #[gtk::glib::object_subclass]
@@ -79,34 +90,52 @@ impl SplitTable {
         split_table
     }
 
-    pub fn set_split(&self, video_player_id: &str, video_player_position: u64) -> Result<(), String> {
+    pub fn set_split(&self, video_player_id: &str, video_player_position: u64) -> Result<(), Error> {
         let imp = self.imp();
         let split_table_column_view_borrow = imp.split_table_column_view.borrow();
         let split_table_column_view = match split_table_column_view_borrow.as_ref() {
             Some(ls) => ls,
-            None => return Err("Missing split_table_column_view".to_string()),
+            None => return Err(Error::Table("Missing split_table_column_view".to_string())),
         };
         let selection_model = split_table_column_view.model().and_downcast::<SingleSelection>().unwrap();
-        if let Some(selected_segment) = selection_model.selected_item().and_downcast::<VideoSegment>() {
-            let selected_index = selection_model.selected();
-            if video_player_position < selected_segment.get_offset(video_player_id) {
-                return Err("Error: Split time set be starting time.".to_string());
+        let segment_id = selection_model.selected_item().and_downcast::<VideoSegment>()
+            .map(|selected_segment| selected_segment.get_segment_id())
+            .ok_or_else(|| Error::Table("Segment not selected".to_string()))?;
+        self.set_split_time(video_player_id, &segment_id, video_player_position)
+    }
+
+    //Sets the split time of a specific segment, used when a mark is dragged on a seek bar
+    pub fn set_split_time(&self, video_player_id: &str, segment_id: &str, video_player_position: u64) -> Result<(), Error> {
+        let imp = self.imp();
+        let split_table_liststore_borrow = imp.split_table_liststore.borrow();
+        let split_table_liststore = match split_table_liststore_borrow.as_ref() {
+            Some(ls) => ls,
+            None => return Err(Error::Table("Missing split_table_liststore".to_string())),
+        };
+        for i in 0..split_table_liststore.n_items() {
+            let video_segment = split_table_liststore.item(i).and_downcast::<VideoSegment>().unwrap();
+            if video_segment.get_segment_id() != segment_id {
+                continue;
             }
-            selected_segment.set_time(video_player_id, video_player_position);
-            self.correct_conflicts(video_player_id, selected_index);
-            Ok(())
-        } else {
-            Err("Segment not selected".to_string())
+            let (video_player_position, frame) = self.snap_time(video_player_id, video_player_position);
+            if video_player_position < video_segment.get_offset(video_player_id) {
+                return Err(Error::Table("Split time set before starting time.".to_string()));
+            }
+            video_segment.set_time_with_frame(video_player_id, video_player_position, frame);
+            self.correct_conflicts(video_player_id, i);
+            return Ok(());
         }
+        Err(Error::Table(format!("Segment {segment_id} not found")))
     }
 
-    pub fn set_start_time_offset(&self, video_player_id: &str, video_player_position: u64) -> Result<(), String> {
+    pub fn set_start_time_offset(&self, video_player_id: &str, video_player_position: u64) -> Result<(), Error> {
         let imp = self.imp();
         let split_table_liststore_borrow = imp.split_table_liststore.borrow();
         let split_table_liststore = match split_table_liststore_borrow.as_ref() {
             Some(ls) => ls,
-            None => return Err("Missing split_table_liststore".to_string()),
+            None => return Err(Error::Table("Missing split_table_liststore".to_string())),
         };
+        let (video_player_position, frame) = self.snap_time(video_player_id, video_player_position);
         let start_offset_time_entry = self.get_offset_time_entry(video_player_id);
         //let start_offset_time_entry = start_time_offset_liststore.item(video_player_id).and_downcast::<TimeEntry>().unwrap();
         
@@ -115,7 +144,7 @@ impl SplitTable {
             if i == 0 {
                 let time = video_segment.get_time(video_player_id);
                 if video_player_position > time {
-                    return Err("Start time set after segment times. Remove earlier splits.".to_string());
+                    return Err(Error::Table("Start time set after segment times. Remove earlier splits.".to_string()));
                 }
                 if time != u64::MAX {
                     video_segment.set_duration(video_player_id, time - video_player_position);
@@ -123,16 +152,43 @@ impl SplitTable {
             }
             video_segment.set_offset(video_player_id, video_player_position);
         }
-        start_offset_time_entry.set_time(video_player_position);
+        start_offset_time_entry.set_time_with_frame(video_player_position, frame);
         Ok(())
     }
 
-    pub fn add_start_time_offset_row(&self, video_player_id: &str) -> Result<TimeEntry, String> {
+    //Enables or disables snapping recorded and entered times to the start of the displayed frame
+    pub fn set_snap_to_frames(&self, snap: bool) {
+        let imp = self.imp();
+        imp.snap_to_frames.set(snap);
+    }
+
+    //Sets the function used to find the frame a time falls in for a video player
+    //Returns the start time of the frame and its frame number
+    pub fn set_frame_snapper<F>(&self, snapper: F)
+    where F: Fn(&str, u64) -> Option<(u64, u64)> + 'static {
+        let imp = self.imp();
+        *imp.frame_snapper.borrow_mut() = Some(Box::new(snapper));
+    }
+
+    //Snaps a time to the start of the frame it falls in when snapping is enabled
+    //Output: time to store and the frame number if it was snapped
+    fn snap_time(&self, video_player_id: &str, time: u64) -> (u64, Option<u64>) {
+        let imp = self.imp();
+        if !imp.snap_to_frames.get() || time == u64::MAX {
+            return (time, None);
+        }
+        match imp.frame_snapper.borrow().as_ref().and_then(|snapper| snapper(video_player_id, time)) {
+            Some((snapped, frame)) => (snapped, Some(frame)),
+            None => (time, None),
+        }
+    }
+
+    pub fn add_start_time_offset_row(&self, video_player_id: &str) -> Result<TimeEntry, Error> {
         let imp = self.imp();
         let start_time_offset_liststore_borrow = imp.start_time_offset_liststore.borrow();
         let start_time_offset_liststore = match start_time_offset_liststore_borrow.as_ref() {
             Some(stol) => stol,
-            None => return Err("Missing start_time_offset_liststore".to_string()),
+            None => return Err(Error::Table("Missing start_time_offset_liststore".to_string())),
         };
 
         let new_start_time_offset_time_entry = TimeEntry::new(0);
@@ -319,17 +375,25 @@ impl SplitTable {
                         match &property {
                             prop if prop.starts_with("name") => {
                                 // do name stuff here
-                                println!("Change name not impletemented");
+                                log_warn!("Changing segment names is not implemented");
                             }
                             prop if prop.starts_with("relative-time") => {
-                                println!("Changing {}", property);
+                                log_debug!("Changing {}", property);
                                 let row_index = liststore.find(&video_segment).unwrap();
                                 let valid_entry = validate_split_table_entry(&entry);
                                 if !valid_entry { // Restores segment data if invalid entry
                                     let stored_entry_data: u64 = proxy.property("value");
                                     entry.set_text(format_clock(stored_entry_data).as_str());
                                 } else { // updates segment data with new entry and fixes any conflicts
-                                    let new_relative_time = string_to_nseconds(&entry.text().to_string()).unwrap();
+                                    let new_relative_time = match string_to_nseconds(&entry.text().to_string()) {
+                                        Ok(time) => time,
+                                        Err(e) => {
+                                            show_error(&entry, &e);
+                                            let stored_entry_data: u64 = proxy.property("value");
+                                            entry.set_text(format_clock(stored_entry_data).as_str());
+                                            return;
+                                        }
+                                    };
                                     let new_time = new_relative_time + video_segment.get_offset(video_id.as_str());
                                     let old_time = video_segment.get_time(video_id.as_str());
 
@@ -342,7 +406,8 @@ impl SplitTable {
                                     // Only updates the time if the difference between the current time and the new time is greater than the maximum allowed difference of 2 milliseconds
                                     // If the difference is less than the maximum allowed difference it is likely the entered value is the truncated version of the current value which we would rather keep. 
                                     if difference > maximum_allowed_difference {
-                                        video_segment.set_time(video_id.as_str(), new_time);
+                                        let (new_time, frame) = this.snap_time(video_id.as_str(), new_time);
+                                        video_segment.set_time_with_frame(video_id.as_str(), new_time, frame);
                                         this.correct_conflicts(video_id.as_str(), row_index);
                                     } else {
                                         let stored_entry_data: u64 = proxy.property("value");
@@ -351,7 +416,7 @@ impl SplitTable {
                                 }
                             }
                             prop if prop.starts_with("duration") => {
-                                println!("Changing {}", property);
+                                log_debug!("Changing {}", property);
                                 let row_index = liststore.find(&video_segment).unwrap();
                                 let valid_entry = validate_split_table_entry(&entry);
                                 if !valid_entry { // Restores segment data if invalid entry
@@ -359,7 +424,15 @@ impl SplitTable {
                                     entry.set_text(format_clock(stored_entry_data).as_str());
                                 } else { // updates segment data with new entry and fixes any conflicts
                                     let old_duration = video_segment.get_duration(video_id.as_str()).unwrap();
-                                    let new_duration = string_to_nseconds(&entry.text().to_string()).unwrap();
+                                    let new_duration = match string_to_nseconds(&entry.text().to_string()) {
+                                        Ok(duration) => duration,
+                                        Err(e) => {
+                                            show_error(&entry, &e);
+                                            let stored_entry_data: u64 = proxy.property("value");
+                                            entry.set_text(format_clock(stored_entry_data).as_str());
+                                            return;
+                                        }
+                                    };
 
                                     let old_duration_milli = ClockTime::from_nseconds(old_duration).mseconds();
                                     let new_duration_milli = ClockTime::from_nseconds(new_duration).mseconds();
@@ -370,12 +443,13 @@ impl SplitTable {
                                     // Only updates the duration if the difference between the current duration and the new duration is greater than the maximum allowed difference of 2 milliseconds
                                     // If the difference is less than the maximum allowed difference it is likely the entered value is the truncated version of the current value which we would rather keep.
                                     if difference > maximum_allowed_difference {
-                                        video_segment.set_duration(video_id.as_str(), new_duration);
                                         let previous_time: u64 = match this.get_previous_time(video_id.as_str(), row_index) {
                                             Some(time) => time,
                                             None => video_segment.get_offset(video_id.as_str()),
                                         };
-                                        video_segment.set_time(video_id.as_str(), previous_time + new_duration);
+                                        let (new_time, frame) = this.snap_time(video_id.as_str(), previous_time + new_duration);
+                                        video_segment.set_duration(video_id.as_str(), new_time.saturating_sub(previous_time));
+                                        video_segment.set_time_with_frame(video_id.as_str(), new_time, frame);
                                         this.correct_conflicts(video_id.as_str(), row_index);
                                     } else {
                                         let stored_entry_data: u64 = proxy.property("value");
@@ -385,7 +459,7 @@ impl SplitTable {
                                 }
                             }
                             _ => {
-                                eprintln!("Invalid property: {}", property);
+                                log_error!("Invalid property: {}", property);
                             }
                         }
                     }
@@ -406,7 +480,13 @@ impl SplitTable {
                     }
                 }
             ));
-            list_item.set_child(Some(&entry));
+            //Split times carry the comment of this video's split
+            let cell = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            cell.append(&entry);
+            if property.starts_with("relative-time") {
+                cell.append(&create_annotation_button(list_item, Some(video_id.clone())));
+            }
+            list_item.set_child(Some(&cell));
         }));
 
         // Binds the stored data to the displayed entry objects
@@ -414,7 +494,11 @@ impl SplitTable {
         let video_id = video_player_id.to_string();
         factory.connect_bind(move |_, list_item| {
             let item = list_item.item().and_then(|obj| obj.downcast::<VideoSegment>().ok()).expect("The item is not a VideoSegment");
-            let entry = list_item.child().and_then(|child| child.downcast::<Entry>().ok()).expect("The child widget is not Entry");
+            let cell = list_item.child().and_downcast::<gtk::Box>().expect("The child widget is not a Box");
+            let entry = cell.first_child().and_downcast::<Entry>().expect("The cell has no Entry");
+            if let Some(annotation_button) = cell.last_child().and_downcast::<gtk::MenuButton>() {
+                update_annotation_button(&annotation_button, &item.get_annotation(Some(&video_id)));
+            }
             let proxy = VideoSegmentProxy::new(&item, &video_id, &property);
             // Binds the u64 stored in the video segment to the entries formatted clock
             // Any changes to the videosegment will be updated in the entry object
@@ -474,7 +558,7 @@ impl SplitTable {
                 #[weak(rename_to = liststore)] liststore_clone,
                 move |_| {
                     if let Some(video_segment) = list_item.item().and_downcast::<VideoSegment>() {
-                        println!("Changed focus");
+                        log_trace!("Changed focus");
                         let selection_model = column_view.model().and_downcast::<SingleSelection>().unwrap();
                         let row_index = liststore.find(&video_segment).unwrap_or(u32::MAX);
                         if row_index != u32::MAX {
@@ -483,16 +567,23 @@ impl SplitTable {
                     }
                 }
             ));
-            list_item.set_child(Some(&entry));
+            //Segment names carry the note of the whole segment
+            let cell = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            cell.append(&entry);
+            cell.append(&create_annotation_button(list_item, None));
+            list_item.set_child(Some(&cell));
         });
         
         // Binds the stored data to the displayed entry objects
         factory.connect_bind(move |_factory, list_item: &ListItem| {
-            let entry = list_item.child().unwrap().downcast::<Entry>().expect("The child is not an Entry");
+            let cell = list_item.child().and_downcast::<gtk::Box>().expect("The child is not a Box");
+            let entry = cell.first_child().and_downcast::<Entry>().expect("The cell has no Entry");
+            let annotation_button = cell.last_child().and_downcast::<gtk::MenuButton>().expect("The cell has no annotation button");
             let item = list_item.item();
             let video_segment = item.and_downcast_ref::<VideoSegment>().expect("Item is not a VideoSegment");
             let current_name = video_segment.get_name();
             entry.set_text(&current_name);
+            update_annotation_button(&annotation_button, &video_segment.get_annotation(None));
         });
 
         
@@ -519,13 +610,16 @@ impl SplitTable {
         let factory = gtk::SignalListItemFactory::new();
         let split_table_liststore_clone = split_table_liststore.clone();
         // Creates the entry objects
-        factory.connect_setup(move |_, list_item| {
+        factory.connect_setup(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, list_item| {
             let entry = gtk::Entry::new();
             entry.add_css_class("flat");
             entry.set_hexpand(true);
             entry.set_halign(gtk::Align::Fill);
             
             entry.connect_activate(glib::clone!(
+                #[weak(rename_to = this)] this,
                 #[weak(rename_to = list_item)] list_item,
                 #[weak(rename_to = entry)] entry,
                 #[weak(rename_to = split_table_liststore)] split_table_liststore_clone,
@@ -536,9 +630,17 @@ impl SplitTable {
                             let time_entry_data = time_entry.get_time();
                             entry.set_text(format_clock(time_entry_data).as_str());
                         } else {
-                            let new_time = string_to_nseconds(&entry.text().to_string()).unwrap();
-                            time_entry.set_time(new_time);
+                            let new_time = match string_to_nseconds(&entry.text().to_string()) {
+                                Ok(time) => time,
+                                Err(e) => {
+                                    show_error(&entry, &e);
+                                    entry.set_text(format_clock(time_entry.get_time()).as_str());
+                                    return;
+                                }
+                            };
                             let video_player_id = unsafe { get_data::<String>(&time_entry, "video_player_id").unwrap().as_ref() };
+                            let (new_time, frame) = this.snap_time(video_player_id.as_str(), new_time);
+                            time_entry.set_time_with_frame(new_time, frame);
                             for i in 0..split_table_liststore.n_items() {
                                 let video_segment = split_table_liststore.item(i).and_downcast::<VideoSegment>().unwrap();
                                 video_segment.set_offset(video_player_id.as_str(), new_time);
@@ -549,7 +651,7 @@ impl SplitTable {
                 }
             ));
             list_item.set_child(Some(&entry));
-        });
+        }));
         
         // Binds the stored data to the displayed entry objects
         factory.connect_bind(move |_, list_item| {
@@ -623,13 +725,12 @@ impl SplitTable {
         let row_count = split_table_liststore.n_items();
         for i in 0..video_player_count {
             let video_player = video_player_container.child_at_index(i)
-                .and_then(|child| child.child())
-                .and_downcast::<VideoPlayer>()
+                .and_then(|child| VideoPlayer::from_flowbox_child(&child))
                 .unwrap();
             let video_player_id = video_player.get_id().to_string();
             let time = row.get_time_entry_copy(video_player_id.as_str());
             let row_id = row.get_segment_id();
-            video_player.connect_time_to_seekbar(format!("video-{i}, segment-{row_id}"), time, "black");
+            video_player.connect_time_to_seekbar(format!("video-{i}, segment-{row_id}"), time, "black", &row);
         }
     }
 
@@ -643,18 +744,53 @@ impl SplitTable {
 
         let row_count = split_table_liststore.n_items();
         let video_player = video_player_container.child_at_index(column_index as i32)
-            .and_then(|child| child.child())
-            .and_downcast::<VideoPlayer>()
+            .and_then(|child| VideoPlayer::from_flowbox_child(&child))
             .unwrap();
         let column_index = video_player.get_id().to_string();
         for i in 0..row_count {
             let row = split_table_liststore.item(i).and_downcast::<VideoSegment>().unwrap();
             let time = row.get_time_entry_copy(column_index.as_str());
             let segment_id = row.get_segment_id();
-            video_player.connect_time_to_seekbar(format!("video-{column_index}, segment-{segment_id}"), time, "black");
+            video_player.connect_time_to_seekbar(format!("video-{column_index}, segment-{segment_id}"), time, "black", &row);
+        }
+
+        //Segment bands follow the rows and selection of the split table
+        let split_table_column_view_borrow = imp.split_table_column_view.borrow();
+        if let Some(selection_model) = split_table_column_view_borrow.as_ref().and_then(|cv| cv.model()).and_downcast::<SingleSelection>() {
+            video_player.connect_segments_to_seekbar(split_table_liststore, &selection_model);
         }
     }
 
+    //Selects the next row whose name, notes, comments or tags contain the text, wrapping around
+    //Input: text to search for and whether to start after the selected row instead of at it
+    //Output: whether a row matched
+    pub fn select_next_match(&self, text: &str, skip_selected: bool) -> bool {
+        let text = text.trim();
+        if text.is_empty() {
+            return true;
+        }
+        let (Some(liststore), Some(column_view)) = (self.get_split_table_liststore(), self.get_split_table_column_view()) else {
+            return false;
+        };
+        let Some(selection_model) = column_view.model().and_downcast::<SingleSelection>() else {
+            return false;
+        };
+        let row_count = liststore.n_items();
+        let start = match selection_model.selected() {
+            gtk::INVALID_LIST_POSITION => 0,
+            selected if skip_selected => selected + 1,
+            selected => selected,
+        };
+        for offset in 0..row_count {
+            let row_index = (start + offset) % row_count;
+            if liststore.item(row_index).and_downcast::<VideoSegment>().is_some_and(|segment| segment.matches(text)) {
+                selection_model.select_item(row_index, true);
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn get_offset_time_entry(&self, video_player_id: &str) -> TimeEntry {
         let imp = self.imp();
         imp.start_time_offset_row_map.borrow().get(video_player_id).unwrap().clone()
@@ -712,3 +848,77 @@ impl SplitTable {
         }
     }
 }
+
+//Creates the button that shows and edits the note and tags of a segment, or the comment on one video's split
+//Input: list item holding the segment and the video player id of the split, None for the segment note
+fn create_annotation_button(list_item: &ListItem, video_player_id: Option<String>) -> gtk::MenuButton {
+    let annotation_button = gtk::MenuButton::new();
+    annotation_button.add_css_class("flat");
+    annotation_button.set_valign(gtk::Align::Center);
+
+    let note_view = gtk::TextView::new();
+    note_view.set_wrap_mode(gtk::WrapMode::WordChar);
+    note_view.set_size_request(240, 80);
+    let tags_entry = Entry::new();
+    tags_entry.set_placeholder_text(Some("Tags, e.g. mistake, new route"));
+    let apply_button = gtk::Button::with_label("Apply");
+
+    let popover_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    popover_box.append(&note_view);
+    popover_box.append(&tags_entry);
+    popover_box.append(&apply_button);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&popover_box));
+    annotation_button.set_popover(Some(&popover));
+
+    //Loads the annotation of the row the cell currently shows
+    popover.connect_show(glib::clone!(
+        #[weak] list_item,
+        #[weak] note_view,
+        #[weak] tags_entry,
+        #[strong] video_player_id,
+        move |_| {
+            if let Some(video_segment) = list_item.item().and_downcast::<VideoSegment>() {
+                let annotation = video_segment.get_annotation(video_player_id.as_deref());
+                note_view.buffer().set_text(&annotation.note);
+                tags_entry.set_text(&annotation.tags.join(", "));
+            }
+        }
+    ));
+
+    apply_button.connect_clicked(glib::clone!(
+        #[weak] list_item,
+        #[weak] annotation_button,
+        #[weak] popover,
+        #[weak] note_view,
+        #[weak] tags_entry,
+        move |_| {
+            if let Some(video_segment) = list_item.item().and_downcast::<VideoSegment>() {
+                let buffer = note_view.buffer();
+                let annotation = Annotation {
+                    note: buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).trim().to_string(),
+                    tags: Annotation::parse_tags(&tags_entry.text()),
+                };
+                update_annotation_button(&annotation_button, &annotation);
+                video_segment.set_annotation(video_player_id.as_deref(), annotation);
+            }
+            popover.popdown();
+        }
+    ));
+    annotation_button
+}
+
+//Shows the icon of the first well known tag, or a dimmed note icon when there is nothing written
+fn update_annotation_button(annotation_button: &gtk::MenuButton, annotation: &Annotation) {
+    let icon = TAG_ICONS.iter()
+        .find(|(tag, _)| annotation.has_tag(tag))
+        .map_or(NOTE_ICON, |(_, icon)| *icon);
+    annotation_button.set_icon_name(icon);
+    if annotation.is_empty() {
+        annotation_button.add_css_class("dim-label");
+        annotation_button.set_tooltip_text(Some("Add a note"));
+    } else {
+        annotation_button.remove_css_class("dim-label");
+        annotation_button.set_tooltip_text(Some(&annotation.describe()));
+    }
+}
@@ -11,6 +11,7 @@ mod imp {
     pub struct TimeEntry {
         pub time: RefCell<u64>,
         pub old_time: RefCell<u64>,
+        pub frame: RefCell<u64>,
     }
     
     #[gtk::glib::object_subclass]
@@ -36,6 +37,14 @@ mod imp {
                         .minimum(0)
                         .maximum(u64::MAX)
                         .flags(glib::ParamFlags::READWRITE)
+                        .build(),
+                        ParamSpecUInt64::builder("frame")
+                        .nick("Frame")
+                        .blurb("Frame number the time was snapped to")
+                        .minimum(0)
+                        .maximum(u64::MAX)
+                        .default_value(u64::MAX)
+                        .flags(glib::ParamFlags::READWRITE)
                         .build(),]
                 });
                 PROPERTIES.as_ref()
@@ -49,6 +58,9 @@ mod imp {
                 2 => {
                     self.old_time.borrow().to_value()
                 },
+                3 => {
+                    self.frame.borrow().to_value()
+                },
                 _ => unimplemented!(),
             }
         }
@@ -60,10 +72,15 @@ mod imp {
                     let old_time = *self.time.borrow();
                     *self.old_time.borrow_mut() = old_time;
                     *self.time.borrow_mut() = val;
-                    println!("Set Value: {val}, Old Value: {old_time}");
+                    //A new time no longer matches the frame it was snapped to
+                    *self.frame.borrow_mut() = u64::MAX;
+                    log_trace!("Set Value: {val}, Old Value: {old_time}");
                 },
                 2 => {
 
+                },
+                3 => {
+                    *self.frame.borrow_mut() = value.get::<u64>().unwrap();
                 },
                 _ => unimplemented!(),
             }
@@ -81,6 +98,7 @@ impl TimeEntry {
         let time_entry = glib::Object::new::<Self>();
         time_entry.set_property("time", time);
         time_entry.set_property("old-time", time);
+        time_entry.set_property("frame", u64::MAX);
         time_entry
     }
 
@@ -92,6 +110,22 @@ impl TimeEntry {
         self.set_property("time", new_time);
     }
 
+    //Gets the frame number the time was snapped to
+    pub fn get_frame(&self) -> Option<u64> {
+        let frame: u64 = self.property("frame");
+        if frame == u64::MAX {
+            None
+        } else {
+            Some(frame)
+        }
+    }
+
+    //Sets the time along with the frame number it was snapped to
+    pub fn set_time_with_frame(&self, new_time: u64, frame: Option<u64>) {
+        self.set_property("time", new_time);
+        self.set_property("frame", frame.unwrap_or(u64::MAX));
+    }
+
     pub fn get_old_time(&self) -> u64 {
         self.property("old-time")
     }
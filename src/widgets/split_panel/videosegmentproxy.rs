@@ -115,7 +115,7 @@ mod imp {
         }
 
         fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
-            println!("Set property id: {_id}, value: {value:?}, pspec: {pspec:?}");
+            log_trace!("Set property id: {_id}, value: {value:?}, pspec: {pspec:?}");
             match pspec.name() {
                 "segment" => {
                     let segment = value.get::<VideoSegment>().ok();
@@ -5,6 +5,7 @@ use glib::subclass::Signal;
 use once_cell::sync::Lazy;
 use gtk::subclass::{prelude::*};
 use gtk::prelude::*;
+use crate::error::Error;
 use crate::video_pipeline::VideoPipeline;
 use std::sync::{Weak, Arc, Mutex};
 use std::collections::{HashMap, HashSet};
@@ -26,6 +27,49 @@ pub enum SyncEvent {
     PlaybackStarted { base_time: ClockTime, scale_position: ClockTime },
     PlaybackPaused,
     Seeked,
+    DriftReport { stats: HashMap<String, DriftStats> },
+}
+
+//How often drift is checked during shared playback
+const DRIFT_CHECK_INTERVAL_MS: u64 = 500;
+//Drift compared to the other videos at which playback speed is nudged
+const DRIFT_NUDGE_THRESHOLD: u64 = 40_000_000;
+//Drift compared to the other videos at which the video is seeked back in line
+const DRIFT_SEEK_THRESHOLD: u64 = 250_000_000;
+//Drift at which a nudged video goes back to normal speed
+const DRIFT_SETTLED_THRESHOLD: u64 = 10_000_000;
+//How much faster or slower a nudged video plays
+const DRIFT_NUDGE_RATE: f64 = 0.05;
+//Checks skipped after a corrective seek while the video prerolls again
+const DRIFT_SEEK_COOLDOWN_CHECKS: u32 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DriftCorrection {
+    #[default]
+    None,
+    RateNudge(f64),
+    Seek,
+}
+
+//Drift of a single video since shared playback last started
+#[derive(Clone, Debug, Default)]
+pub struct DriftStats {
+    //Distance from where the shared clock says the video should be in nanoseconds, negative when behind
+    pub drift: i64,
+    //Drift compared to the median of every playing video, which is what gets corrected
+    pub relative_drift: i64,
+    pub max_relative_drift: u64,
+    pub corrections: u32,
+    pub correction: DriftCorrection,
+    //Checks left before the video is corrected again
+    pub cooldown: u32,
+}
+
+//Clock time and positions when shared playback last started, used to work out where each video should be
+#[derive(Clone, Debug)]
+pub struct PlaybackAnchor {
+    pub clock_time: ClockTime,
+    pub positions: HashMap<String, ClockTime>,
 }
 
 mod imp {
@@ -37,6 +81,9 @@ mod imp {
         pub shared_clock: OnceCell<Clock>,
         pub sync_callbacks: RefCell<Vec<Box<dyn Fn(SyncEvent)>>>,
         pub is_synced: Rc<Cell<bool>>,
+        pub playback_anchor: RefCell<Option<PlaybackAnchor>>,
+        pub drift_monitor: RefCell<Option<glib::SourceId>>,
+        pub drift_stats: RefCell<HashMap<String, DriftStats>>,
     }
     
     #[gtk::glib::object_subclass]
@@ -50,7 +97,10 @@ mod imp {
 
     impl ObjectImpl for SyncManager {
         fn dispose(&self) {
-            println!("Disposing sync manager");
+            log_debug!("Disposing sync manager");
+            if let Some(source) = self.drift_monitor.borrow_mut().take() {
+                source.remove();
+            }
             self.pipelines.lock().unwrap().clear();
         }
     }
@@ -70,14 +120,14 @@ impl SyncManager {
     }
 
     //Adds pipeline to sync manager
-    pub fn add_pipeline(&self, pipeline_id: &str, video_pipeline_weak: Weak<Mutex<VideoPipeline>>) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn add_pipeline(&self, pipeline_id: &str, video_pipeline_weak: Weak<Mutex<VideoPipeline>>) -> Result<(), Error> {
         let mut imp = self.imp();
         let video_pipeline_arc = match video_pipeline_weak.upgrade() {
             Some(p) => p,
-            None => return Err("Failed to upgrade video pipeline".into()),
+            None => return Err(Error::Sync(format!("Video {pipeline_id} was closed before it could be synced"))),
         };
         let video_pipeline = video_pipeline_arc.lock().unwrap();
-        println!("Adding pipeline into sync manager");
+        log_debug!("Adding pipeline {pipeline_id} to sync manager");
         imp.pipelines.lock().unwrap().insert(pipeline_id.to_string(), video_pipeline_weak);
         Ok(())
 
@@ -92,123 +142,224 @@ impl SyncManager {
     //Syncs and plays each video
     //Input: Start time offsets for each individual video
     //Output: Shared base time for the videos and the progression of the videos to display on the ui
-    pub fn play_videos(&self, offsets: HashMap<String, u64>) {
+    //Nothing is played when a video is missing its offset, videos that fail to play are reported together
+    pub fn play_videos(&self, offsets: HashMap<String, u64>) -> Result<(), Error> {
         let imp = self.imp();
         if imp.is_synced.get() {
-            return;
+            return Ok(());
         }
+        self.check_all_present(&offsets, "start time offset")?;
         //Get time from the central clock to use for base times
-        let shared_clock_time = imp.shared_clock.get().unwrap().time().unwrap();
+        let shared_clock_time = self.get_shared_clock_time()?;
 
+        let mut failures = Vec::new();
+        let mut anchor_positions = HashMap::new();
         for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
             let video_pipeline = match pipeline_weak.upgrade() {
                 Some(p) => p,
-                None => return,
+                None => continue,
             };
 
             let pipeline = video_pipeline.lock().unwrap();
-            let offset_time = offsets.get(video_player_id).unwrap();
+            let offset_time = offsets[video_player_id];
+            if let Some(position) = pipeline.get_position() {
+                anchor_positions.insert(video_player_id.clone(), position);
+            }
 
             //Sync each pipeline to the same central clock 
             pipeline.pipeline().unwrap().use_clock(imp.shared_clock.get());
 
             //Updates base time relative to the start time offset past in
-            pipeline.pipeline().unwrap().set_base_time(shared_clock_time + ClockTime::from_nseconds(*offset_time));
+            pipeline.pipeline().unwrap().set_base_time(shared_clock_time + ClockTime::from_nseconds(offset_time));
             
-            pipeline.play_video();
+            if let Err(e) = pipeline.play_video() {
+                failures.push(format!("{video_player_id}: {}", e.message()));
+            }
         }
 
+        imp.playback_anchor.replace(Some(PlaybackAnchor { clock_time: shared_clock_time, positions: anchor_positions }));
+
         //Finds the position the ui progression bar should be.
         let scale_position = self.get_current_logical_position(&offsets);
         self.emit_event(SyncEvent::PlaybackStarted { base_time: shared_clock_time, scale_position: scale_position });
+        self.start_drift_monitor();
+        Self::collect_failures("play", failures)
     }
 
     //Pauses all videos
-    pub fn pause_videos(&self) {
+    pub fn pause_videos(&self) -> Result<(), Error> {
         let imp = self.imp();
-        for pipeline_weak in imp.pipelines.lock().unwrap().values() {
+        imp.playback_anchor.replace(None);
+        self.stop_drift_monitor();
+        let mut failures = Vec::new();
+        for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
             let pipeline = match pipeline_weak.upgrade() {
                 Some(p) => p,
-                None => return,
+                None => continue,
             };
-            pipeline.lock().unwrap().pause_video();
+            if let Err(e) = pipeline.lock().unwrap().pause_video() {
+                failures.push(format!("{video_player_id}: {}", e.message()));
+            }
         }
 
         self.emit_event(SyncEvent::PlaybackPaused);
+        Self::collect_failures("pause", failures)
     }
 
     //Moves each video 1 frame forward
-    pub fn frame_forward(&self) {
+    pub fn frame_forward(&self) -> Result<(), Error> {
         let imp = self.imp();
-        for pipeline_weak in imp.pipelines.lock().unwrap().values() {
+        let mut failures = Vec::new();
+        for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
             let pipeline = match pipeline_weak.upgrade() {
                 Some(p) => p,
-                None => return,
+                None => continue,
             };
             //Moves pipeline forward a frame with respect to the clamp times
             let result = pipeline.lock().unwrap().frame_forward_clamped();
             if let Err(e) = result {
-                eprintln!("Sync manager frame forward error: {e}");
+                failures.push(format!("{video_player_id}: {}", e.message()));
             }
         }
+        Self::collect_failures("step forward", failures)
     }
 
     //Move each video 1 frame backward
-    pub fn frame_backward(&self) {
+    pub fn frame_backward(&self) -> Result<(), Error> {
         let imp = self.imp();
-        for pipeline_weak in imp.pipelines.lock().unwrap().values() {
+        let mut failures = Vec::new();
+        for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
             let pipeline = match pipeline_weak.upgrade() {
                 Some(p) => p,
-                None => return,
+                None => continue,
             };
             //Moves pipeline backward a frame with respect to the clamp times
             let result = pipeline.lock().unwrap().frame_backward_clamped();
             if let Err(e) = result {
-                eprintln!("Sync manager frame backward error: {e}");
+                failures.push(format!("{video_player_id}: {}", e.message()));
             }
         }
+        Self::collect_failures("step backward", failures)
     }
 
     //Set each video to specified position
     //Input: HashMap<video_player_id, position>
-    pub fn seek(&self, positions: HashMap<String, ClockTime>) {
+    //Nothing is seeked when a video is missing its position
+    pub fn seek(&self, positions: HashMap<String, ClockTime>) -> Result<(), Error> {
         let imp = self.imp();
-        let shared_clock_time = imp.shared_clock.get().unwrap().time().unwrap();
+        self.check_all_present(&positions, "seek position")?;
+        let shared_clock_time = self.get_shared_clock_time()?;
+        let mut failures = Vec::new();
+        let mut anchor_positions = HashMap::new();
         for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
             let video_pipeline = match pipeline_weak.upgrade() {
                 Some(p) => p,
-                None => return,
+                None => continue,
             };
-            let position = positions.get(video_player_id.as_str()).unwrap();
+            let position = positions[video_player_id.as_str()];
             let pipeline = video_pipeline.lock().unwrap();
+            anchor_positions.insert(video_player_id.clone(), pipeline.get_clamped_position(position));
             
             //Sync each pipeline to the same central clock 
             pipeline.pipeline().unwrap().use_clock(imp.shared_clock.get());
             
             //Updates base time to relect the new position
-            pipeline.pipeline().unwrap().set_base_time(shared_clock_time - *position);
+            pipeline.pipeline().unwrap().set_base_time(shared_clock_time.saturating_sub(position));
             
             //Performs the seek opertation with respect the clamped times
-            let result = pipeline.seek_clamped(*position);
-            if let Err(e) = result {
-                eprintln!("Failed to perform seek clamped: {e}");
+            if let Err(e) = pipeline.seek_clamped(position) {
+                failures.push(format!("{video_player_id}: {}", e.message()));
+            }
+        }
+        //Seeking while playing restarts the expected positions from the seeked positions
+        //Flushing seeks also return nudged videos to normal speed
+        if imp.playback_anchor.borrow().is_some() {
+            imp.playback_anchor.replace(Some(PlaybackAnchor { clock_time: shared_clock_time, positions: anchor_positions }));
+            for stats in imp.drift_stats.borrow_mut().values_mut() {
+                stats.correction = DriftCorrection::None;
+                stats.cooldown = DRIFT_SEEK_COOLDOWN_CHECKS;
             }
         }
         self.emit_event(SyncEvent::Seeked);
+        Self::collect_failures("seek", failures)
     }
 
-    //Not currently being used
-    pub fn sync_clocks(&self) -> Result<(), Box<dyn std::error::Error>> {
+    //Seeks a single video while the others keep playing, used to change its alignment in the sync view
+    //Input: id of the video, position in the video
+    pub fn seek_video(&self, pipeline_id: &str, position: ClockTime) -> Result<(), Error> {
         let imp = self.imp();
-        let sync_time = imp.shared_clock.get().unwrap().time().unwrap();
+        let video_pipeline = imp.pipelines.lock().unwrap().get(pipeline_id)
+            .and_then(|weak| weak.upgrade())
+            .ok_or_else(|| Error::Sync(format!("Video {pipeline_id} is not synced")))?;
+        let pipeline = video_pipeline.lock().unwrap();
+        let position = pipeline.get_clamped_position(position);
+        pipeline.seek_clamped(position)?;
+        drop(pipeline);
+
+        //Expected positions restart from now so the moved video isn't treated as drifting
+        if imp.playback_anchor.borrow().is_some() {
+            let clock_time = self.get_shared_clock_time()?;
+            let mut positions = HashMap::new();
+            for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
+                if video_player_id == pipeline_id {
+                    positions.insert(video_player_id.clone(), position);
+                } else if let Some(other) = pipeline_weak.upgrade() {
+                    let other_position = other.lock().unwrap().get_position();
+                    if let Some(other_position) = other_position {
+                        positions.insert(video_player_id.clone(), other_position);
+                    }
+                }
+            }
+            imp.playback_anchor.replace(Some(PlaybackAnchor { clock_time, positions }));
+            if let Some(stats) = imp.drift_stats.borrow_mut().get_mut(pipeline_id) {
+                stats.correction = DriftCorrection::None;
+                stats.cooldown = DRIFT_SEEK_COOLDOWN_CHECKS;
+            }
+        }
+        Ok(())
+    }
+
+    //Checks that every synced video has a value before acting on any of them
+    fn check_all_present<T>(&self, values: &HashMap<String, T>, what: &str) -> Result<(), Error> {
+        let imp = self.imp();
+        let mut missing: Vec<String> = imp.pipelines.lock().unwrap().keys()
+            .filter(|id| !values.contains_key(id.as_str()))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort();
+        Err(Error::Sync(format!("No {what} for video {}", missing.join(", "))))
+    }
+
+    //Combines the failures of each video into one error
+    fn collect_failures(action: &str, failures: Vec<String>) -> Result<(), Error> {
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Sync(format!("Failed to {action} {}", failures.join("; "))))
+        }
+    }
+
+    //Gets the current time of the shared clock
+    fn get_shared_clock_time(&self) -> Result<ClockTime, Error> {
+        self.imp().shared_clock.get()
+            .and_then(|clock| clock.time())
+            .ok_or_else(|| Error::Sync("Shared clock has no time".to_string()))
+    }
+
+    //Not currently being used
+    pub fn sync_clocks(&self) -> Result<(), Error> {
+        let sync_time = self.get_shared_clock_time()?;
         self.emit_event(SyncEvent::SyncEnabled { base_time: sync_time });
         Ok(())
     }
 
     //Restores individual clocks to the pipelines
-    pub fn unsync_clocks(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn unsync_clocks(&self) -> Result<(), Error> {
         let imp = self.imp();
-        self.pause_videos();
+        self.pause_videos()?;
 
         //Wait for each video that finish switching to paused state
         for pipeline_weak in imp.pipelines.lock().unwrap().values() {
@@ -233,7 +384,7 @@ impl SyncManager {
         for (id, pipeline_weak)  in imp.pipelines.lock().unwrap().iter() {
             if let Some(video_pipeline) = pipeline_weak.upgrade() {
                 let pipeline = video_pipeline.lock().unwrap().pipeline().unwrap();
-                println!("RESETING CLOCK");
+                log_debug!("Restoring the individual clock of pipeline {id}");
                 
                 //reset internal state and wait for state change
                 let _ = pipeline.set_state(gstreamer::State::Null);
@@ -260,6 +411,143 @@ impl SyncManager {
         Ok(())
     }
 
+    //Gets how far a playing video is ahead of where the shared clock says it should be
+    //Output: drift in nanoseconds, negative when behind, None unless the video is playing under shared control
+    pub fn get_drift(&self, pipeline_id: &str) -> Option<i64> {
+        let imp = self.imp();
+        let anchor = imp.playback_anchor.borrow().clone()?;
+        let start_position = *anchor.positions.get(pipeline_id)?;
+        let video_pipeline = imp.pipelines.lock().unwrap().get(pipeline_id)?.upgrade()?;
+        let video_pipeline = video_pipeline.lock().unwrap();
+        if video_pipeline.pipeline()?.current_state() != gstreamer::State::Playing {
+            return None;
+        }
+        let position = video_pipeline.get_position()?;
+        let elapsed = self.get_shared_clock_time().ok()?.saturating_sub(anchor.clock_time);
+        let expected = start_position + elapsed;
+        Some(position.nseconds() as i64 - expected.nseconds() as i64)
+    }
+
+    //Gets the drift statistics of a video since shared playback last started
+    pub fn get_drift_stats(&self, pipeline_id: &str) -> Option<DriftStats> {
+        self.imp().drift_stats.borrow().get(pipeline_id).cloned()
+    }
+
+    //Starts checking that the videos stay in line with each other while they play
+    fn start_drift_monitor(&self) {
+        let imp = self.imp();
+        self.stop_drift_monitor();
+        imp.drift_stats.borrow_mut().clear();
+        let source = glib::timeout_add_local(Duration::from_millis(DRIFT_CHECK_INTERVAL_MS), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                this.check_drift();
+                glib::ControlFlow::Continue
+            }
+        ));
+        imp.drift_monitor.replace(Some(source));
+    }
+
+    //Stops checking drift and returns nudged videos to normal speed
+    fn stop_drift_monitor(&self) {
+        let imp = self.imp();
+        if let Some(source) = imp.drift_monitor.borrow_mut().take() {
+            source.remove();
+        }
+        for (video_player_id, stats) in imp.drift_stats.borrow_mut().iter_mut() {
+            if let DriftCorrection::RateNudge(_) = stats.correction {
+                stats.correction = DriftCorrection::None;
+                let pipeline = imp.pipelines.lock().unwrap().get(video_player_id).and_then(|weak| weak.upgrade());
+                if let Some(pipeline) = pipeline {
+                    if let Err(e) = pipeline.lock().unwrap().set_playback_rate(1.0) {
+                        log_warn!("Failed to restore the speed of video {video_player_id}: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    //Compares each playing video to the others and corrects the ones that drifted away
+    //Drift is compared to the median so a delay shared by every video, such as startup latency, isn't corrected
+    fn check_drift(&self) {
+        let imp = self.imp();
+        let ids: Vec<String> = imp.pipelines.lock().unwrap().keys().cloned().collect();
+        let drifts: Vec<(String, i64)> = ids.into_iter()
+            .filter_map(|id| self.get_drift(&id).map(|drift| (id, drift)))
+            .collect();
+        if drifts.is_empty() {
+            return;
+        }
+        let mut sorted: Vec<i64> = drifts.iter().map(|(_, drift)| *drift).collect();
+        sorted.sort_unstable();
+        let median = sorted[(sorted.len() - 1) / 2];
+
+        let mut all_stats = imp.drift_stats.borrow_mut();
+        for (video_player_id, drift) in drifts {
+            let stats = all_stats.entry(video_player_id.clone()).or_default();
+            stats.drift = drift;
+            stats.relative_drift = drift - median;
+            stats.max_relative_drift = stats.max_relative_drift.max(stats.relative_drift.unsigned_abs());
+            if stats.cooldown > 0 {
+                stats.cooldown -= 1;
+                continue;
+            }
+            match self.correct_drift(&video_player_id, stats.relative_drift, stats.correction) {
+                Ok(correction) => {
+                    let changed = correction != stats.correction;
+                    if changed && correction != DriftCorrection::None {
+                        stats.corrections += 1;
+                        log_debug!("Correcting drift of video {video_player_id} ({} ns) with {correction:?}", stats.relative_drift);
+                    }
+                    if correction == DriftCorrection::Seek {
+                        stats.cooldown = DRIFT_SEEK_COOLDOWN_CHECKS;
+                    }
+                    stats.correction = correction;
+                }
+                Err(e) => log_warn!("Failed to correct drift of video {video_player_id}: {e}"),
+            }
+        }
+        let stats = all_stats.clone();
+        drop(all_stats);
+        self.emit_event(SyncEvent::DriftReport { stats });
+    }
+
+    //Seeks a video back in line when it drifted far, otherwise nudges its speed until it catches up
+    //Input: video id, drift compared to the other videos, correction currently applied
+    //Output: correction applied after this check
+    fn correct_drift(&self, pipeline_id: &str, relative_drift: i64, current: DriftCorrection) -> Result<DriftCorrection, Error> {
+        let imp = self.imp();
+        let video_pipeline = imp.pipelines.lock().unwrap().get(pipeline_id)
+            .and_then(|weak| weak.upgrade())
+            .ok_or_else(|| Error::Sync(format!("Video {pipeline_id} was closed")))?;
+        let pipeline = video_pipeline.lock().unwrap();
+        let distance = relative_drift.unsigned_abs();
+        if distance >= DRIFT_SEEK_THRESHOLD {
+            let position = pipeline.get_position()
+                .ok_or_else(|| Error::Sync(format!("Video {pipeline_id} has no position")))?;
+            let target = (position.nseconds() as i64 - relative_drift).max(0) as u64;
+            pipeline.seek_clamped(ClockTime::from_nseconds(target))?;
+            Ok(DriftCorrection::Seek)
+        } else if distance >= DRIFT_NUDGE_THRESHOLD {
+            //Videos behind speed up and videos ahead slow down
+            let rate = if relative_drift < 0 { 1.0 + DRIFT_NUDGE_RATE } else { 1.0 - DRIFT_NUDGE_RATE };
+            if current != DriftCorrection::RateNudge(rate) {
+                pipeline.set_playback_rate(rate)?;
+            }
+            Ok(DriftCorrection::RateNudge(rate))
+        } else if let DriftCorrection::RateNudge(_) = current {
+            if distance <= DRIFT_SETTLED_THRESHOLD {
+                pipeline.set_playback_rate(1.0)?;
+                Ok(DriftCorrection::None)
+            } else {
+                Ok(current)
+            }
+        } else {
+            Ok(DriftCorrection::None)
+        }
+    }
+
     pub fn get_shared_clock(&self) -> Clock {
         let imp = self.imp();
         imp.shared_clock.get().unwrap().clone()
@@ -288,7 +576,7 @@ impl SyncManager {
         if let Some((video_pipeline_arc, video_player_id, longest_duration)) = self.get_longest_pipeline(offsets) {
             let video_pipeline = video_pipeline_arc.lock().unwrap();
             let start_time = offsets[video_player_id.as_str()];
-            let current_position = video_pipeline.get_position().unwrap().nseconds().saturating_sub(start_time);
+            let current_position = video_pipeline.get_position().unwrap_or(ClockTime::ZERO).nseconds().saturating_sub(start_time);
             let percent = current_position as f64 / longest_duration as f64;
             // let logical_duration = self.get_logical_duration()?.nseconds();
             // let start_time = self.get_start()?;
@@ -307,11 +595,20 @@ impl SyncManager {
         let imp = self.imp();
         let mut longest_pipeline: Option<(Arc<Mutex<VideoPipeline>>, String, u64)> = None; 
         for (video_player_id, pipeline_weak) in imp.pipelines.lock().unwrap().iter() {
-            let video_pipeline_arc = pipeline_weak.upgrade().unwrap();
+            let video_pipeline_arc = match pipeline_weak.upgrade() {
+                Some(p) => p,
+                None => continue,
+            };
             let video_pipeline = video_pipeline_arc.lock().unwrap();
-            let offset = offsets[video_player_id];
+            let offset = match offsets.get(video_player_id) {
+                Some(offset) => *offset,
+                None => continue,
+            };
             //let duration = video_pipeline.get_logical_duration();
-            let duration = video_pipeline.get_end().unwrap().nseconds() - offset;
+            let duration = match video_pipeline.get_end() {
+                Ok(end) => end.nseconds().saturating_sub(offset),
+                Err(_) => continue,
+            };
             if let Some((_, _, longest_duration)) = longest_pipeline.clone() {
                 if duration > longest_duration {
                     longest_pipeline = Some((video_pipeline_arc.clone(), video_player_id.to_string(), duration));
@@ -0,0 +1,180 @@
+use gtk::cairo;
+use std::f64::consts::PI;
+
+//Drawings within this distance of a frame's time belong to that frame
+const SAME_FRAME_TOLERANCE: u64 = 1_000_000;
+//Color drawings are made in
+const DRAWING_COLOR: (f64, f64, f64) = (1.0, 0.85, 0.0);
+
+//Tool picked for drawing on a paused frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DrawingTool {
+    #[default]
+    Off,
+    Arrow,
+    Circle,
+    Text,
+}
+
+impl DrawingTool {
+    //Gets the tool for the position of an entry in the drawing tool drop down
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => DrawingTool::Arrow,
+            2 => DrawingTool::Circle,
+            3 => DrawingTool::Text,
+            _ => DrawingTool::Off,
+        }
+    }
+}
+
+//Point as a fraction of the frame width and height, so drawings follow the frame when the player is resized
+pub type FramePoint = (f64, f64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Arrow { start: FramePoint, end: FramePoint },
+    //Circle through the edge point
+    Circle { center: FramePoint, edge: FramePoint },
+    Text { position: FramePoint, text: String },
+}
+
+//Shapes drawn on one frame, shown from its time until the hold duration has passed
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameDrawing {
+    pub time: u64,
+    pub hold: u64,
+    pub shapes: Vec<Shape>,
+}
+
+impl FrameDrawing {
+    fn is_at(&self, time: u64) -> bool {
+        self.time.abs_diff(time) <= SAME_FRAME_TOLERANCE
+    }
+
+    fn is_shown_at(&self, position: u64) -> bool {
+        self.is_at(position) || (position > self.time && position - self.time <= self.hold)
+    }
+}
+
+//Drawings of a video, ordered by time
+#[derive(Default)]
+pub struct FrameDrawings {
+    drawings: Vec<FrameDrawing>,
+}
+
+impl FrameDrawings {
+    //Adds a shape to the drawing of the frame at a time
+    //Inputs: time of the frame, how long the drawing stays on screen during playback, shape
+    pub fn add_shape(&mut self, time: u64, hold: u64, shape: Shape) {
+        if let Some(drawing) = self.drawings.iter_mut().find(|drawing| drawing.is_at(time)) {
+            drawing.hold = hold;
+            drawing.shapes.push(shape);
+            return;
+        }
+        let index = self.drawings.partition_point(|drawing| drawing.time < time);
+        self.drawings.insert(index, FrameDrawing { time, hold, shapes: vec![shape] });
+    }
+
+    //Removes the drawing of the frame at a time
+    //Output: whether there was a drawing to remove
+    pub fn clear_at(&mut self, time: u64) -> bool {
+        let count = self.drawings.len();
+        self.drawings.retain(|drawing| !drawing.is_at(time));
+        self.drawings.len() != count
+    }
+
+    //Gets the shapes of every drawing on screen at a position
+    pub fn shapes_at(&self, position: u64) -> Vec<Shape> {
+        self.drawings.iter()
+            .filter(|drawing| drawing.is_shown_at(position))
+            .flat_map(|drawing| drawing.shapes.iter().cloned())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drawings.is_empty()
+    }
+}
+
+//Part of a widget the video frame is shown in
+#[derive(Clone, Copy, Debug)]
+pub struct FrameArea {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl FrameArea {
+    //Gets the frame point under a widget position, None outside the frame
+    pub fn frame_point_at(&self, x: f64, y: f64) -> Option<FramePoint> {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return None;
+        }
+        let point = ((x - self.x) / self.width, (y - self.y) / self.height);
+        if (0.0..=1.0).contains(&point.0) && (0.0..=1.0).contains(&point.1) {
+            Some(point)
+        } else {
+            None
+        }
+    }
+
+    //Gets the frame point nearest to a widget position
+    pub fn clamped_frame_point_at(&self, x: f64, y: f64) -> FramePoint {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (((x - self.x) / self.width).clamp(0.0, 1.0), ((y - self.y) / self.height).clamp(0.0, 1.0))
+    }
+
+    fn widget_point(&self, point: FramePoint) -> (f64, f64) {
+        (self.x + point.0 * self.width, self.y + point.1 * self.height)
+    }
+}
+
+//Draws shapes over the frame, sizes follow the frame so drawings look the same at any player size
+pub fn draw_shapes(cr: &cairo::Context, area: &FrameArea, shapes: &[Shape]) {
+    let scale = area.width.min(area.height);
+    let line_width = (scale * 0.006).max(2.0);
+    let (red, green, blue) = DRAWING_COLOR;
+    cr.set_source_rgb(red, green, blue);
+    cr.set_line_width(line_width);
+    cr.set_line_cap(cairo::LineCap::Round);
+    cr.set_line_join(cairo::LineJoin::Round);
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size((scale * 0.05).max(12.0));
+
+    for shape in shapes {
+        match shape {
+            Shape::Arrow { start, end } => {
+                let (start_x, start_y) = area.widget_point(*start);
+                let (end_x, end_y) = area.widget_point(*end);
+                cr.move_to(start_x, start_y);
+                cr.line_to(end_x, end_y);
+                //Head of the arrow at the end point
+                let angle = (end_y - start_y).atan2(end_x - start_x);
+                let head_length = line_width * 5.0;
+                for side in [-1.0, 1.0] {
+                    let head_angle = angle + PI + side * PI / 6.0;
+                    cr.move_to(end_x, end_y);
+                    cr.line_to(end_x + head_length * head_angle.cos(), end_y + head_length * head_angle.sin());
+                }
+                let _ = cr.stroke();
+            }
+            Shape::Circle { center, edge } => {
+                let (center_x, center_y) = area.widget_point(*center);
+                let (edge_x, edge_y) = area.widget_point(*edge);
+                let radius = (edge_x - center_x).hypot(edge_y - center_y);
+                cr.new_sub_path();
+                cr.arc(center_x, center_y, radius, 0.0, 2.0 * PI);
+                let _ = cr.stroke();
+            }
+            Shape::Text { position, text } => {
+                let (x, y) = area.widget_point(*position);
+                cr.move_to(x, y);
+                let _ = cr.show_text(text);
+            }
+        }
+    }
+}
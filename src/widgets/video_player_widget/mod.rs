@@ -1 +1,2 @@
 pub mod video_player;
+pub mod frame_drawing;
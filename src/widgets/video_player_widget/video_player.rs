@@ -8,8 +8,14 @@ use gtk::CssProvider;
 use gtk::gdk::Display;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
-use crate::video_pipeline::VideoPipeline;
+use crate::error::Error;
+use crate::video_pipeline::{missing_plugin_hint, PipelineEvent, VideoPipeline};
+use crate::waveform::{generate_waveform, WaveformData};
+use crate::thumbnails::{generate_thumbnails, ThumbnailData};
+use crate::proxy::{generate_proxy, ProxyData};
+use crate::frame_index::{generate_frame_index, FrameIndex};
 use crate::widgets::split_panel::timeentry::TimeEntry;
+use crate::widgets::split_panel::splits::VideoSegment;
 use std::rc::Rc;
 use std::cell::{Cell, RefCell};
 use once_cell::sync::Lazy;
@@ -18,9 +24,12 @@ use glib::{WeakRef, clone::Downgrade, clone::Upgrade};
 use std::time::Instant;
 use gstreamer::ClockTime;
 use crate::helpers::format::format_clock;
+use crate::helpers::data::{get_data, store_data};
+use gtk::FlowBoxChild;
+use crate::widgets::video_player_widget::frame_drawing::{draw_shapes, DrawingTool, FrameArea, FrameDrawings, FramePoint, Shape};
 
 mod imp {
-    use gtk::{Box, Button, Label, Picture};
+    use gtk::{Box, Button, Label, Picture, Scale};
     use glib::subclass::Signal;
 
 
@@ -45,6 +54,44 @@ mod imp {
         pub last_click: Rc<RefCell<Option<Instant>>>,
 
         pub state: Rc<RefCell<Option<gstreamer::State>>>,
+
+        pub detached_window: RefCell<Option<gtk::Window>>,
+
+        pub flowbox_slot: RefCell<Option<WeakRef<FlowBoxChild>>>,
+
+        pub detached_for_fullscreen: Cell<bool>,
+
+        pub volume: Cell<f64>,
+
+        pub muted: Cell<bool>,
+
+        pub waveform: RefCell<Option<Arc<Mutex<WaveformData>>>>,
+
+        pub thumbnails: RefCell<Option<Arc<Mutex<ThumbnailData>>>>,
+
+        pub cached_frame_timeout: RefCell<Option<glib::SourceId>>,
+
+        pub frame_index: RefCell<Option<Arc<Mutex<FrameIndex>>>>,
+
+        pub source_path: RefCell<Option<String>>,
+
+        pub proxy: RefCell<Option<Arc<Mutex<ProxyData>>>>,
+
+        pub proxy_timeout: RefCell<Option<glib::SourceId>>,
+
+        pub using_proxy: Cell<bool>,
+        //Set while the shared seek bar controls the video, the source can't be switched meanwhile
+        pub shared_control: Cell<bool>,
+
+        pub drawings: RefCell<FrameDrawings>,
+
+        pub drawing_tool: Cell<DrawingTool>,
+
+        //Shape being dragged out, shown until the drag ends
+        pub drawing_preview: RefCell<Option<Shape>>,
+
+        //Position the drawings are shown for
+        pub drawing_position: Cell<u64>,
         
         #[template_child]
         pub vbox: TemplateChild<Box>,
@@ -55,9 +102,30 @@ mod imp {
         #[template_child]
         pub text_view: TemplateChild<Label>,
 
+        #[template_child]
+        pub message_revealer: TemplateChild<gtk::Revealer>,
+
+        #[template_child]
+        pub message_box: TemplateChild<Box>,
+
+        #[template_child]
+        pub message_label: TemplateChild<Label>,
+
+        #[template_child]
+        pub message_hint: TemplateChild<Label>,
+
+        #[template_child]
+        pub message_close_button: TemplateChild<Button>,
+
         #[template_child]
         pub picture: TemplateChild<Picture>,
 
+        #[template_child]
+        pub drawing_area: TemplateChild<gtk::DrawingArea>,
+
+        #[template_child]
+        pub overlay_label: TemplateChild<Label>,
+
         #[template_child]
         pub seek_bar: TemplateChild<SeekBar>,
 
@@ -87,6 +155,39 @@ mod imp {
 
         #[template_child]
         pub toggle_mute_button: TemplateChild<Button>,
+
+        #[template_child]
+        pub volume_scale: TemplateChild<Scale>,
+
+        #[template_child]
+        pub solo_button: TemplateChild<Button>,
+
+        #[template_child]
+        pub filmstrip_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub drawing_tool_dropdown: TemplateChild<gtk::DropDown>,
+
+        #[template_child]
+        pub drawing_hold_spin: TemplateChild<gtk::SpinButton>,
+
+        #[template_child]
+        pub clear_drawing_button: TemplateChild<Button>,
+
+        #[template_child]
+        pub proxy_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub vfr_warning: TemplateChild<Label>,
+
+        #[template_child]
+        pub proxy_progress: TemplateChild<gtk::ProgressBar>,
+
+        #[template_child]
+        pub detach_button: TemplateChild<Button>,
+
+        #[template_child]
+        pub fullscreen_button: TemplateChild<Button>,
     }
     
     #[gtk::glib::object_subclass]
@@ -105,15 +206,6 @@ mod imp {
     }
 
     impl VideoPlayer {
-        //Sets up seek bar widget
-        fn setup_seek_bar(&self) {
-            println!("Setting up seek bar!");
-
-            let adjustment = gtk::Adjustment::new(0.0, 0.0, 100.0, 1.0, 0.0, 0.0);
-            let scale = self.seek_bar.get_scale();
-            scale.set_adjustment(&adjustment);
-        }
-
         //Enable/Disables video player user controls
         fn set_controls(&self, status: bool) {
             self.next_frame_button.set_sensitive(status);
@@ -130,29 +222,56 @@ mod imp {
         
         //Clean up video player before disposal
         pub fn cleanup(&self) {
-            println!("cleaning up Video Player");
+            log_debug!("Cleaning up video player");
+            //Closes the detached window if the video player is popped out
+            if let Some(window) = self.detached_window.borrow_mut().take() {
+                window.set_child(None::<&gtk::Widget>);
+                window.destroy();
+            }
             //Removes scale updating timeout 
             if let Some(timeout) = self.timeout_id.borrow_mut().take() {
                 timeout.remove();
             }
+            //Stops waiting on a cached frame
+            if let Some(timeout) = self.cached_frame_timeout.borrow_mut().take() {
+                timeout.remove();
+            }
+            //Stops indexing frames
+            if let Some(frame_index) = self.frame_index.borrow_mut().take() {
+                frame_index.lock().unwrap().cancelled = true;
+            }
+            //Stops any proxy still being generated
+            if let Some(timeout) = self.proxy_timeout.borrow_mut().take() {
+                timeout.remove();
+            }
+            if let Some(proxy) = self.proxy.borrow_mut().take() {
+                proxy.lock().unwrap().cancelled = true;
+            }
+            //Stops any waveform still being computed
+            if let Some(waveform) = self.waveform.borrow_mut().take() {
+                waveform.lock().unwrap().cancelled = true;
+            }
+            //Stops any thumbnails still being generated
+            if let Some(thumbnails) = self.thumbnails.borrow_mut().take() {
+                thumbnails.lock().unwrap().cancelled = true;
+            }
             //disposes of the associated pipeline
             if let Ok(mut pipeline) = self.gstreamer_manager.lock() {
-                println!("pipeline cleanup");
+                log_debug!("Cleaning up pipeline");
                 pipeline.cleanup();
             } else {
-                eprintln!("Can't cleanup gstreamer_manager");
+                log_error!("Can't cleanup gstreamer_manager");
             }
         }
     }
     
     impl ObjectImpl for VideoPlayer {
         fn dispose(&self) {
-            println!("disposing Video Player");
+            log_debug!("Disposing video player");
         }
 
         //Sets up initial state of the video player
         fn constructed(&self) {
-            self.setup_seek_bar();
             self.set_controls(false);
             self.set_scale_interation(false);
         }
@@ -181,10 +300,68 @@ mod imp {
                     Signal::builder("remove-video-player")
                     .flags(glib::SignalFlags::RUN_LAST)
                     .build(),
+                    Signal::builder("solo-button-clicked")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type()])
+                    .build(),
+                    Signal::builder("clamp-end-reached")
+                    .flags(glib::SignalFlags::RUN_LAST)
+                    .param_types([String::static_type()])
+                    .build(),
                     ]
                 });
             SIGNALS.as_ref()
         }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecDouble::builder("volume")
+                        .nick("Volume")
+                        .blurb("Audio volume of the video")
+                        .minimum(0.0)
+                        .maximum(1.0)
+                        .default_value(1.0)
+                        .flags(glib::ParamFlags::READWRITE)
+                        .build(),
+                    glib::ParamSpecBoolean::builder("muted")
+                        .nick("Muted")
+                        .blurb("Whether the audio of the video is muted")
+                        .default_value(false)
+                        .flags(glib::ParamFlags::READWRITE)
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "volume" => self.volume.get().to_value(),
+                "muted" => self.muted.get().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "volume" => {
+                    let volume = value.get::<f64>().unwrap();
+                    self.volume.set(volume);
+                    if let Ok(pipeline) = self.gstreamer_manager.lock() {
+                        pipeline.set_volume(volume);
+                    }
+                }
+                "muted" => {
+                    let muted = value.get::<bool>().unwrap();
+                    self.muted.set(muted);
+                    if let Ok(pipeline) = self.gstreamer_manager.lock() {
+                        pipeline.set_muted(muted);
+                    }
+                }
+                _ => unimplemented!(),
+            }
+        }
     }
     impl WidgetImpl for VideoPlayer {}
     impl BoxImpl for VideoPlayer {}
@@ -215,37 +392,46 @@ impl VideoPlayer {
         *imp.state.borrow_mut() = Some(gstreamer::State::Paused);
         *imp.debouce_duration.borrow_mut() = Duration::from_millis(200);
         *imp.last_click.borrow_mut() = None;
+        imp.volume.set(1.0);
+        imp.muted.set(false);
 
-        println!("created video player widget");
+        log_debug!("Created video player widget");
         widget
     }
 
     // Controls automatic seek bar movement while video is playing
-    fn start_updating_scale(&self, scale: &gtk::Scale) {
-        println!("Starting to update the seek bar");
+    fn start_updating_scale(&self, seek_bar: &SeekBar) {
+        log_debug!("Starting to update the seek bar");
         let imp = imp::VideoPlayer::from_obj(self);
         let gstman_weak = Arc::downgrade(&imp.gstreamer_manager);
-        let seek_bar_clone = scale.clone();
+        let seek_bar_clone = seek_bar.clone();
         let timestamp_label = imp.video_position.clone();
+        let vfr_warning = imp.vfr_warning.clone();
         let is_dragging_clone = imp.is_dragging.clone();
+        let this_weak = self.downgrade();
         // Sets up timeout to update the seekbar every 100 milliseconds
         let source_id = timeout_add_local(Duration::from_millis(100), move || {
             // Skips update if user is moving the seek bar
             if is_dragging_clone.get() {
-                println!("Dragging, skipping update scale");
+                log_trace!("Dragging, skipping update scale");
                 return glib::ControlFlow::Continue
             }
             // Updates the seek bar based on the videos position
             if let Some(gstman) = gstman_weak.upgrade() {
                 if let Ok(pipeline) = gstman.lock() {
-                    if let Ok(new_value) = pipeline.position_to_percent() {
-                        seek_bar_clone.set_value(new_value);
-                    }
                     if let Some(position) = pipeline.get_position() {
                         let nanos = position.nseconds();
+                        seek_bar_clone.set_time(nanos);
+                        if let Some(this) = this_weak.upgrade() {
+                            this.update_drawing_position(nanos);
+                        }
                         let formatted_time = format_clock(nanos);
-                        timestamp_label.set_label(&format!("Position: {formatted_time}"));
+                        match pipeline.get_current_frame() {
+                            Some(frame) => timestamp_label.set_label(&format!("Position: {formatted_time} (Frame {frame})")),
+                            None => timestamp_label.set_label(&format!("Position: {formatted_time}")),
+                        }
                     }
+                    vfr_warning.set_visible(pipeline.is_variable_frame_rate());
                 }
             }
             glib::ControlFlow::Continue
@@ -259,13 +445,13 @@ impl VideoPlayer {
         let gstman_weak = Arc::downgrade(&imp.gstreamer_manager);
         if let Some(gstman) = gstman_weak.upgrade() {
             if let Ok(pipeline) = gstman.lock() {
-                // gets seek bar progress 0.0 - 1.0
-                let percent = (imp.seek_bar.get_scale().value() / 100.0) as f64;
-                // Gets precentage time in nanoseconds of the total videos duration
-                let position = pipeline.percent_to_position(percent).expect("Failed to get position");
-                println!("Position: {position}");
+                // Gets seek bar position in nanoseconds
+                let position = imp.seek_bar.get_time();
+                log_trace!("Seek bar position: {position}");
                 // Updates the video players position from acquired position
-                pipeline.seek_position(gstreamer::ClockTime::from_nseconds(position)).expect("Failed to seek position");
+                if let Err(e) = pipeline.seek_position(gstreamer::ClockTime::from_nseconds(position)) {
+                    self.report_error(&e);
+                }
             }
         }
     }
@@ -281,7 +467,7 @@ impl VideoPlayer {
             move |_,_,_x,_y| {
                 //println!("---------------------Left click Begin at: x: {x}, y: {y}");
                 is_dragging_weak.set(true);
-                println!("emiiiittttttting seek-bar-pressed");
+                log_trace!("Emitting seek-bar-pressed");
                 this.emit_by_name::<()>("seek-bar-pressed", &[]);
             }
         ));
@@ -297,7 +483,7 @@ impl VideoPlayer {
         ));
 
         gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
-        scale_box.add_controller(gesture);
+        scale_box.get_scale().add_controller(gesture);
     }
 
     //Dynamically load css for video player
@@ -309,7 +495,7 @@ impl VideoPlayer {
                 provider.load_from_file(&file);
             }
             Err(e) => {
-                eprintln!("Failed to get current working directory to load css ({e})");
+                log_warn!("Failed to get current working directory to load css ({e})");
             }
         }
         if let Some(display) = Display::default() {
@@ -317,9 +503,98 @@ impl VideoPlayer {
         }
     }
 
-    pub fn load_file(&self, window: gtk::ApplicationWindow) {
+    //Opens a video file in the video player
+    //Input: path of the video file
+    pub fn load_path(&self, path: &str) {
         let imp = self.imp();
-        let gstman_weak = Arc::downgrade(&imp.gstreamer_manager);
+        self.set_controls(false);
+        self.set_scale_interation(false);
+        log_info!("Opening {path}");
+        imp.text_view.set_label(path);
+        if let Ok(mut pipeline) = imp.gstreamer_manager.lock() {
+            //Builds pipeline from selected file
+            pipeline.reset();
+            self.hide_message();
+            //Reports pipeline errors and warnings on the video player
+            pipeline.set_event_callback(glib::clone!(
+                #[weak(rename_to = this)] self,
+                move |event| {
+                    this.handle_pipeline_event(event);
+                }
+            ));
+            //pipeline.build_pipeline(Some(&file_uri.to_string()));
+            let build_result = pipeline.build_pipeline(Some(path))
+                .and_then(|_| pipeline.get_length().ok_or_else(|| Error::Pipeline("Could not read the length of the video".to_string())));
+            let timeline_length = match build_result {
+                Ok(length) => length,
+                Err(e) => {
+                    //Keeps the player open showing the problem so it can be removed or another file opened
+                    self.report_error(&e);
+                    return;
+                }
+            };
+            //Restores the video players audio settings on the new pipeline
+            pipeline.set_volume(imp.volume.get());
+            pipeline.set_muted(imp.muted.get());
+            //Sets gstreamers paintable element to picture widget
+            let paintable = pipeline.get_paintable();
+            imp.picture.set_paintable(Some(&paintable));
+            //Shows frames from the frame cache while stepping backward
+            pipeline.set_cached_frame_callback(glib::clone!(
+                #[weak(rename_to = this)] self,
+                move |sample| {
+                    this.show_cached_frame(&sample);
+                }
+            ));
+
+            //A new file starts on the original video
+            self.reset_proxy();
+            *imp.source_path.borrow_mut() = Some(path.to_string());
+
+            //Sets up initial seek bar state
+            imp.seek_bar.reset_zoom();
+            imp.seek_bar.set_timeline_length(timeline_length);
+            self.start_updating_scale(&imp.seek_bar);
+            let nanos: &dyn ToValue = &timeline_length;
+            
+            //Reset pipeline clamp
+            if let Err(e) = pipeline.reset_clamps() {
+                self.report_error(&e);
+            }
+
+            //Computes the audio waveform in the background for the new file
+            let waveform = generate_waveform(path);
+            if let Some(old_waveform) = imp.waveform.replace(Some(waveform.clone())) {
+                old_waveform.lock().unwrap().cancelled = true;
+            }
+            imp.seek_bar.add_waveform_lane(self.get_id(), waveform, "silver", TimeEntry::new(0));
+
+            //Indexes the timestamp of every frame in the background
+            let frame_index = generate_frame_index(path);
+            if let Some(old_frame_index) = imp.frame_index.replace(Some(frame_index.clone())) {
+                old_frame_index.lock().unwrap().cancelled = true;
+            }
+            pipeline.set_frame_index(frame_index);
+
+            //Generates hover preview thumbnails in the background
+            let thumbnails = generate_thumbnails(path);
+            if let Some(old_thumbnails) = imp.thumbnails.replace(Some(thumbnails.clone())) {
+                old_thumbnails.lock().unwrap().cancelled = true;
+            }
+            imp.seek_bar.add_preview_source(self.get_id(), thumbnails, "silver", TimeEntry::new(0));
+            
+            self.emit_by_name::<()>("timeline-length-acquired", &[nanos]);
+            
+            //Enable user control
+            self.set_controls(true);
+            self.set_scale_interation(true);
+        } else {
+            log_error!("Failed to aquire lock on Video pipeline");
+        }
+        self.emit_by_name::<()>("pipeline-built", &[]);
+    }
+
+    pub fn load_file(&self, window: gtk::ApplicationWindow) {
         // File Chooser / Open file button
         self.set_controls(false);
         self.set_scale_interation(false);
@@ -342,55 +617,18 @@ impl VideoPlayer {
             dialog.set_transient_for(Some(&window));
         }
 
-        let gstman_weak_clone = gstman_weak.clone();
         dialog.run_async(glib::clone!(
-            #[weak(rename_to = text)] imp.text_view,
-            #[weak(rename_to = pic)] imp.picture,
             #[weak(rename_to = this)] self,
-            #[weak(rename_to = seekbar)] imp.seek_bar,
             move |obj, res| {
                 match res {
                     gtk::ResponseType::Accept => {
-                        println!("Accepted");
-                        if let Some(file) = obj.file() {
-                            //let file_uri = gtk::gio::File::uri(&file);
-                            let path_str = file.path().unwrap();
-                            println!("File accepted: {}", path_str.to_string_lossy());
-                            text.set_label(&path_str.to_string_lossy());
-                            if let Some(gstman) = gstman_weak_clone.upgrade() {
-                                if let Ok(mut pipeline) = gstman.lock() {
-                                    //Builds pipeline from selected file
-                                    pipeline.reset();
-                                    //pipeline.build_pipeline(Some(&file_uri.to_string()));
-                                    pipeline.build_pipeline(Some(&path_str.to_string_lossy()));
-                                    //Sets gstreamers paintable element to picture widget
-                                    let paintable = pipeline.get_paintable();
-                                    pic.set_paintable(Some(&paintable));
-
-                                    //Sets up initial seek bar state
-                                    let scale = seekbar.get_scale();
-                                    this.start_updating_scale(&scale);
-                                    let timeline_length = pipeline.get_length().unwrap();
-                                    seekbar.set_timeline_length(timeline_length);
-                                    let nanos: &dyn ToValue = &timeline_length;
-                                    
-                                    //Reset pipeline clamp
-                                    pipeline.reset_clamps();
-                                    
-                                    this.emit_by_name::<()>("timeline-length-acquired", &[nanos]);
-                                    
-                                    //Enable user control
-                                    this.set_controls(true);
-                                    this.set_scale_interation(true);
-                                } else {
-                                    eprintln!("Failed to aquire lock on Video pipeline");
-                                }
-                                this.emit_by_name::<()>("pipeline-built", &[]);
-                            }
+                        log_debug!("File chooser accepted");
+                        if let Some(path) = obj.file().and_then(|file| file.path()) {
+                            this.load_path(&path.to_string_lossy());
                         }
                     }
                     _ => {
-                        eprintln!("No file selected removing video player");
+                        log_info!("No file selected removing video player");
                         this.emit_by_name::<()>("remove-video-player", &[]);
                     }
                 }
@@ -402,7 +640,7 @@ impl VideoPlayer {
     pub fn setup_event_handlers(&self) {
         let imp = imp::VideoPlayer::from_obj(self);
 
-        println!("Setting up buttons");
+        log_trace!("Setting up video player buttons");
 
         imp.remove_video_player_button.connect_clicked(glib::clone!(
             #[weak(rename_to = this)] self,
@@ -415,12 +653,15 @@ impl VideoPlayer {
         // Moves video one frame backward
         imp.previous_frame_button.connect_clicked(glib::clone!(
             #[strong] gstman_weak,
+            #[weak(rename_to = this)] self,
             move |_| {
                 if let Some(gstman) = gstman_weak.upgrade() {
                     if let Ok(pipeline) = gstman.lock() {
-                        pipeline.frame_backward();
+                        if let Err(e) = pipeline.frame_backward() {
+                            this.report_error(&e);
+                        }
                     } else {
-                        eprintln!("Failed to aquire lock on Video pipeline");
+                        log_error!("Failed to aquire lock on Video pipeline");
                     }
                 }
             }
@@ -433,13 +674,14 @@ impl VideoPlayer {
             #[weak(rename_to = last_click)] imp.last_click,
             #[strong(rename_to = debounce_duration)] imp.debouce_duration,
             #[weak(rename_to = state)] imp.state,
+            #[weak(rename_to = this)] self,
             move |_| {
                 //Button Debounce
                 let now = Instant::now();
                 let mut last_click = last_click.borrow_mut();
                 if let Some(last_time) = *last_click {
                     if now.duration_since(last_time) < *debounce_duration.borrow() {
-                        println!("Debouncing video player play button");
+                        log_trace!("Debouncing video player play button");
                         return;
                     }
                 }
@@ -451,18 +693,24 @@ impl VideoPlayer {
                 if state.unwrap() == gstreamer::State::Playing {
                     if let Some(gstman) = gstman_weak.upgrade() {
                         if let Ok(pipeline) = gstman.lock() {
-                            pipeline.pause_video();
+                            if let Err(e) = pipeline.pause_video() {
+                                this.report_error(&e);
+                                return;
+                            }
                         } else {
-                            eprintln!("Failed to aquire lock on Video pipeline");
+                            log_error!("Failed to aquire lock on Video pipeline");
                         }
                     }
                     *state = Some(gstreamer::State::Paused);
                 } else if state.unwrap() == gstreamer::State::Paused {
                     if let Some(gstman) = gstman_weak.upgrade() {
                         if let Ok(pipeline) = gstman.lock() {
-                            pipeline.play_video();
+                            if let Err(e) = pipeline.play_video() {
+                                this.report_error(&e);
+                                return;
+                            }
                         } else {
-                            eprintln!("Failed to aquire lock on Video pipeline");
+                            log_error!("Failed to aquire lock on Video pipeline");
                         }
                     }
                     *state = Some(gstreamer::State::Playing);
@@ -474,12 +722,15 @@ impl VideoPlayer {
         // Moves video one frame forward
         imp.next_frame_button.connect_clicked(glib::clone!(
             #[strong] gstman_weak,
+            #[weak(rename_to = this)] self,
             move |_| {
                 if let Some(gstman) = gstman_weak.upgrade() {
                     if let Ok(pipeline) = gstman.lock() {
-                        pipeline.frame_forward();
+                        if let Err(e) = pipeline.frame_forward() {
+                            this.report_error(&e);
+                        }
                     } else {
-                        eprintln!("Failed to aquire lock on Video pipeline");
+                        log_error!("Failed to aquire lock on Video pipeline");
                     }
                 }
             }
@@ -503,10 +754,12 @@ impl VideoPlayer {
                 let pos = match pipeline.get_position() {
                     Some(time) => time,
                     None => {
-                        eprintln!("Failed to get position trying to set split time");
+                        log_warn!("Failed to get position trying to set split time");
                         return;
                     }
                 };
+                //Releases the pipeline so the split can be snapped to the displayed frame
+                drop(pipeline);
 
                 //Emits position on signal for split table to handle
                 let nanos: &dyn ToValue = &pos.nseconds();
@@ -533,10 +786,11 @@ impl VideoPlayer {
                 let pos = match pipeline.get_position() {
                     Some(time) => time,
                     None => {
-                        eprintln!("Failed to get position trying to set the start time offset");
+                        log_warn!("Failed to get position trying to set the start time offset");
                         return;
                     }
                 };
+                drop(pipeline);
 
                 //Emit position on signal for split table to handle
                 let nanos: &dyn ToValue = &pos.nseconds();
@@ -546,22 +800,102 @@ impl VideoPlayer {
         ));
 
         //Toggle audio mute
-        let gstman_weak = Arc::downgrade(&imp.gstreamer_manager);
         imp.toggle_mute_button.connect_clicked(glib::clone!(
-            #[strong] gstman_weak,
+            #[weak(rename_to = this)] self,
             move |_| {
-                let gstman = match gstman_weak.upgrade() {
-                    Some(val) => val,
-                    None => return,
-                };
-                let video_pipeline = match gstman.lock() {
-                    Ok(val) => val,
-                    Err(_) => return,
-                };
-                video_pipeline.toggle_mute();
+                this.set_muted(!this.is_muted());
+            }
+        ));
+
+        //Keeps the mute button label in sync with the muted state
+        self.connect_notify_local(Some("muted"), glib::clone!(
+            #[weak(rename_to = mute_button)] imp.toggle_mute_button,
+            move |this, _| {
+                mute_button.set_label(if this.is_muted() { "Unmute" } else { "Mute" });
+            }
+        ));
+
+        //Volume slider controls the volume property of the video player
+        self.bind_property("volume", &imp.volume_scale.adjustment(), "value")
+            .flags(glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE)
+            .build();
+
+        //Solo button: mutes all other video players
+        imp.solo_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                let id: &dyn ToValue = &this.get_id();
+                this.emit_by_name::<()>("solo-button-clicked", &[id]);
+            }
+        ));
+        
+        //Clicking a split mark moves the video to that split
+        let gstman_weak = Arc::downgrade(&imp.gstreamer_manager);
+        imp.seek_bar.connect_local("mark-clicked", false, glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] None,
+            move |values| {
+                let split_time = values[3].get::<u64>().unwrap();
+                if let Some(gstman) = gstman_weak.upgrade() {
+                    if let Ok(pipeline) = gstman.lock() {
+                        if let Err(e) = pipeline.seek_position(ClockTime::from_nseconds(split_time)) {
+                            this.report_error(&e);
+                        }
+                    }
+                }
+                None
+            }
+        ));
+
+        //Filmstrip button: shows a row of thumbnails under the seek bar
+        imp.filmstrip_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = seek_bar)] imp.seek_bar,
+            move |button| {
+                seek_bar.set_filmstrip_visible(button.is_active());
+            }
+        ));
+
+        //Hides the pipeline message bar
+        imp.message_close_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.hide_message();
+            }
+        ));
+
+        //Switches between the proxy and the original video, generating the proxy the first time
+        imp.proxy_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |button| {
+                if button.is_active() {
+                    this.start_proxy();
+                } else if this.imp().using_proxy.get() {
+                    this.use_original();
+                }
+            }
+        ));
+
+        //Pops the video player out into its own window or back into the main window
+        imp.detach_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                if this.is_detached() {
+                    this.attach();
+                } else {
+                    this.detach();
+                }
+            }
+        ));
+
+        //Toggles fullscreen for the video player
+        imp.fullscreen_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.toggle_fullscreen();
             }
         ));
         
+        self.setup_drawing();
         Self::connect_scale_drag_signals(self,&imp.seek_bar);
         Self::load_css();
         // imp.remove_video_player_button.add_css_class("data-table");
@@ -579,10 +913,17 @@ impl VideoPlayer {
         Arc::downgrade(&imp.gstreamer_manager)
     }
 
+    //Shows the split table segments as bands on the video player's seek bar
+    pub fn connect_segments_to_seekbar(&self, segments: &gio::ListStore, selection: &gtk::SingleSelection) {
+        let imp = self.imp();
+        imp.seek_bar.set_band_source(segments, selection);
+        imp.seek_bar.add_band_lane(self.get_id(), self.get_color().as_str(), TimeEntry::new(0));
+    }
+
     //Connect a split table entry to video player
-    pub fn connect_time_to_seekbar(&self, id: String, time_entry: TimeEntry, color: &str) {
+    pub fn connect_time_to_seekbar(&self, id: String, time_entry: TimeEntry, color: &str, segment: &VideoSegment) {
         let imp = imp::VideoPlayer::from_obj(self);
-        imp.seek_bar.add_mark(id, time_entry, color, TimeEntry::new(0));
+        imp.seek_bar.add_mark(id, time_entry, color, TimeEntry::new(0), self.get_id().as_str(), segment);
     }
 
     //Enable/Disable video player user controls
@@ -595,6 +936,19 @@ impl VideoPlayer {
         imp.split_button.set_sensitive(status);
     }
 
+    //Marks the video player as controlled by the shared seek bar
+    //Switching the source restarts the pipeline, which would lose the base time shared with the other videos
+    //A proxy that became ready meanwhile is switched to once shared control is released
+    pub fn set_shared_control(&self, shared: bool) {
+        let imp = self.imp();
+        imp.shared_control.set(shared);
+        imp.proxy_button.set_sensitive(!shared);
+        let proxy_ready = imp.proxy.borrow().as_ref().is_some_and(|proxy| proxy.lock().unwrap().is_ready());
+        if !shared && proxy_ready && imp.proxy_button.is_active() && !imp.using_proxy.get() {
+            self.use_proxy();
+        }
+    }
+
     //Enable/Disable video player scale interaction
     pub fn set_scale_interation(&self, status: bool) {
         let imp = self.imp();
@@ -626,10 +980,664 @@ impl VideoPlayer {
         imp.color.borrow().to_string()
     }
 
+    //Gets the audio waveform of the loaded file
+    pub fn get_waveform(&self) -> Option<Arc<Mutex<WaveformData>>> {
+        let imp = self.imp();
+        imp.waveform.borrow().clone()
+    }
+
+    //Shows a message from the pipeline above the video
+    //Input: css class of the message (error or warning), message text, optional hint on how to fix it
+    fn show_message(&self, kind: &str, text: &str, hint: Option<&str>) {
+        let imp = self.imp();
+        for class in ["error", "warning"] {
+            imp.message_box.remove_css_class(class);
+        }
+        imp.message_box.add_css_class(kind);
+        imp.message_label.set_label(text);
+        imp.message_hint.set_label(hint.unwrap_or(""));
+        imp.message_hint.set_visible(hint.is_some());
+        imp.message_revealer.set_reveal_child(true);
+    }
+
+    //Shows an error from the pipeline or the split table above the video
+    pub fn report_error(&self, error: &Error) {
+        log_error!("Video player {}: {error}", self.get_id());
+        self.show_message("error", &error.to_string(), None);
+    }
+
+    //Shows text over the top left of the video, hidden when None
+    pub fn set_overlay_text(&self, text: Option<&str>) {
+        let imp = self.imp();
+        match text {
+            Some(text) => {
+                imp.overlay_label.set_label(text);
+                imp.overlay_label.set_visible(true);
+            }
+            None => imp.overlay_label.set_visible(false),
+        }
+    }
+
+    //Sets up drawing arrows, circles and text on the paused frame
+    fn setup_drawing(&self) {
+        let imp = self.imp();
+
+        imp.drawing_area.set_draw_func(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_, cr, _width, _height| {
+                let imp = this.imp();
+                let Some(area) = this.get_frame_area() else {
+                    return;
+                };
+                let mut shapes = imp.drawings.borrow().shapes_at(imp.drawing_position.get());
+                shapes.extend(imp.drawing_preview.borrow().iter().cloned());
+                draw_shapes(cr, &area, &shapes);
+            }
+        ));
+
+        //Clicks only go to the drawing area while a tool is picked
+        imp.drawing_tool_dropdown.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |dropdown| {
+                let imp = this.imp();
+                let tool = DrawingTool::from_index(dropdown.selected());
+                imp.drawing_tool.set(tool);
+                imp.drawing_area.set_can_target(tool != DrawingTool::Off);
+                imp.drawing_area.set_cursor_from_name(if tool == DrawingTool::Off { None } else { Some("crosshair") });
+            }
+        ));
+
+        let drag = gtk::GestureDrag::new();
+        drag.connect_drag_begin(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |gesture, x, y| {
+                if this.is_playing() {
+                    this.report_error(&Error::Pipeline("Pause the video to draw on a frame".to_string()));
+                    gesture.set_state(gtk::EventSequenceState::Denied);
+                    return;
+                }
+                let imp = this.imp();
+                let Some(point) = this.get_frame_area().and_then(|area| area.frame_point_at(x, y)) else {
+                    gesture.set_state(gtk::EventSequenceState::Denied);
+                    return;
+                };
+                let preview = match imp.drawing_tool.get() {
+                    DrawingTool::Arrow => Some(Shape::Arrow { start: point, end: point }),
+                    DrawingTool::Circle => Some(Shape::Circle { center: point, edge: point }),
+                    DrawingTool::Text | DrawingTool::Off => None,
+                };
+                *imp.drawing_preview.borrow_mut() = preview;
+            }
+        ));
+
+        drag.connect_drag_update(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |gesture, offset_x, offset_y| {
+                let imp = this.imp();
+                let (Some((start_x, start_y)), Some(area)) = (gesture.start_point(), this.get_frame_area()) else {
+                    return;
+                };
+                let point = area.clamped_frame_point_at(start_x + offset_x, start_y + offset_y);
+                if let Some(shape) = imp.drawing_preview.borrow_mut().as_mut() {
+                    match shape {
+                        Shape::Arrow { end, .. } => *end = point,
+                        Shape::Circle { edge, .. } => *edge = point,
+                        Shape::Text { .. } => {}
+                    }
+                }
+                imp.drawing_area.queue_draw();
+            }
+        ));
+
+        //Keeps the dragged out shape, or asks for the text to place where the text tool was clicked
+        drag.connect_drag_end(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |gesture, offset_x, offset_y| {
+                let imp = this.imp();
+                let preview = imp.drawing_preview.take();
+                imp.drawing_area.queue_draw();
+                let Some((start_x, start_y)) = gesture.start_point() else {
+                    return;
+                };
+                if imp.drawing_tool.get() == DrawingTool::Text && !this.is_playing() {
+                    if let Some(point) = this.get_frame_area().and_then(|area| area.frame_point_at(start_x, start_y)) {
+                        this.ask_drawing_text(start_x, start_y, point);
+                    }
+                } else if let Some(shape) = preview {
+                    //A click without dragging draws nothing
+                    if offset_x.hypot(offset_y) >= 3.0 {
+                        this.add_drawing_shape(shape);
+                    }
+                }
+            }
+        ));
+        imp.drawing_area.add_controller(drag);
+
+        imp.clear_drawing_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                let imp = this.imp();
+                let Some(position) = this.get_position() else {
+                    return;
+                };
+                if imp.drawings.borrow_mut().clear_at(position) {
+                    imp.drawing_area.queue_draw();
+                }
+            }
+        ));
+    }
+
+    //Gets where the video frame is shown inside the drawing area
+    fn get_frame_area(&self) -> Option<FrameArea> {
+        let imp = self.imp();
+        let bounds = imp.picture.compute_bounds(&*imp.drawing_area)?;
+        let (mut x, mut y) = (bounds.x() as f64, bounds.y() as f64);
+        let (mut width, mut height) = (bounds.width() as f64, bounds.height() as f64);
+        //The picture keeps the frame's aspect ratio, leaving bars at the sides or at the top and bottom
+        let ratio = imp.picture.paintable()?.intrinsic_aspect_ratio();
+        if ratio > 0.0 && height > 0.0 {
+            if width / height > ratio {
+                let fitted_width = height * ratio;
+                x += (width - fitted_width) / 2.0;
+                width = fitted_width;
+            } else {
+                let fitted_height = width / ratio;
+                y += (height - fitted_height) / 2.0;
+                height = fitted_height;
+            }
+        }
+        Some(FrameArea { x, y, width, height })
+    }
+
+    //Adds a shape to the drawing of the current frame
+    fn add_drawing_shape(&self, shape: Shape) {
+        let imp = self.imp();
+        let Some(position) = self.get_position() else {
+            log_warn!("Failed to get position trying to draw on the frame");
+            return;
+        };
+        let hold = (imp.drawing_hold_spin.value() * 1_000_000_000.0) as u64;
+        imp.drawings.borrow_mut().add_shape(position, hold, shape);
+        imp.drawing_position.set(position);
+        imp.drawing_area.queue_draw();
+    }
+
+    //Asks for the text to draw at a point
+    //Inputs: position in the drawing area the popover points at, point on the frame the text starts at
+    fn ask_drawing_text(&self, x: f64, y: f64, point: FramePoint) {
+        let imp = self.imp();
+        let entry = gtk::Entry::new();
+        entry.set_placeholder_text(Some("Text to draw"));
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&entry));
+        popover.set_parent(&*imp.drawing_area);
+        popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+        entry.connect_activate(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[weak] popover,
+            move |entry| {
+                let text = entry.text().trim().to_string();
+                if !text.is_empty() {
+                    this.add_drawing_shape(Shape::Text { position: point, text });
+                }
+                popover.popdown();
+            }
+        ));
+        //Removed once closed, a new one is made for every text
+        popover.connect_closed(|popover| {
+            glib::idle_add_local_once(glib::clone!(
+                #[weak] popover,
+                move || popover.unparent()
+            ));
+        });
+        popover.popup();
+    }
+
+    //Redraws the drawings when the shown frame changes
+    fn update_drawing_position(&self, position: u64) {
+        let imp = self.imp();
+        if imp.drawing_position.replace(position) != position && !imp.drawings.borrow().is_empty() {
+            imp.drawing_area.queue_draw();
+        }
+    }
+
+    //Hides the pipeline message bar
+    fn hide_message(&self) {
+        let imp = self.imp();
+        imp.message_revealer.set_reveal_child(false);
+    }
+
+    //Reacts to errors, warnings and the end of playback reported by the pipeline
+    fn handle_pipeline_event(&self, event: PipelineEvent) {
+        let imp = self.imp();
+        let showing_error = imp.message_revealer.reveals_child() && imp.message_box.has_css_class("error");
+        match event {
+            PipelineEvent::Error { message, .. } => {
+                //Keeps the install hint of a missing plugin reported just before the error
+                let hint = imp.message_hint.is_visible().then(|| imp.message_hint.label().to_string());
+                self.show_message("error", &message, hint.as_deref());
+                *imp.state.borrow_mut() = Some(gstreamer::State::Paused);
+            }
+            PipelineEvent::Warning { message, .. } => {
+                if !showing_error {
+                    self.show_message("warning", &message, None);
+                }
+            }
+            PipelineEvent::MissingPlugin { name } => {
+                self.show_message("error", &format!("Missing plugin: {name}"), Some(&missing_plugin_hint(&name)));
+            }
+            PipelineEvent::EndOfStream => {
+                if let Ok(pipeline) = imp.gstreamer_manager.lock() {
+                    if let Err(e) = pipeline.pause_video() {
+                        drop(pipeline);
+                        self.report_error(&e);
+                    }
+                }
+                *imp.state.borrow_mut() = Some(gstreamer::State::Paused);
+            }
+            PipelineEvent::ClampEndReached => {
+                *imp.state.borrow_mut() = Some(gstreamer::State::Paused);
+                self.emit_by_name::<()>("clamp-end-reached", &[&self.get_id()]);
+            }
+        }
+    }
+
+    //Starts playing the proxy of the loaded file, waiting for it to be generated if needed
+    fn start_proxy(&self) {
+        let imp = self.imp();
+        let path = match imp.source_path.borrow().clone() {
+            Some(p) => p,
+            None => return,
+        };
+        let proxy = imp.proxy.borrow().clone().unwrap_or_else(|| {
+            let proxy = generate_proxy(&path);
+            *imp.proxy.borrow_mut() = Some(proxy.clone());
+            proxy
+        });
+        if imp.proxy_timeout.borrow().is_some() {
+            return;
+        }
+
+        //Shows the transcode progress until the proxy is ready
+        imp.proxy_progress.set_visible(true);
+        let source_id = timeout_add_local(Duration::from_millis(200), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                let imp = this.imp();
+                let (progress, complete, ready, error) = {
+                    let proxy = proxy.lock().unwrap();
+                    (proxy.progress, proxy.complete, proxy.is_ready(), proxy.error.clone())
+                };
+                imp.proxy_progress.set_fraction(progress);
+                imp.proxy_progress.set_text(Some(&format!("Proxy {:.0}%", progress * 100.0)));
+                if !complete {
+                    return glib::ControlFlow::Continue;
+                }
+
+                imp.proxy_progress.set_visible(false);
+                imp.proxy_timeout.borrow_mut().take();
+                if let Some(e) = error {
+                    log_warn!("Proxy unavailable: {e}");
+                    this.show_message("warning", &format!("Proxy unavailable: {e}"), None);
+                    imp.proxy.borrow_mut().take();
+                    imp.proxy_button.set_active(false);
+                } else if ready && imp.proxy_button.is_active() {
+                    this.use_proxy();
+                }
+                glib::ControlFlow::Break
+            }
+        ));
+        *imp.proxy_timeout.borrow_mut() = Some(source_id);
+    }
+
+    //Plays the generated proxy in place of the original video
+    fn use_proxy(&self) {
+        let imp = self.imp();
+        let proxy_path = match imp.proxy.borrow().as_ref() {
+            Some(proxy) => proxy.lock().unwrap().path.to_string_lossy().to_string(),
+            None => return,
+        };
+        if imp.shared_control.get() {
+            self.show_message("warning", "The proxy will be used once shared control is released", None);
+            return;
+        }
+        if let Ok(pipeline) = imp.gstreamer_manager.lock() {
+            match pipeline.set_source(&proxy_path) {
+                Ok(()) => imp.using_proxy.set(true),
+                Err(e) => {
+                    log_error!("Failed to switch to proxy: {e}");
+                    self.show_message("error", &format!("Failed to switch to proxy: {}", e.message()), None);
+                }
+            }
+        }
+    }
+
+    //Plays the original video again
+    fn use_original(&self) {
+        let imp = self.imp();
+        let path = match imp.source_path.borrow().clone() {
+            Some(p) => p,
+            None => return,
+        };
+        if imp.shared_control.get() {
+            return;
+        }
+        if let Ok(pipeline) = imp.gstreamer_manager.lock() {
+            match pipeline.set_source(&path) {
+                Ok(()) => imp.using_proxy.set(false),
+                Err(e) => {
+                    log_error!("Failed to switch to original video: {e}");
+                    self.show_message("error", &format!("Failed to switch to original video: {}", e.message()), None);
+                }
+            }
+        }
+    }
+
+    //Stops using and generating the proxy of the previous file
+    fn reset_proxy(&self) {
+        let imp = self.imp();
+        if let Some(timeout) = imp.proxy_timeout.borrow_mut().take() {
+            timeout.remove();
+        }
+        if let Some(proxy) = imp.proxy.borrow_mut().take() {
+            proxy.lock().unwrap().cancelled = true;
+        }
+        imp.using_proxy.set(false);
+        imp.proxy_progress.set_visible(false);
+        imp.proxy_button.set_active(false);
+    }
+
+    //Shows a frame from the frame cache until the pipeline has decoded it again
+    //Called while the pipeline is locked, so the pipeline is only checked from the timeout
+    fn show_cached_frame(&self, sample: &gstreamer::Sample) {
+        let imp = self.imp();
+        let texture = match sample_to_texture(sample) {
+            Some(t) => t,
+            None => return,
+        };
+        imp.picture.set_paintable(Some(&texture));
+
+        if let Some(timeout) = imp.cached_frame_timeout.borrow_mut().take() {
+            timeout.remove();
+        }
+        let started = Instant::now();
+        let source_id = timeout_add_local(Duration::from_millis(15), glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::ControlFlow::Break,
+            move || {
+                let imp = this.imp();
+                let pipeline = match imp.gstreamer_manager.try_lock() {
+                    Ok(p) => p,
+                    Err(_) => return glib::ControlFlow::Continue,
+                };
+                //Switches back to the sink once it shows the same frame, or gives up waiting after a while
+                if pipeline.is_showing_cached_frame() && started.elapsed() < Duration::from_secs(2) {
+                    return glib::ControlFlow::Continue;
+                }
+                imp.picture.set_paintable(Some(&pipeline.get_paintable()));
+                imp.cached_frame_timeout.borrow_mut().take();
+                glib::ControlFlow::Break
+            }
+        ));
+        *imp.cached_frame_timeout.borrow_mut() = Some(source_id);
+    }
+
+    //Gets the start time and number of the frame shown at a time of the loaded file
+    pub fn snap_to_frame(&self, time: u64) -> Option<(u64, u64)> {
+        let imp = self.imp();
+        let pipeline = imp.gstreamer_manager.lock().ok()?;
+        let snapped = pipeline.snap_to_frame(time)?;
+        let frame = pipeline.frame_number_at(snapped)?;
+        Some((snapped, frame))
+    }
+
+    //Gets the hover preview thumbnails of the loaded file
+    pub fn get_thumbnails(&self) -> Option<Arc<Mutex<ThumbnailData>>> {
+        let imp = self.imp();
+        imp.thumbnails.borrow().clone()
+    }
+
     //Cleans up the video player before disposal
     pub fn cleanup(&self) {
         let imp = self.imp();
         imp.cleanup();
-        println!("pipeline cleanup function");
+        log_debug!("Cleaning up video player pipeline");
+    }
+
+    //Gets the video player from a video container child
+    //Detached video players leave a placeholder in the container that holds a reference to the video player
+    pub fn from_flowbox_child(child: &impl IsA<gtk::Widget>) -> Option<VideoPlayer> {
+        let fb_child = child.upcast_ref::<gtk::Widget>().downcast_ref::<FlowBoxChild>()?;
+        let content = fb_child.child()?;
+        if let Some(video_player) = content.downcast_ref::<VideoPlayer>() {
+            return Some(video_player.clone());
+        }
+        get_data::<VideoPlayer>(&content, "detached_video_player").map(|video_player| unsafe { video_player.as_ref() }.clone())
+    }
+
+    //Gets the video container child the video player belongs to, even while detached
+    pub fn flowbox_child(&self) -> Option<FlowBoxChild> {
+        let imp = self.imp();
+        if let Some(slot) = imp.flowbox_slot.borrow().as_ref().and_then(|weak| weak.upgrade()) {
+            return Some(slot);
+        }
+        self.parent().and_downcast::<FlowBoxChild>()
+    }
+
+    //Checks if the video player is currently in its own window
+    pub fn is_detached(&self) -> bool {
+        let imp = self.imp();
+        imp.detached_window.borrow().is_some()
+    }
+
+    //Moves the video player into its own window leaving a placeholder in the video container
+    pub fn detach(&self) {
+        let imp = self.imp();
+        if self.is_detached() {
+            return;
+        }
+        let fb_child = match self.parent().and_downcast::<FlowBoxChild>() {
+            Some(c) => c,
+            None => {
+                log_warn!("Can't detach video player. Video player is not in the video container");
+                return;
+            }
+        };
+
+        //Placeholder keeps the video players spot in the container
+        let placeholder = gtk::Box::new(gtk::Orientation::Vertical, 10);
+        placeholder.set_valign(gtk::Align::Center);
+        placeholder.set_halign(gtk::Align::Center);
+        let placeholder_label = gtk::Label::new(Some(&format!("Detached: {}", imp.text_view.label())));
+        placeholder_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+        let reattach_button = gtk::Button::with_label("Reattach");
+        reattach_button.add_css_class("video-player-control-button");
+        reattach_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = this)] self,
+            move |_| {
+                this.attach();
+            }
+        ));
+        placeholder.append(&placeholder_label);
+        placeholder.append(&reattach_button);
+        store_data(&placeholder, "detached_video_player", self.clone());
+
+        fb_child.set_child(Some(&placeholder));
+        imp.flowbox_slot.borrow_mut().replace(fb_child.downgrade());
+
+        let window = gtk::Window::builder()
+            .title(imp.text_view.label().as_str())
+            .default_width(960)
+            .default_height(720)
+            .build();
+        window.set_child(Some(self));
+
+        //Closing the window returns the video player to the main window
+        window.connect_close_request(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::Propagation::Proceed,
+            move |_| {
+                this.return_to_slot();
+                glib::Propagation::Proceed
+            }
+        ));
+
+        //Escape leaves fullscreen
+        let key_controller = gtk::EventControllerKey::new();
+        key_controller.connect_key_pressed(glib::clone!(
+            #[weak(rename_to = this)] self,
+            #[upgrade_or] glib::Propagation::Proceed,
+            move |_, key, _, _| {
+                if key == gtk::gdk::Key::Escape && this.is_fullscreen() {
+                    this.toggle_fullscreen();
+                    return glib::Propagation::Stop;
+                }
+                glib::Propagation::Proceed
+            }
+        ));
+        window.add_controller(key_controller);
+
+        imp.detach_button.set_label("Attach");
+        imp.detached_window.borrow_mut().replace(window.clone());
+        window.present();
+    }
+
+    //Returns a detached video player to its spot in the video container and closes its window
+    pub fn attach(&self) {
+        let imp = self.imp();
+        let window = match imp.detached_window.borrow().clone() {
+            Some(w) => w,
+            None => return,
+        };
+        self.return_to_slot();
+        window.destroy();
+    }
+
+    //Moves the video player from its window back into the video container
+    fn return_to_slot(&self) {
+        let imp = self.imp();
+        let window = match imp.detached_window.borrow_mut().take() {
+            Some(w) => w,
+            None => return,
+        };
+        window.set_child(None::<&gtk::Widget>);
+        imp.detached_for_fullscreen.set(false);
+        imp.detach_button.set_label("Detach");
+        imp.fullscreen_button.set_label("Fullscreen");
+
+        let slot = imp.flowbox_slot.borrow_mut().take().and_then(|weak| weak.upgrade());
+        match slot {
+            Some(fb_child) => fb_child.set_child(Some(self)),
+            None => log_warn!("Video container spot for detached video player no longer exists"),
+        }
+    }
+
+    //Checks if the video player is currently fullscreen
+    pub fn is_fullscreen(&self) -> bool {
+        let imp = self.imp();
+        imp.detached_window.borrow().as_ref().map_or(false, |window| window.is_fullscreen())
+    }
+
+    //Toggles fullscreen. Attached video players are detached while fullscreen and returned afterwards
+    pub fn toggle_fullscreen(&self) {
+        let imp = self.imp();
+        if self.is_fullscreen() {
+            if let Some(window) = imp.detached_window.borrow().as_ref() {
+                window.unfullscreen();
+            }
+            imp.fullscreen_button.set_label("Fullscreen");
+            if imp.detached_for_fullscreen.get() {
+                self.attach();
+            }
+            return;
+        }
+
+        if !self.is_detached() {
+            self.detach();
+            if !self.is_detached() {
+                return;
+            }
+            imp.detached_for_fullscreen.set(true);
+        }
+        if let Some(window) = imp.detached_window.borrow().as_ref() {
+            window.fullscreen();
+        }
+        imp.fullscreen_button.set_label("Exit Fullscreen");
+    }
+
+    //Sets the audio volume of the video player (0.0 - 1.0)
+    pub fn set_volume(&self, volume: f64) {
+        self.set_property("volume", volume.clamp(0.0, 1.0));
+    }
+
+    //Gets the audio volume of the video player
+    pub fn get_volume(&self) -> f64 {
+        self.property::<f64>("volume")
+    }
+
+    //Mutes or unmutes the video player audio
+    pub fn set_muted(&self, muted: bool) {
+        self.set_property("muted", muted);
+    }
+
+    //Checks if the video player audio is muted
+    pub fn is_muted(&self) -> bool {
+        self.property::<bool>("muted")
+    }
+
+    //Gets the path of the loaded video file
+    pub fn get_source_path(&self) -> Option<String> {
+        self.imp().source_path.borrow().clone()
+    }
+
+    //Checks if the video player is playing
+    pub fn is_playing(&self) -> bool {
+        *self.imp().state.borrow() == Some(gstreamer::State::Playing)
+    }
+
+    //Plays or pauses the video the same way as the play button
+    pub fn toggle_play(&self) {
+        self.imp().play_button.emit_clicked();
+    }
+
+    //Gets the position of the video in nanoseconds
+    pub fn get_position(&self) -> Option<u64> {
+        let pipeline = self.pipeline().upgrade()?;
+        let position = pipeline.lock().unwrap().get_position();
+        position.map(|position| position.nseconds())
+    }
+
+    //Moves the video to a position
+    //Input: position in nanoseconds
+    pub fn seek(&self, position: u64) -> Result<(), Error> {
+        let pipeline = self.pipeline().upgrade()
+            .ok_or_else(|| Error::Pipeline(format!("Video {} has no pipeline", self.get_id())))?;
+        let result = pipeline.lock().unwrap().seek_position(ClockTime::from_nseconds(position));
+        result
     }
 }
+
+//Creates a texture from a decoded sample
+fn sample_to_texture(sample: &gstreamer::Sample) -> Option<gtk::gdk::Texture> {
+    //Cached frames keep the decoder's format, only the frame being shown is converted
+    let rgba_caps = gstreamer::Caps::builder("video/x-raw").field("format", "RGBA").build();
+    let sample = match gstreamer_video::convert_sample(sample, &rgba_caps, gstreamer::ClockTime::from_seconds(1)) {
+        Ok(sample) => sample,
+        Err(e) => {
+            log_warn!("Failed to convert cached frame: {e}");
+            return None;
+        }
+    };
+    let info = gstreamer_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+    let buffer = sample.buffer()?;
+    let map = buffer.map_readable().ok()?;
+    let bytes = glib::Bytes::from(map.as_slice());
+    Some(gtk::gdk::MemoryTexture::new(
+        info.width() as i32,
+        info.height() as i32,
+        gtk::gdk::MemoryFormat::R8g8b8a8,
+        &bytes,
+        info.stride()[0] as usize,
+    ).upcast())
+}